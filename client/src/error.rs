@@ -0,0 +1,92 @@
+//! Errors of the client: the problems sent back by the server,
+//! the typed bodies of some refusals, and the transport errors.
+
+use reqwest;
+
+use std::error;
+use std::fmt;
+
+use models::{Candidate, Language, Problem, Sentence};
+
+#[derive(Debug)]
+pub enum Error {
+    /// the server refused the request with an `application/problem+json` body
+    Problem(Problem),
+    /// another sentence already has this content in this language variant
+    Conflict(Sentence),
+    /// the sentence is almost certainly written in this other language
+    LanguageMismatch(Candidate),
+    /// the language still has sentences and `cascade` has not been asked
+    LanguageInUse(Language),
+    /// the server answered with a status this request cannot get
+    UnexpectedStatus(u16),
+    /// a successful response lacks a header or has a malformed one
+    InvalidResponse(String),
+    /// the request could not be sent or the response could not be read
+    Http(reqwest::Error),
+}
+
+impl Error {
+
+    /// Returns the HTTP status of the refusal, None if there was no response.
+    pub fn status(&self) -> Option<u16> {
+        match *self {
+            Error::Problem(ref problem) => Some(problem.status),
+            Error::Conflict(_) | Error::LanguageInUse(_) => Some(409),
+            Error::LanguageMismatch(_) => Some(400),
+            Error::UnexpectedStatus(status) => Some(status),
+            Error::InvalidResponse(_) | Error::Http(_) => None,
+        }
+    }
+
+    /// Returns the problem code (see `doc/openapi.json`), if the server sent one.
+    pub fn code(&self) -> Option<&str> {
+        match *self {
+            Error::Problem(ref problem) => Some(&problem.code),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Error {
+        Error::Http(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Problem(ref problem) => match problem.detail {
+                Some(ref detail) => write!(formatter, "{}: {}", problem.code, detail),
+                None => write!(formatter, "{}", problem.code),
+            },
+            Error::Conflict(ref sentence) => write!(
+                formatter,
+                "conflicts with sentence {}",
+                sentence.id.map_or(String::new(), |id| id.to_string()),
+            ),
+            Error::LanguageMismatch(ref candidate) => write!(
+                formatter,
+                "the text looks written in {} (score {:.3})",
+                candidate.iso639_3,
+                candidate.score,
+            ),
+            Error::LanguageInUse(ref language) => write!(
+                formatter,
+                "language {} still has {} sentences",
+                language.iso639_3,
+                language.sentences,
+            ),
+            Error::UnexpectedStatus(status) => write!(formatter, "unexpected status {}", status),
+            Error::InvalidResponse(ref reason) => write!(formatter, "invalid response: {}", reason),
+            Error::Http(ref error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        "sentence aligner client error"
+    }
+}
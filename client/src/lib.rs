@@ -0,0 +1,731 @@
+//! Typed client of the sentence aligner HTTP API (see `doc/openapi.json`).
+//!
+//! Every endpoint has its method, taking and returning the models of
+//! `sentence-aligner-core`. The refusals of the server are returned as
+//! `Error`: a problem, or the typed body some refusals come with.
+
+extern crate reqwest;
+extern crate serde_json;
+extern crate uuid;
+extern crate sentence_aligner_core;
+
+pub mod error;
+
+pub use sentence_aligner_core::models;
+pub use error::Error;
+
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use reqwest::header::{ContentType, Headers};
+use serde_json::Value;
+use uuid::Uuid;
+
+use models::{
+    ApiKey,
+    Candidate,
+    ContentCollision,
+    Contributions,
+    DeletionReport,
+    DuplicateGroup,
+    Language,
+    LanguageDeletionReport,
+    LanguageMergeReport,
+    NewApiKey,
+    NewReview,
+    Problem,
+    RefreshReport,
+    Review,
+    Revision,
+    Role,
+    Sentence,
+    SentenceMergeReport,
+    User,
+};
+use sentence_aligner_core::structure;
+
+/// Header telling which language was detected instead of the declared one.
+const WARNING_HEADER: &str = "X-Detected-Language";
+
+/// A created sentence.
+#[derive(Debug, Clone)]
+pub struct Created {
+    pub id: Uuid,
+    /// the language the text looks written in, if it is not the declared one
+    pub detected_language: Option<Candidate>,
+}
+
+/// A resource with its entity tag, to be sent back
+/// in `If-Match` or `If-None-Match`.
+#[derive(Debug, Clone)]
+pub struct Versioned<T> {
+    pub value: T,
+    pub etag: String,
+}
+
+/// What a modification of a sentence has done.
+#[derive(Debug, Clone)]
+pub struct Edit {
+    /// the entity tag of the new version
+    pub etag: String,
+    /// the `X-Structure-Status` of a text edit: `absent`, `reprojected`
+    /// or `invalidated`, None if the text has not changed
+    pub structure_status: Option<String>,
+}
+
+/// Filters of the sentence list, all optional.
+#[derive(Debug, Clone, Default)]
+pub struct SentenceFilters {
+    /// only the sentences added after this one
+    pub starting_after_id: Option<Uuid>,
+    /// ISO 15924 script code
+    pub script: Option<String>,
+    /// ISO 3166-1 alpha-2 or UN M.49 region code
+    pub region: Option<String>,
+    /// the greatest number of sentences to return,
+    /// the configured default page size if None
+    pub limit: Option<i64>,
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl Client {
+
+    /// Creates a client of the server at the given URL.
+    ///
+    /// Args:
+    ///
+    /// `base_url` - the URL of the server, e.g. `http://localhost:8000`
+    pub fn new(base_url: &str) -> Client {
+        Client {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_right_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Returns the same client, authenticated by an API key
+    /// sent in the `Authorization` header, its user being
+    /// the author of the changes.
+    pub fn with_api_key(mut self, api_key: &str) -> Client {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Creates a sentence.
+    ///
+    /// Args:
+    ///
+    /// `sentence` - the sentence, its id being generated if None
+    ///
+    /// Returns:
+    ///
+    /// the id of the sentence, `Error::Conflict` if the content is already
+    /// used in this language variant, `Error::LanguageMismatch` if the text
+    /// is almost certainly written in another language
+    pub fn create_sentence(&self, sentence: &Sentence) -> Result<Created, Error> {
+
+        let response = send(self.request(Method::Post, "/sentences").json(sentence))?;
+
+        Ok(Created {
+            id: location(&response)?.parse().map_err(|_| {
+                Error::InvalidResponse("malformed Location header".to_string())
+            })?,
+            detected_language: detected_language(&response),
+        })
+    }
+
+    /// Lists the sentences, by page, in the order they have been added.
+    pub fn get_sentences(&self, filters: &SentenceFilters) -> Result<Vec<Sentence>, Error> {
+
+        let mut query: Vec<(&str, String)> = Vec::new();
+        if let Some(starting_after_id) = filters.starting_after_id {
+            query.push(("starting_after_id", starting_after_id.to_string()));
+        }
+        if let Some(ref script) = filters.script {
+            query.push(("script", script.clone()));
+        }
+        if let Some(ref region) = filters.region {
+            query.push(("region", region.clone()));
+        }
+        if let Some(limit) = filters.limit {
+            query.push(("limit", limit.to_string()));
+        }
+
+        let mut request = self.request(Method::Get, "/sentences");
+        if !query.is_empty() {
+            request.query(&query);
+        }
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Returns a sentence with its entity tag.
+    pub fn get_sentence(&self, id: &Uuid) -> Result<Versioned<Sentence>, Error> {
+
+        let mut response = send(&mut self.request(Method::Get, &format!("/sentences/{}", id)))?;
+
+        Ok(Versioned {
+            etag: etag(&response)?,
+            value: response.json()?,
+        })
+    }
+
+    /// Returns a sentence unless it is still at the given version.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence
+    /// `etag` - the entity tag of the version already known
+    ///
+    /// Returns:
+    ///
+    /// the sentence, None if it has not changed
+    pub fn get_sentence_if_none_match(
+        &self,
+        id: &Uuid,
+        etag: &str,
+    ) -> Result<Option<Versioned<Sentence>>, Error> {
+
+        let mut request = self.request(Method::Get, &format!("/sentences/{}", id));
+        request.headers(condition("If-None-Match", Some(etag)));
+
+        let mut response = send(&mut request)?;
+
+        if response.status() == StatusCode::NotModified {
+            return Ok(None);
+        }
+
+        Ok(Some(Versioned {
+            etag: self::etag(&response)?,
+            value: response.json()?,
+        }))
+    }
+
+    /// Replaces the text of a sentence, its structure being
+    /// reprojected on the new text or removed.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence
+    /// `text` - the new text
+    /// `if_match` - the entity tag the sentence must still have, if any
+    pub fn edit_sentence_text(
+        &self,
+        id: &Uuid,
+        text: &str,
+        if_match: Option<&str>,
+    ) -> Result<Edit, Error> {
+
+        let mut request = self.request(Method::Put, &format!("/sentences/{}/text", id));
+        request
+            .header(ContentType::plaintext())
+            .headers(condition("If-Match", if_match))
+            .body(text.to_string());
+
+        edit(send(&mut request)?)
+    }
+
+    /// Replaces the structure of a sentence, its text being unchanged.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence
+    /// `structure` - the XML structure of the text
+    /// `if_match` - the entity tag the sentence must still have, if any
+    pub fn edit_sentence_structure(
+        &self,
+        id: &Uuid,
+        structure: &str,
+        if_match: Option<&str>,
+    ) -> Result<Edit, Error> {
+
+        let mut request = self.request(Method::Put, &format!("/sentences/{}/structure", id));
+        request
+            .header(ContentType::xml())
+            .headers(condition("If-Match", if_match))
+            .body(structure.to_string());
+
+        edit(send(&mut request)?)
+    }
+
+    /// Moves a sentence to another language variant.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence
+    /// `language` - an ISO 639 code or a BCP 47 tag, whose script
+    ///              and region replace the ones of the sentence
+    /// `if_match` - the entity tag the sentence must still have, if any
+    pub fn edit_sentence_language(
+        &self,
+        id: &Uuid,
+        language: &str,
+        if_match: Option<&str>,
+    ) -> Result<Edit, Error> {
+
+        let mut request = self.request(Method::Put, &format!("/sentences/{}/language", id));
+        request
+            .header(ContentType::plaintext())
+            .headers(condition("If-Match", if_match))
+            .body(language.to_string());
+
+        edit(send(&mut request)?)
+    }
+
+    /// Updates several fields of a sentence at once.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence
+    /// `patch` - a JSON merge patch of the sentence
+    /// `if_match` - the entity tag the sentence must still have, if any
+    pub fn patch_sentence(
+        &self,
+        id: &Uuid,
+        patch: &Value,
+        if_match: Option<&str>,
+    ) -> Result<Edit, Error> {
+
+        let mut headers = condition("If-Match", if_match);
+        headers.set_raw("Content-Type", "application/merge-patch+json");
+
+        let mut request = self.request(Method::Patch, &format!("/sentences/{}", id));
+        request
+            .headers(headers)
+            .body(patch.to_string());
+
+        edit(send(&mut request)?)
+    }
+
+    /// Deletes a sentence.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence
+    /// `purge` - true to remove it for good with its revisions,
+    ///           false to only hide it until it is restored
+    pub fn delete_sentence(&self, id: &Uuid, purge: bool) -> Result<DeletionReport, Error> {
+
+        let mut request = self.request(Method::Delete, &format!("/sentences/{}", id));
+        if purge {
+            request.query(&[("purge", "true")]);
+        }
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Restores a soft deleted sentence.
+    pub fn restore_sentence(&self, id: &Uuid) -> Result<(), Error> {
+
+        send(&mut self.request(Method::Post, &format!("/sentences/{}/restore", id)))?;
+
+        Ok(())
+    }
+
+    /// Merges a sentence into another one of the same language variant.
+    ///
+    /// Args:
+    ///
+    /// `id` - the sentence to remove
+    /// `target_id` - the sentence to keep
+    pub fn merge_sentence(&self, id: &Uuid, target_id: &Uuid) -> Result<SentenceMergeReport, Error> {
+
+        let path = format!("/sentences/{}/merge-into/{}", id, target_id);
+
+        Ok(send(&mut self.request(Method::Post, &path))?.json()?)
+    }
+
+    /// Returns the revisions of a sentence, the oldest first.
+    pub fn get_sentence_history(&self, id: &Uuid) -> Result<Vec<Revision>, Error> {
+
+        let path = format!("/sentences/{}/history", id);
+
+        Ok(send(&mut self.request(Method::Get, &path))?.json()?)
+    }
+
+    /// Undoes a revision of a sentence and all the ones made after it.
+    pub fn revert_sentence(&self, id: &Uuid, revision_id: i32) -> Result<(), Error> {
+
+        let path = format!("/sentences/{}/revert/{}", id, revision_id);
+        send(&mut self.request(Method::Post, &path))?;
+
+        Ok(())
+    }
+
+    /// Reviews a sentence, or its structure.
+    ///
+    /// Returns:
+    ///
+    /// the recorded review
+    pub fn review_sentence(&self, id: &Uuid, review: &NewReview) -> Result<Review, Error> {
+
+        let path = format!("/sentences/{}/reviews", id);
+
+        Ok(send(self.request(Method::Post, &path).json(review))?.json()?)
+    }
+
+    /// Returns the reviews of a sentence, the oldest first.
+    pub fn get_sentence_reviews(&self, id: &Uuid) -> Result<Vec<Review>, Error> {
+
+        let path = format!("/sentences/{}/reviews", id);
+
+        Ok(send(&mut self.request(Method::Get, &path))?.json()?)
+    }
+
+    /// Submits a draft sentence, and its structure, for review.
+    pub fn submit_sentence(&self, id: &Uuid) -> Result<(), Error> {
+
+        send(&mut self.request(Method::Post, &format!("/sentences/{}/submit", id)))?;
+
+        Ok(())
+    }
+
+    /// Lists the sentences pending review, the ones added first coming first.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - only the sentences of this language, if any
+    pub fn get_review_queue(&self, iso639_3: Option<&str>) -> Result<Vec<Sentence>, Error> {
+
+        let mut request = self.request(Method::Get, "/review/queue");
+        if let Some(iso639_3) = iso639_3 {
+            request.query(&[("lang", iso639_3)]);
+        }
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Creates a language.
+    ///
+    /// Args:
+    ///
+    /// `code` - an ISO 639-3, ISO 639-1, ISO 639-2/B or BCP 47 code
+    ///
+    /// Returns:
+    ///
+    /// the ISO 639-3 code the language is stored with
+    pub fn create_language(&self, code: &str) -> Result<String, Error> {
+
+        let mut request = self.request(Method::Post, "/languages");
+        request
+            .header(ContentType::plaintext())
+            .body(code.to_string());
+
+        location(&send(&mut request)?)
+    }
+
+    /// Lists the languages with their number of sentences.
+    pub fn get_languages(&self) -> Result<Vec<Language>, Error> {
+        Ok(send(&mut self.request(Method::Get, "/languages"))?.json()?)
+    }
+
+    /// Returns a language with its number of sentences.
+    pub fn get_language(&self, iso639_3: &str) -> Result<Language, Error> {
+
+        let path = format!("/languages/{}", iso639_3);
+
+        Ok(send(&mut self.request(Method::Get, &path))?.json()?)
+    }
+
+    /// Lists the sentences of a language, optionally of one script or region.
+    pub fn get_sentences_of_language(
+        &self,
+        iso639_3: &str,
+        script: Option<&str>,
+        region: Option<&str>,
+    ) -> Result<Vec<Sentence>, Error> {
+
+        let mut query: Vec<(&str, &str)> = Vec::new();
+        if let Some(script) = script {
+            query.push(("script", script));
+        }
+        if let Some(region) = region {
+            query.push(("region", region));
+        }
+
+        let mut request = self.request(Method::Get, &format!("/languages/{}/sentences", iso639_3));
+        if !query.is_empty() {
+            request.query(&query);
+        }
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Deletes a language.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the language
+    /// `cascade` - true to purge its sentences along with it
+    ///
+    /// Returns:
+    ///
+    /// what has been removed, `Error::LanguageInUse` if the language
+    /// still has sentences and `cascade` is false
+    pub fn delete_language(&self, iso639_3: &str, cascade: bool) -> Result<LanguageDeletionReport, Error> {
+
+        let mut request = self.request(Method::Delete, &format!("/languages/{}", iso639_3));
+        if cascade {
+            request.query(&[("cascade", "true")]);
+        }
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Gives a language another code, moving its sentences to it.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the language
+    /// `new_code` - an ISO 639-3, ISO 639-1, ISO 639-2/B or BCP 47 code
+    ///   not used by another language
+    ///
+    /// Returns:
+    ///
+    /// the language under its new ISO 639-3 code
+    pub fn rename_language(&self, iso639_3: &str, new_code: &str) -> Result<Language, Error> {
+
+        let mut request = self.request(Method::Put, &format!("/languages/{}", iso639_3));
+        request
+            .header(ContentType::plaintext())
+            .body(new_code.to_string());
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Moves all the sentences of a language to another one, then deletes it.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the language to remove
+    /// `target_iso639_3` - the language receiving the sentences
+    pub fn merge_language(&self, iso639_3: &str, target_iso639_3: &str) -> Result<LanguageMergeReport, Error> {
+
+        let path = format!("/languages/{}/merge-into/{}", iso639_3, target_iso639_3);
+
+        Ok(send(&mut self.request(Method::Post, &path))?.json()?)
+    }
+
+    /// Returns the languages a text is most likely written in, the most likely first.
+    pub fn detect_language(&self, text: &str) -> Result<Vec<Candidate>, Error> {
+
+        let mut request = self.request(Method::Post, "/detect-language");
+        request
+            .header(ContentType::plaintext())
+            .body(text.to_string());
+
+        Ok(send(&mut request)?.json()?)
+    }
+
+    /// Lists the sentences that would collide once canonicalised.
+    pub fn get_content_collisions(&self) -> Result<Vec<ContentCollision>, Error> {
+        Ok(send(&mut self.request(Method::Get, "/maintenance/content-collisions"))?.json()?)
+    }
+
+    /// Recomputes the near-duplicate groups.
+    pub fn refresh_near_duplicates(&self) -> Result<RefreshReport, Error> {
+        Ok(send(&mut self.request(Method::Post, "/maintenance/near-duplicates"))?.json()?)
+    }
+
+    /// Trains the language detector again from the stored sentences.
+    pub fn refresh_language_detection(&self) -> Result<(), Error> {
+        send(&mut self.request(Method::Post, "/maintenance/language-detection"))?;
+        Ok(())
+    }
+
+    /// Lists the near-duplicate groups of a language, as found by the last refresh.
+    pub fn get_near_duplicates(&self, iso639_3: &str) -> Result<Vec<DuplicateGroup>, Error> {
+
+        let path = format!("/languages/{}/near-duplicates", iso639_3);
+
+        Ok(send(&mut self.request(Method::Get, &path))?.json()?)
+    }
+
+    /// Creates an API key.
+    ///
+    /// Returns:
+    ///
+    /// the key, its secret `key` being only given this once
+    pub fn create_api_key(&self, name: &str, role: Role) -> Result<ApiKey, Error> {
+
+        let new_key = NewApiKey {
+            name: name.to_string(),
+            role: role,
+        };
+
+        Ok(send(self.request(Method::Post, "/api-keys").json(&new_key))?.json()?)
+    }
+
+    /// Lists the API keys, without their secret.
+    pub fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+        Ok(send(&mut self.request(Method::Get, "/api-keys"))?.json()?)
+    }
+
+    /// Revokes an API key.
+    pub fn revoke_api_key(&self, id: i32) -> Result<(), Error> {
+        send(&mut self.request(Method::Delete, &format!("/api-keys/{}", id)))?;
+        Ok(())
+    }
+
+    /// Lists the users.
+    pub fn get_users(&self) -> Result<Vec<User>, Error> {
+        Ok(send(&mut self.request(Method::Get, "/users"))?.json()?)
+    }
+
+    /// Returns a user.
+    pub fn get_user(&self, id: i32) -> Result<User, Error> {
+        Ok(send(&mut self.request(Method::Get, &format!("/users/{}", id)))?.json()?)
+    }
+
+    /// Returns the counts per language of the sentences added and
+    /// edits made by a user, with the latest of them.
+    pub fn get_user_contributions(&self, id: i32) -> Result<Contributions, Error> {
+        Ok(send(&mut self.request(Method::Get, &format!("/users/{}/contributions", id)))?.json()?)
+    }
+
+    /// Tells whether the server process is up.
+    pub fn health(&self) -> Result<(), Error> {
+        send(&mut self.request(Method::Get, "/health"))?;
+        Ok(())
+    }
+
+    /// Tells whether the server can handle requests,
+    /// its database being reachable and its schema up to date.
+    pub fn ready(&self) -> Result<(), Error> {
+        send(&mut self.request(Method::Get, "/ready"))?;
+        Ok(())
+    }
+
+    /// Returns the metrics of the server, in the Prometheus text format.
+    pub fn metrics(&self) -> Result<String, Error> {
+        Ok(send(&mut self.request(Method::Get, "/metrics"))?.text()?)
+    }
+
+    /// Starts a request on a path of the server.
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+
+        let mut request = self.http.request(method, &format!("{}{}", self.base_url, path));
+
+        if let Some(ref api_key) = self.api_key {
+            let mut headers = Headers::new();
+            headers.set_raw("Authorization", format!("Bearer {}", api_key));
+            request.headers(headers);
+        }
+
+        request
+    }
+}
+
+/// Sends a request, turning a refusal into an error.
+fn send(request: &mut RequestBuilder) -> Result<Response, Error> {
+
+    let response = request.send()?;
+
+    if response.status().is_success() || response.status() == StatusCode::NotModified {
+        return Ok(response);
+    }
+
+    Err(refusal(response))
+}
+
+/// Reads the error of a refused request: a problem, or one of the
+/// JSON bodies of the refusals that tell more than a problem could.
+fn refusal(mut response: Response) -> Error {
+
+    let status = response.status();
+
+    if has_content_type(&response, "application/problem+json") {
+        return match response.json::<Problem>() {
+            Ok(Problem { detected_language: Some(candidate), .. }) => {
+                Error::LanguageMismatch(candidate)
+            },
+            Ok(problem) => Error::Problem(problem),
+            Err(error) => Error::Http(error),
+        };
+    }
+
+    if !has_content_type(&response, "application/json") {
+        return Error::UnexpectedStatus(status.as_u16());
+    }
+
+    let body: Value = match response.json() {
+        Ok(body) => body,
+        Err(error) => return Error::Http(error),
+    };
+
+    // a conflicting sentence has a text, a language in use has none
+    let error = match status {
+        StatusCode::Conflict if body.get("text").is_some() => {
+            serde_json::from_value(body).map(Error::Conflict)
+        },
+        StatusCode::Conflict => serde_json::from_value(body).map(Error::LanguageInUse),
+        _ => return Error::UnexpectedStatus(status.as_u16()),
+    };
+
+    error.unwrap_or(Error::UnexpectedStatus(status.as_u16()))
+}
+
+/// Returns the value of a response header, if it is sent once.
+fn header(response: &Response, name: &str) -> Option<String> {
+    response.headers()
+        .get_raw(name)
+        .and_then(|raw| raw.one())
+        .map(|value| String::from_utf8_lossy(value).into_owned())
+}
+
+fn has_content_type(response: &Response, content_type: &str) -> bool {
+    header(response, "Content-Type").map_or(false, |value| value.starts_with(content_type))
+}
+
+/// Returns the last segment of the `Location` of a created resource.
+fn location(response: &Response) -> Result<String, Error> {
+
+    let location = match header(response, "Location") {
+        Some(location) => location,
+        None => return Err(Error::InvalidResponse("missing Location header".to_string())),
+    };
+
+    Ok(location.rsplit('/').next().unwrap_or("").to_string())
+}
+
+fn etag(response: &Response) -> Result<String, Error> {
+    header(response, "ETag").ok_or_else(|| Error::InvalidResponse("missing ETag header".to_string()))
+}
+
+/// Reads the outcome of a sentence modification.
+fn edit(response: Response) -> Result<Edit, Error> {
+    Ok(Edit {
+        etag: etag(&response)?,
+        structure_status: header(&response, structure::STATUS_HEADER),
+    })
+}
+
+/// Reads the `X-Detected-Language` warning of a created sentence,
+/// e.g. `fra; score=0.812`.
+fn detected_language(response: &Response) -> Option<Candidate> {
+
+    let warning = header(response, WARNING_HEADER)?;
+    let mut parts = warning.splitn(2, "; score=");
+
+    let iso639_3 = parts.next()?.to_string();
+    let score = parts.next()?.parse().ok()?;
+
+    Some(Candidate {
+        iso639_3: iso639_3,
+        score: score,
+    })
+}
+
+/// Returns the headers of a conditional request, none if there is no condition.
+fn condition(name: &str, etag: Option<&str>) -> Headers {
+
+    let mut headers = Headers::new();
+    if let Some(etag) = etag {
+        headers.set_raw(name.to_string(), etag.to_string());
+    }
+
+    headers
+}
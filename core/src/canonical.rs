@@ -0,0 +1,186 @@
+//! Canonical form of the sentence contents.
+//!
+//! Visually identical contents must be stored identically, otherwise
+//! they escape the uniqueness of a content per language variant.
+//! Each step can be disabled:
+//!
+//! * Unicode normalisation form C
+//! * any run of spaces, tabs, new lines or non-breaking spaces becomes one space
+//! * leading and trailing spaces are removed
+//! * typographic quotes become ASCII ones
+
+use unicode_normalization::UnicodeNormalization;
+
+use structure;
+
+pub struct Canonicalizer {
+    nfc: bool,
+    collapse_whitespace: bool,
+    trim: bool,
+    normalise_quotes: bool,
+}
+
+impl Canonicalizer {
+
+    /// Creates a canonicalisation with the given steps enabled.
+    ///
+    /// Args:
+    ///
+    /// `nfc` - Unicode normalisation form C
+    /// `collapse_whitespace` - runs of whitespace become one space
+    /// `trim` - leading and trailing whitespace are removed
+    /// `normalise_quotes` - typographic quotes become ASCII ones
+    pub fn new(
+        nfc: bool,
+        collapse_whitespace: bool,
+        trim: bool,
+        normalise_quotes: bool,
+    ) -> Canonicalizer {
+        Canonicalizer {
+            nfc: nfc,
+            collapse_whitespace: collapse_whitespace,
+            trim: trim,
+            normalise_quotes: normalise_quotes,
+        }
+    }
+
+    /// Returns the canonical form of a sentence content.
+    ///
+    /// Args:
+    ///
+    /// `text` - the content to canonicalise
+    pub fn text(&self, text: &str) -> String {
+
+        let mut text = if self.nfc {
+            text.nfc().collect::<String>()
+        } else {
+            text.to_string()
+        };
+
+        if self.collapse_whitespace {
+            let mut collapsed = String::with_capacity(text.len());
+            let mut previous_is_whitespace = false;
+            for character in text.chars() {
+                if character.is_whitespace() {
+                    if !previous_is_whitespace {
+                        collapsed.push(' ');
+                    }
+                    previous_is_whitespace = true;
+                } else {
+                    collapsed.push(character);
+                    previous_is_whitespace = false;
+                }
+            }
+            text = collapsed;
+        }
+
+        if self.trim {
+            text = text.trim().to_string();
+        }
+
+        if self.normalise_quotes {
+            text = text.chars()
+                .map(|character| match character {
+                    '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+                    '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+                    character => character,
+                })
+                .collect();
+        }
+
+        text
+    }
+
+    /// Canonicalises the text nodes of a structure the same way
+    /// as the content of its sentence, leaving the elements untouched.
+    ///
+    /// Args:
+    ///
+    /// `structure` - the XML structure
+    ///
+    /// Returns:
+    ///
+    /// the canonical structure, None if a change spans several text nodes
+    pub fn structure(&self, structure: &str) -> Option<String> {
+
+        let text = structure::extract_text(structure);
+        let canonical = self.text(&text);
+
+        if canonical == text {
+            return Some(structure.to_string());
+        }
+
+        structure::reproject(structure, &text, &canonical)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn canonicalizer() -> Canonicalizer {
+        Canonicalizer::new(true, true, true, true)
+    }
+
+    #[test]
+    fn test_text_of_empty_string() {
+
+        assert_eq!(canonicalizer().text(""), "");
+        assert_eq!(canonicalizer().text(" \t\n"), "");
+    }
+
+    #[test]
+    fn test_text_already_canonical_is_unchanged() {
+
+        assert_eq!(canonicalizer().text("Un café, s'il vous plaît."), "Un café, s'il vous plaît.");
+    }
+
+    #[test]
+    fn test_text_composes_nfd_into_nfc() {
+
+        let decomposed = "cafe\u{301}";
+
+        assert_eq!(canonicalizer().text(decomposed), "caf\u{e9}");
+        assert_eq!(Canonicalizer::new(false, true, true, true).text(decomposed), decomposed);
+    }
+
+    #[test]
+    fn test_text_collapses_and_trims_whitespace() {
+
+        assert_eq!(canonicalizer().text("  A \t\u{a0}cat\n\nsat.  "), "A cat sat.");
+        assert_eq!(Canonicalizer::new(true, false, true, true).text(" A  cat "), "A  cat");
+        assert_eq!(Canonicalizer::new(true, true, false, true).text(" A  cat "), " A cat ");
+    }
+
+    #[test]
+    fn test_text_normalises_quotes_if_asked() {
+
+        let quoted = "\u{201C}It\u{2019}s\u{201D}";
+
+        assert_eq!(canonicalizer().text(quoted), "\"It's\"");
+        assert_eq!(Canonicalizer::new(true, true, true, false).text(quoted), quoted);
+    }
+
+    #[test]
+    fn test_structure_canonicalises_text_nodes_only() {
+
+        assert_eq!(
+            canonicalizer().structure("<s><w>cafe\u{301}</w> <w>noir</w></s>"),
+            Some("<s><w>caf\u{e9}</w> <w>noir</w></s>".to_string()),
+        );
+        assert_eq!(
+            canonicalizer().structure("<s><w>A</w> <w>cat</w></s>"),
+            Some("<s><w>A</w> <w>cat</w></s>".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_structure_trimmed_across_nodes() {
+
+        assert_eq!(
+            canonicalizer().structure("<s><w>A</w> <w>cat </w></s>"),
+            Some("<s><w>A</w> <w>cat</w></s>".to_string()),
+        );
+    }
+}
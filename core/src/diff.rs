@@ -0,0 +1,198 @@
+//! Character level diff between two versions of a text.
+
+use std::cmp::max;
+
+/// One step of the edit script turning an old text into a new one.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operation {
+    /// the character is present in both texts
+    Equal(char),
+    /// the character is only present in the old text
+    Delete(char),
+    /// the character is only present in the new text
+    Insert(char),
+}
+
+/// Largest longest common subsequence table built, in cells;
+/// past this size, the changed span is reported as a single deletion
+/// followed by a single insertion.
+pub const MAX_TABLE_CELLS: usize = 1_000_000;
+
+/// Computes the edit script turning `old` into `new`, character by character.
+///
+/// The common prefix and suffix are stripped first so the longest common
+/// subsequence table is only built for the part that actually changed,
+/// which keeps it small for the usual sentence edits. When that part is
+/// still too large for the table (see `MAX_TABLE_CELLS`), it is deleted
+/// and inserted as a whole rather than diffed.
+///
+/// Args:
+///
+/// `old` - the text before the edit
+/// `new` - the text after the edit
+///
+/// Returns:
+///
+/// the list of operations, in text order
+pub fn diff_chars(old: &str, new: &str) -> Vec<Operation> {
+
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+
+    let prefix = old.iter()
+        .zip(new.iter())
+        .take_while(|&(old_char, new_char)| old_char == new_char)
+        .count();
+
+    let suffix = old[prefix..].iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|&(old_char, new_char)| old_char == new_char)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let old_length = old_middle.len();
+    let new_length = new_middle.len();
+
+    let mut operations = Vec::with_capacity(old.len() + new_length);
+    operations.extend(old[..prefix].iter().map(|&c| Operation::Equal(c)));
+
+    let cells = (old_length + 1).saturating_mul(new_length + 1);
+    if cells > MAX_TABLE_CELLS {
+        operations.extend(old_middle.iter().map(|&c| Operation::Delete(c)));
+        operations.extend(new_middle.iter().map(|&c| Operation::Insert(c)));
+        operations.extend(old[old.len() - suffix..].iter().map(|&c| Operation::Equal(c)));
+        return operations;
+    }
+
+    // lengths[i][j] is the length of the longest common subsequence
+    // of old_middle[i..] and new_middle[j..]
+    let mut lengths = vec![vec![0usize; new_length + 1]; old_length + 1];
+    for i in (0..old_length).rev() {
+        for j in (0..new_length).rev() {
+            lengths[i][j] = if old_middle[i] == new_middle[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                max(lengths[i + 1][j], lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_length && j < new_length {
+        if old_middle[i] == new_middle[j] {
+            operations.push(Operation::Equal(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            operations.push(Operation::Delete(old_middle[i]));
+            i += 1;
+        } else {
+            operations.push(Operation::Insert(new_middle[j]));
+            j += 1;
+        }
+    }
+    operations.extend(old_middle[i..].iter().map(|&c| Operation::Delete(c)));
+    operations.extend(new_middle[j..].iter().map(|&c| Operation::Insert(c)));
+
+    operations.extend(old[old.len() - suffix..].iter().map(|&c| Operation::Equal(c)));
+
+    operations
+}
+
+/// Renders an edit script as the new text where deleted parts are
+/// written `[-like this-]` and inserted ones `{+like this+}`,
+/// in the same way as `git diff --word-diff=plain`.
+///
+/// Args:
+///
+/// `operations` - the edit script, as returned by `diff_chars`
+///
+/// Returns:
+///
+/// the human readable diff
+pub fn render(operations: &[Operation]) -> String {
+
+    let mut rendered = String::new();
+    let mut deleted = String::new();
+    let mut inserted = String::new();
+
+    for operation in operations.iter().map(Some).chain(Some(None)) {
+        match operation {
+            Some(&Operation::Delete(character)) => deleted.push(character),
+            Some(&Operation::Insert(character)) => inserted.push(character),
+            _ => {
+                if !deleted.is_empty() {
+                    rendered += &format!("[-{}-]", deleted);
+                    deleted.clear();
+                }
+                if !inserted.is_empty() {
+                    rendered += &format!("{{+{}+}}", inserted);
+                    inserted.clear();
+                }
+                if let Some(&Operation::Equal(character)) = operation {
+                    rendered.push(character);
+                }
+            },
+        }
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_diff_chars_keeps_common_prefix_and_suffix() {
+
+        let operations = diff_chars("a cat", "a bat");
+
+        assert_eq!(render(&operations), "a [-c-]{+b+}at");
+    }
+
+    #[test]
+    fn test_diff_chars_above_table_limit_replaces_changed_part() {
+
+        let old = format!("<{}>", "a".repeat(2000));
+        let new = format!("<{}>", "b".repeat(2000));
+
+        let operations = diff_chars(&old, &new);
+
+        assert_eq!(operations.len(), 4002);
+        assert_eq!(operations[0], Operation::Equal('<'));
+        assert!(operations[1..2001].iter().all(|operation| *operation == Operation::Delete('a')));
+        assert!(operations[2001..4001].iter().all(|operation| *operation == Operation::Insert('b')));
+        assert_eq!(operations[4001], Operation::Equal('>'));
+    }
+
+    #[test]
+    fn test_diff_chars_of_empty_strings() {
+
+        assert!(diff_chars("", "").is_empty());
+        assert_eq!(render(&diff_chars("", "ab")), "{+ab+}");
+        assert_eq!(render(&diff_chars("ab", "")), "[-ab-]");
+    }
+
+    #[test]
+    fn test_diff_chars_of_identical_strings_keeps_everything() {
+
+        let operations = diff_chars("a cat", "a cat");
+
+        assert_eq!(operations.len(), 5);
+        assert!(operations.iter().all(|operation| match *operation {
+            Operation::Equal(_) => true,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn test_diff_chars_compares_characters_not_bytes() {
+
+        assert_eq!(render(&diff_chars("naïve", "naive")), "na[-ï-]{+i+}ve");
+    }
+}
@@ -0,0 +1,235 @@
+//! Embedded ISO 639-3 code table.
+//!
+//! The table comes from the SIL registration authority (`iso639_3.tab`),
+//! its macrolanguage column being filled from the active mappings of
+//! `iso-639-3-macrolanguages.tab`, from the same authority.
+
+use std::collections::HashMap;
+
+const CODE_TABLE: &str = include_str!("iso639_3.tab");
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Individual,
+    Macrolanguage,
+    Special,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageType {
+    Living,
+    Extinct,
+    Ancient,
+    Historical,
+    Constructed,
+    Special,
+}
+
+/// Reference information about one ISO 639-3 code.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LanguageInfo {
+    pub iso639_1: Option<String>,
+    pub name: String,
+    pub scope: Scope,
+    #[serde(rename = "type")]
+    pub language_type: LanguageType,
+    pub macrolanguage: Option<String>,
+}
+
+/// All the ISO 639-3 codes, with lookups from the other code sets.
+pub struct Registry {
+    languages: HashMap<String, LanguageInfo>,
+    iso639_1_codes: HashMap<String, String>,
+    iso639_2b_codes: HashMap<String, String>,
+}
+
+impl Registry {
+
+    /// Loads the embedded code table.
+    pub fn load() -> Registry {
+
+        let mut registry = Registry {
+            languages: HashMap::new(),
+            iso639_1_codes: HashMap::new(),
+            iso639_2b_codes: HashMap::new(),
+        };
+
+        // the first line holds the column names
+        for line in CODE_TABLE.lines().skip(1) {
+
+            let columns: Vec<&str> = line.split('\t').collect();
+            let code = columns[0].to_string();
+
+            if !columns[1].is_empty() && columns[1] != columns[0] {
+                registry.iso639_2b_codes.insert(columns[1].to_string(), code.clone());
+            }
+            if !columns[3].is_empty() {
+                registry.iso639_1_codes.insert(columns[3].to_string(), code.clone());
+            }
+
+            let scope = match columns[4] {
+                "M" => Scope::Macrolanguage,
+                "S" => Scope::Special,
+                _ => Scope::Individual,
+            };
+
+            let language_type = match columns[5] {
+                "E" => LanguageType::Extinct,
+                "A" => LanguageType::Ancient,
+                "H" => LanguageType::Historical,
+                "C" => LanguageType::Constructed,
+                "S" => LanguageType::Special,
+                _ => LanguageType::Living,
+            };
+
+            let info = LanguageInfo {
+                iso639_1: non_empty(columns[3]),
+                name: columns[6].to_string(),
+                scope: scope,
+                language_type: language_type,
+                macrolanguage: non_empty(columns[7]),
+            };
+
+            registry.languages.insert(code, info);
+        }
+
+        registry
+    }
+
+    /// Returns the reference information of an ISO 639-3 code.
+    pub fn get(&self, iso639_3: &str) -> Option<&LanguageInfo> {
+        self.languages.get(iso639_3)
+    }
+
+    /// Turns a language code into the matching ISO 639-3 code.
+    ///
+    /// Accepted inputs, case insensitive and surrounding spaces ignored:
+    ///
+    /// * an ISO 639-3 code (`fra`)
+    /// * an ISO 639-1 code (`fr`)
+    /// * an ISO 639-2/B code (`fre`)
+    /// * a BCP 47 tag (`fr-CA`, `zh-yue-HK`), only its language part is kept,
+    ///   an extended language subtag takes precedence over its macrolanguage
+    ///
+    /// Args:
+    ///
+    /// `code` - the code to normalise
+    ///
+    /// Returns:
+    ///
+    /// the ISO 639-3 code, None if the code is unknown
+    pub fn normalise(&self, code: &str) -> Option<String> {
+
+        let tag = code.trim().to_lowercase();
+        let mut subtags = tag.split(|c: char| c == '-' || c == '_');
+
+        let primary = subtags.next().unwrap_or("");
+        let iso639_3 = match primary.len() {
+            2 => self.iso639_1_codes.get(primary)?.clone(),
+            3 if self.languages.contains_key(primary) => primary.to_string(),
+            3 => self.iso639_2b_codes.get(primary)?.clone(),
+            _ => return None,
+        };
+
+        if let Some(extlang) = subtags.next() {
+            let is_member = self.get(extlang)
+                .and_then(|info| info.macrolanguage.as_ref())
+                .map_or(false, |macrolanguage| *macrolanguage == iso639_3);
+
+            if is_member {
+                return Some(extlang.to_string());
+            }
+        }
+
+        Some(iso639_3)
+    }
+}
+
+/// Returns None for the empty cells of the code table.
+fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_normalise_iso639_3_code() {
+
+        let registry = Registry::load();
+
+        assert_eq!(registry.normalise("fra"), Some("fra".to_string()));
+        assert_eq!(registry.normalise(" FRA "), Some("fra".to_string()));
+    }
+
+    #[test]
+    fn test_normalise_two_letter_code() {
+
+        let registry = Registry::load();
+
+        assert_eq!(registry.normalise("fr"), Some("fra".to_string()));
+        assert_eq!(registry.normalise("nb"), Some("nob".to_string()));
+        assert_eq!(registry.normalise("sh"), Some("hbs".to_string()));
+    }
+
+    #[test]
+    fn test_normalise_bibliographic_code() {
+
+        let registry = Registry::load();
+
+        assert_eq!(registry.normalise("fre"), Some("fra".to_string()));
+        assert_eq!(registry.normalise("ger"), Some("deu".to_string()));
+    }
+
+    #[test]
+    fn test_normalise_bcp47_tag() {
+
+        let registry = Registry::load();
+
+        assert_eq!(registry.normalise("fr-CA"), Some("fra".to_string()));
+        assert_eq!(registry.normalise("fr_CA"), Some("fra".to_string()));
+        assert_eq!(registry.normalise("sr-Latn-RS"), Some("srp".to_string()));
+        assert_eq!(registry.normalise("zh-yue-HK"), Some("yue".to_string()));
+        assert_eq!(registry.normalise("et-ekk"), Some("ekk".to_string()));
+        // not a member of the macrolanguage, only the language is kept
+        assert_eq!(registry.normalise("fr-yue"), Some("fra".to_string()));
+    }
+
+    #[test]
+    fn test_normalise_unknown_code() {
+
+        let registry = Registry::load();
+
+        assert_eq!(registry.normalise(""), None);
+        assert_eq!(registry.normalise("x"), None);
+        assert_eq!(registry.normalise("xx"), None);
+        assert_eq!(registry.normalise("xyz"), None);
+        assert_eq!(registry.normalise("fran"), None);
+    }
+
+    #[test]
+    fn test_individual_languages_have_their_macrolanguage() {
+
+        let registry = Registry::load();
+
+        let macrolanguage = |iso639_3: &str| {
+            registry.get(iso639_3).and_then(|info| info.macrolanguage.clone())
+        };
+
+        assert_eq!(macrolanguage("ekk"), Some("est".to_string()));
+        assert_eq!(macrolanguage("pes"), Some("fas".to_string()));
+        assert_eq!(macrolanguage("nob"), Some("nor".to_string()));
+        assert_eq!(macrolanguage("srp"), Some("hbs".to_string()));
+        assert_eq!(macrolanguage("cmn"), Some("zho".to_string()));
+        assert_eq!(macrolanguage("fra"), None);
+        assert_eq!(macrolanguage("nor"), None);
+    }
+}
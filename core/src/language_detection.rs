@@ -0,0 +1,301 @@
+//! Language identification with character trigrams.
+//!
+//! A naive Bayes model is trained from sentences of known languages,
+//! a language being only detected once it has a model.
+
+use std::collections::{HashMap, HashSet};
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Above this probability for another language, the declared one gets a warning.
+pub const WARNING_THRESHOLD: f64 = 0.5;
+
+/// Above this probability for another language, the declared one is rejected.
+pub const REJECTION_THRESHOLD: f64 = 0.999;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Candidate {
+    pub iso639_3: String,
+    /// probability of the text being in this language,
+    /// the scores of all the candidates sum up to 1
+    pub score: f64,
+}
+
+/// What the declared language of a text is worth.
+pub enum Verdict {
+    /// the declared language is the most likely one or nothing is known
+    Agrees,
+    /// another language is more likely
+    Disagrees(Candidate),
+    /// another language is almost certain
+    StronglyDisagrees(Candidate),
+}
+
+struct Profile {
+    trigrams: HashMap<String, u32>,
+    total: u32,
+}
+
+pub struct Detector {
+    profiles: HashMap<String, Profile>,
+    vocabulary: usize,
+}
+
+impl Detector {
+
+    /// Trains a model per language from a corpus.
+    ///
+    /// Args:
+    ///
+    /// `corpus` - the language and the content of each training sentence
+    ///
+    /// Returns:
+    ///
+    /// the detector, knowing no language if the corpus is empty
+    pub fn train<I>(corpus: I) -> Detector
+        where I: IntoIterator<Item = (String, String)>
+    {
+
+        let mut profiles: HashMap<String, Profile> = HashMap::new();
+        let mut vocabulary: HashSet<String> = HashSet::new();
+
+        for (iso639_3, content) in corpus {
+
+            let profile = profiles.entry(iso639_3).or_insert_with(|| Profile {
+                trigrams: HashMap::new(),
+                total: 0,
+            });
+
+            for trigram in trigrams(&content) {
+                profile.total += 1;
+                *profile.trigrams.entry(trigram.clone()).or_insert(0) += 1;
+                vocabulary.insert(trigram);
+            }
+        }
+
+        Detector {
+            profiles: profiles,
+            vocabulary: vocabulary.len(),
+        }
+    }
+
+    /// Ranks the known languages by probability for the given text.
+    ///
+    /// Args:
+    ///
+    /// `text` - the text to identify
+    ///
+    /// Returns:
+    ///
+    /// all the known languages, the most likely first
+    pub fn detect(&self, text: &str) -> Vec<Candidate> {
+
+        let text_trigrams = trigrams(text);
+
+        // add-one smoothing, the +1 being for the trigrams never seen
+        let vocabulary = (self.vocabulary + 1) as f64;
+
+        let log_likelihoods: Vec<(&String, f64)> = self.profiles.iter()
+            .map(|(iso639_3, profile)| {
+                let total = profile.total as f64 + vocabulary;
+                let log_likelihood: f64 = text_trigrams.iter()
+                    .map(|trigram| {
+                        let count = profile.trigrams.get(trigram).cloned().unwrap_or(0);
+                        ((count as f64 + 1.0) / total).ln()
+                    })
+                    .sum();
+                (iso639_3, log_likelihood)
+            })
+            .collect();
+
+        // the log-sum-exp is shifted by the maximum to avoid underflows
+        let max = log_likelihoods.iter()
+            .map(|&(_, log_likelihood)| log_likelihood)
+            .fold(::std::f64::NEG_INFINITY, f64::max);
+        let sum: f64 = log_likelihoods.iter()
+            .map(|&(_, log_likelihood)| (log_likelihood - max).exp())
+            .sum();
+
+        let mut candidates: Vec<Candidate> = log_likelihoods.into_iter()
+            .map(|(iso639_3, log_likelihood)| Candidate {
+                iso639_3: iso639_3.clone(),
+                score: (log_likelihood - max).exp() / sum,
+            })
+            .collect();
+
+        candidates.sort_by(|first, second| {
+            second.score
+                .partial_cmp(&first.score)
+                .unwrap()
+                .then_with(|| first.iso639_3.cmp(&second.iso639_3))
+        });
+
+        candidates
+    }
+
+    /// Checks the declared language of a text against the detected one.
+    /// A language without model can be neither confirmed nor refuted.
+    ///
+    /// Args:
+    ///
+    /// `text` - the text to identify
+    /// `iso639_3` - the declared language
+    ///
+    /// Returns:
+    ///
+    /// the verdict, with the most likely language if it is not the declared one
+    pub fn check(&self, text: &str, iso639_3: &str) -> Verdict {
+
+        if !self.profiles.contains_key(iso639_3) {
+            return Verdict::Agrees;
+        }
+
+        let candidates = self.detect(text);
+
+        // the declared language may tie with the first one
+        let declared = candidates.iter()
+            .find(|candidate| candidate.iso639_3 == iso639_3)
+            .map(|candidate| candidate.score)
+            .unwrap_or(0.0);
+
+        let best = match candidates.into_iter().next() {
+            Some(best) => best,
+            None => return Verdict::Agrees,
+        };
+
+        if best.iso639_3 == iso639_3 || declared >= best.score {
+            Verdict::Agrees
+        } else if best.score >= REJECTION_THRESHOLD {
+            Verdict::StronglyDisagrees(best)
+        } else if best.score >= WARNING_THRESHOLD {
+            Verdict::Disagrees(best)
+        } else {
+            Verdict::Agrees
+        }
+    }
+}
+
+/// Splits a text in lowercase character trigrams, words being padded
+/// with spaces so their beginning and end are taken into account.
+/// The text is composed first, a combining accent not being alphabetic.
+fn trigrams(text: &str) -> Vec<String> {
+
+    let text: String = text.nfc().collect();
+    let mut trigrams = Vec::new();
+
+    for word in text.split(|c: char| !c.is_alphabetic()).filter(|word| !word.is_empty()) {
+
+        let chars: Vec<char> = format!(" {} ", word.to_lowercase()).chars().collect();
+
+        for window in chars.windows(3) {
+            trigrams.push(window.iter().collect());
+        }
+    }
+
+    trigrams
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn detector() -> Detector {
+        Detector::train(vec![
+            ("eng", "The cat is sleeping on the sofa in the living room."),
+            ("eng", "We would like to thank everyone who helped with the project."),
+            ("eng", "The weather is nice and the children are playing outside."),
+            ("fra", "Le chat dort sur le canapé dans le salon."),
+            ("fra", "Nous aimerions remercier tous ceux qui ont aidé le projet."),
+            ("fra", "Il fait beau et les enfants jouent dehors à côté de l'école."),
+        ].into_iter().map(|(iso639_3, content)| (iso639_3.to_string(), content.to_string())))
+    }
+
+    #[test]
+    fn test_trigrams_pad_words() {
+
+        assert_eq!(trigrams("Le chat"), vec![" le", "le ", " ch", "cha", "hat", "at "]);
+        assert!(trigrams("").is_empty());
+        assert!(trigrams("42 !").is_empty());
+    }
+
+    #[test]
+    fn test_trigrams_compose_the_text() {
+
+        assert_eq!(trigrams("e\u{0301}te\u{0301}"), trigrams("\u{00e9}t\u{00e9}"));
+    }
+
+    #[test]
+    fn test_untrained_detector_knows_no_language() {
+
+        let detector = Detector::train(Vec::new());
+
+        assert!(detector.detect("Hello.").is_empty());
+        assert!(match detector.check("Hello.", "eng") { Verdict::Agrees => true, _ => false });
+    }
+
+    #[test]
+    fn test_detect_ranks_the_languages() {
+
+        let candidates = detector().detect("Les enfants dorment dans le salon.");
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].iso639_3, "fra");
+        assert!(candidates[0].score > candidates[1].score);
+        assert!((candidates[0].score + candidates[1].score - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_detect_decomposed_text_like_composed_text() {
+
+        let detector = detector();
+        let composed = detector.detect("L'\u{00e9}cole est \u{00e0} c\u{00f4}t\u{00e9}.");
+        let decomposed = detector.detect("L'e\u{0301}cole est a\u{0300} co\u{0302}te\u{0301}.");
+
+        assert_eq!(composed[0].iso639_3, "fra");
+        assert_eq!(decomposed[0].iso639_3, "fra");
+        assert_eq!(composed[0].score, decomposed[0].score);
+    }
+
+    #[test]
+    fn test_check_agrees_with_the_detected_language() {
+
+        let verdict = detector().check("The children are sleeping in the room.", "eng");
+
+        assert!(match verdict { Verdict::Agrees => true, _ => false });
+    }
+
+    #[test]
+    fn test_check_agrees_with_a_language_without_model() {
+
+        let verdict = detector().check("The children are sleeping in the room.", "deu");
+
+        assert!(match verdict { Verdict::Agrees => true, _ => false });
+    }
+
+    #[test]
+    fn test_check_agrees_on_a_tie() {
+
+        // without any trigram, every language is as likely
+        let verdict = detector().check("", "fra");
+
+        assert!(match verdict { Verdict::Agrees => true, _ => false });
+    }
+
+    #[test]
+    fn test_check_disagrees_with_another_language() {
+
+        let verdict = detector().check(
+            "We would like to thank the children who are playing outside in the nice weather.",
+            "fra",
+        );
+
+        match verdict {
+            Verdict::StronglyDisagrees(best) => {
+                assert_eq!(best.iso639_3, "eng");
+                assert!(best.score >= REJECTION_THRESHOLD);
+            },
+            _ => panic!("expected a strong disagreement"),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Language variants: an ISO 639-3 language with an optional
+//! ISO 15924 script and region, as found in BCP 47 tags.
+//!
+//! The script and region lists are the ones of the IANA language subtag registry.
+
+use iso639::Registry;
+
+const SCRIPT_TABLE: &str = include_str!("scripts.tab");
+const REGION_TABLE: &str = include_str!("regions.tab");
+
+#[derive(Clone)]
+pub struct LanguageTag {
+    pub iso639_3: String,
+    pub script: Option<String>,
+    pub region: Option<String>,
+}
+
+impl LanguageTag {
+
+    /// Parses a BCP 47 tag (`sr-Latn-RS`, `zh-yue-Hant`) or a plain language code.
+    ///
+    /// Variants and extensions after the region are ignored.
+    ///
+    /// Args:
+    ///
+    /// `registry` - the ISO 639-3 registry
+    /// `tag` - the tag to parse
+    ///
+    /// Returns:
+    ///
+    /// the tag, None if the language, script or region is unknown
+    pub fn parse(registry: &Registry, tag: &str) -> Option<LanguageTag> {
+
+        let subtags: Vec<&str> = tag.trim().split(|c: char| c == '-' || c == '_').collect();
+
+        let language = registry.normalise(subtags[0])?;
+
+        // an extended language subtag replaces its macrolanguage
+        if subtags.len() > 1 {
+            if let Some(extlang) = registry.normalise(&format!("{}-{}", subtags[0], subtags[1])) {
+                if extlang != language {
+                    return LanguageTag::parse_variant(extlang, &subtags[2..]);
+                }
+            }
+        }
+
+        LanguageTag::parse_variant(language, &subtags[1..])
+    }
+
+    /// Reads the optional script and region following the language.
+    fn parse_variant(iso639_3: String, subtags: &[&str]) -> Option<LanguageTag> {
+
+        let mut subtags = subtags.iter().peekable();
+
+        let script = match subtags.peek() {
+            Some(subtag) if subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Some(normalise_script(subtag)?)
+            },
+            _ => None,
+        };
+        if script.is_some() {
+            subtags.next();
+        }
+
+        let region = match subtags.peek() {
+            Some(subtag) if subtag.len() == 2 || subtag.len() == 3 => {
+                Some(normalise_region(subtag)?)
+            },
+            _ => None,
+        };
+
+        Some(LanguageTag {
+            iso639_3: iso639_3,
+            script: script,
+            region: region,
+        })
+    }
+}
+
+/// Checks an ISO 15924 script code, case insensitive.
+///
+/// Returns:
+///
+/// the code in its canonical case (`Latn`), None if unknown
+pub fn normalise_script(script: &str) -> Option<String> {
+
+    let script = script.trim();
+    let mut canonical: String = script.chars().take(1).flat_map(|c| c.to_uppercase()).collect();
+    canonical += &script.chars().skip(1).collect::<String>().to_lowercase();
+
+    SCRIPT_TABLE.lines()
+        .skip(1)
+        .find(|code| *code == canonical)
+        .map(String::from)
+}
+
+/// Checks a region code (ISO 3166-1 alpha-2 or UN M.49), case insensitive.
+///
+/// Returns:
+///
+/// the code in its canonical case (`RS`), None if unknown
+pub fn normalise_region(region: &str) -> Option<String> {
+
+    let canonical = region.trim().to_uppercase();
+
+    REGION_TABLE.lines()
+        .skip(1)
+        .find(|code| *code == canonical)
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn parse(tag: &str) -> Option<(String, Option<String>, Option<String>)> {
+        LanguageTag::parse(&Registry::load(), tag).map(|tag| (tag.iso639_3, tag.script, tag.region))
+    }
+
+    fn variant(iso639_3: &str, script: Option<&str>, region: Option<&str>) -> Option<(String, Option<String>, Option<String>)> {
+        Some((iso639_3.to_string(), script.map(String::from), region.map(String::from)))
+    }
+
+    #[test]
+    fn test_parse_plain_language_code() {
+
+        assert_eq!(parse("fra"), variant("fra", None, None));
+        assert_eq!(parse("fr"), variant("fra", None, None));
+        assert_eq!(parse("fre"), variant("fra", None, None));
+    }
+
+    #[test]
+    fn test_parse_script_and_region() {
+
+        assert_eq!(parse("sr-Latn-RS"), variant("srp", Some("Latn"), Some("RS")));
+        assert_eq!(parse("SR_latn_rs"), variant("srp", Some("Latn"), Some("RS")));
+        assert_eq!(parse("es-419"), variant("spa", None, Some("419")));
+        assert_eq!(parse("zh-yue-Hant"), variant("yue", Some("Hant"), None));
+    }
+
+    #[test]
+    fn test_parse_ignores_variants() {
+
+        assert_eq!(parse("de-DE-1996"), variant("deu", None, Some("DE")));
+    }
+
+    #[test]
+    fn test_parse_unknown_subtags() {
+
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("xyz-Latn"), None);
+        assert_eq!(parse("fr-Abcd"), None);
+        assert_eq!(parse("fr-XY"), None);
+    }
+
+    #[test]
+    fn test_normalise_script_and_region_case() {
+
+        assert_eq!(normalise_script("LATN"), Some("Latn".to_string()));
+        assert_eq!(normalise_script(" cyrl "), Some("Cyrl".to_string()));
+        assert_eq!(normalise_script(""), None);
+        assert_eq!(normalise_region("rs"), Some("RS".to_string()));
+        assert_eq!(normalise_region("419"), Some("419".to_string()));
+        assert_eq!(normalise_region(""), None);
+    }
+}
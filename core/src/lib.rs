@@ -0,0 +1,19 @@
+//! The core of the sentence aligner: the models exchanged with the server,
+//! the validation and canonicalisation of the sentences, and the algorithms
+//! working on them, free of any storage or HTTP concern.
+
+#[macro_use] extern crate serde_derive;
+
+extern crate uuid;
+extern crate xml;
+extern crate unicode_normalization;
+
+pub mod canonical;
+pub mod diff;
+pub mod iso639;
+pub mod language_detection;
+pub mod language_tag;
+pub mod models;
+pub mod near_duplicates;
+pub mod structure;
+pub mod validation;
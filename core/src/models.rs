@@ -0,0 +1,428 @@
+//! The resources exchanged by the server and its clients,
+//! in the JSON form documented in `doc/openapi.json`.
+
+use uuid::Uuid;
+use std::str::FromStr;
+
+use iso639::{LanguageInfo, Registry};
+use language_tag::LanguageTag;
+
+pub use language_detection::Candidate;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Sentence {
+    pub id: Option<Uuid>,
+    pub text: String,
+    pub iso639_3: String,
+    pub structure: Option<String>,
+    /// ISO 15924 script code, e.g. `Latn`
+    pub script: Option<String>,
+    /// ISO 3166-1 alpha-2 or UN M.49 region code, e.g. `RS`
+    pub region: Option<String>,
+    /// id of the user who has added the sentence, set by the server
+    #[serde(default)]
+    pub created_by: Option<i32>,
+    /// id of the user who has last changed the sentence, set by the server
+    #[serde(default)]
+    pub updated_by: Option<i32>,
+    /// set by the reviews, a new sentence being either a draft
+    /// or pending review, the default
+    #[serde(default)]
+    pub review_status: ReviewStatus,
+    /// the review status of the structure, set by the server,
+    /// None if the sentence has no structure
+    #[serde(default)]
+    pub structure_review_status: Option<ReviewStatus>,
+}
+
+/// State of a sentence at a given point in time.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub text: String,
+    pub iso639_3: Option<String>,
+    pub structure: Option<String>,
+    pub script: Option<String>,
+    pub region: Option<String>,
+}
+
+impl Snapshot {
+
+    /// Returns the language variant of the sentence.
+    pub fn language(&self) -> LanguageTag {
+        LanguageTag {
+            // the language of a sentence cannot be removed
+            // while the sentence exists, so it is always known
+            iso639_3: self.iso639_3.clone().unwrap_or_default(),
+            script: self.script.clone(),
+            region: self.region.clone(),
+        }
+    }
+}
+
+/// A change of a sentence, with the differences it has made.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Revision {
+    pub id: i32,
+    /// UTC date
+    pub revised_at: String,
+    pub author: Option<String>,
+    pub old: Snapshot,
+    pub new: Snapshot,
+    pub text_diff: String,
+    /// None if the structure has not changed
+    pub structure_diff: Option<String>,
+}
+
+/// What has been removed by a sentence deletion.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DeletionReport {
+    pub id: Uuid,
+    pub purged: bool,
+    pub revisions_removed: i64,
+}
+
+/// What a sentence merge has done.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SentenceMergeReport {
+    pub removed: Uuid,
+    pub kept: Uuid,
+    pub structure_moved: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Language {
+    pub iso639_3: String,
+    /// reference information, None for codes created
+    /// before the ISO 639-3 registry was checked
+    #[serde(flatten)]
+    pub info: Option<LanguageInfo>,
+    pub sentences: i64,
+}
+
+impl Language {
+
+    /// Creates a language with its reference information.
+    ///
+    /// Args:
+    ///
+    /// `registry` - the ISO 639-3 registry
+    /// `iso639_3` - the language code
+    /// `sentences` - the number of sentences of the language
+    pub fn new(registry: &Registry, iso639_3: String, sentences: i64) -> Language {
+        Language {
+            info: registry.get(&iso639_3).cloned(),
+            iso639_3: iso639_3,
+            sentences: sentences,
+        }
+    }
+}
+
+/// What has been removed by a language deletion.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LanguageDeletionReport {
+    pub iso639_3: String,
+    pub sentences_removed: u64,
+}
+
+/// A sentence removed by a merge because the target language
+/// already had one with the same content.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MergedDuplicate {
+    pub removed: Uuid,
+    pub kept: Uuid,
+}
+
+/// What a language merge has done.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LanguageMergeReport {
+    pub from: String,
+    pub into: String,
+    pub moved: u64,
+    pub duplicates: Vec<MergedDuplicate>,
+}
+
+/// Sentences of a same language variant whose contents
+/// become identical once canonicalised.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ContentCollision {
+    pub iso639_3: String,
+    pub script: Option<String>,
+    pub region: Option<String>,
+    pub canonical: String,
+    pub sentences: Vec<Uuid>,
+}
+
+/// Near-duplicate sentences of a language variant.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DuplicateGroup {
+    pub group: i32,
+    pub sentences: Vec<Sentence>,
+}
+
+/// What a refresh of the near-duplicate groups has found.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RefreshReport {
+    pub groups: usize,
+    pub sentences: usize,
+}
+
+/// What an API key allows, each role allowing what the lesser ones do.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// reads the corpus
+    Reader,
+    /// adds and edits sentences
+    Contributor,
+    /// restores, merges and reverts sentences
+    Reviewer,
+    /// manages the languages and the API keys, purges sentences
+    Admin,
+}
+
+impl Role {
+
+    /// Returns the name of the role, as stored and sent.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Role::Reader => "reader",
+            Role::Contributor => "contributor",
+            Role::Reviewer => "reviewer",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Role {
+
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Role, String> {
+        match name {
+            "reader" => Ok(Role::Reader),
+            "contributor" => Ok(Role::Contributor),
+            "reviewer" => Ok(Role::Reviewer),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("unknown role {}", name)),
+        }
+    }
+}
+
+/// An API key, without its secret but when it has just been created.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ApiKey {
+    pub id: i32,
+    /// the user the key is given to
+    pub user_id: i32,
+    /// the name of the user
+    pub name: String,
+    pub role: Role,
+    /// UTC date
+    pub created_at: String,
+    /// the key to send in `Authorization: Bearer`, only known at its creation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// An API key to create.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewApiKey {
+    /// the name of the user the key is given to, created if unknown
+    pub name: String,
+    pub role: Role,
+}
+
+/// Someone or something contributing with API keys.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct User {
+    pub id: i32,
+    pub name: String,
+    /// UTC date
+    pub created_at: String,
+}
+
+/// What a user has done in a language.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LanguageContributions {
+    pub iso639_3: String,
+    /// the sentences added by the user, deleted ones excluded
+    pub sentences_created: i64,
+    /// the revisions made by the user
+    pub edits: i64,
+}
+
+/// A revision made by a user.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ContributedEdit {
+    pub revision_id: i32,
+    pub sentence_id: Uuid,
+    /// UTC date
+    pub revised_at: String,
+    /// the language of the sentence after the revision
+    pub iso639_3: Option<String>,
+}
+
+/// The contributions of a user: counts per language, with the
+/// most recent sentences added and edits made, latest first.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Contributions {
+    pub user: User,
+    pub languages: Vec<LanguageContributions>,
+    pub sentences: Vec<Sentence>,
+    pub edits: Vec<ContributedEdit>,
+}
+
+/// Where a sentence, or its structure, is in the review process.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    /// being written, not submitted for review yet
+    Draft,
+    PendingReview,
+    Approved,
+    Rejected,
+}
+
+impl ReviewStatus {
+
+    /// Returns the name of the status, as stored and sent.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ReviewStatus::Draft => "draft",
+            ReviewStatus::PendingReview => "pending_review",
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl Default for ReviewStatus {
+    fn default() -> ReviewStatus {
+        ReviewStatus::PendingReview
+    }
+}
+
+impl FromStr for ReviewStatus {
+
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<ReviewStatus, String> {
+        match name {
+            "draft" => Ok(ReviewStatus::Draft),
+            "pending_review" => Ok(ReviewStatus::PendingReview),
+            "approved" => Ok(ReviewStatus::Approved),
+            "rejected" => Ok(ReviewStatus::Rejected),
+            _ => Err(format!("unknown review status {}", name)),
+        }
+    }
+}
+
+/// What a review is about.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewSubject {
+    /// the text of the sentence, in its language variant
+    Sentence,
+    Structure,
+}
+
+impl ReviewSubject {
+
+    /// Returns the name of the subject, as stored and sent.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ReviewSubject::Sentence => "sentence",
+            ReviewSubject::Structure => "structure",
+        }
+    }
+}
+
+impl Default for ReviewSubject {
+    fn default() -> ReviewSubject {
+        ReviewSubject::Sentence
+    }
+}
+
+impl FromStr for ReviewSubject {
+
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<ReviewSubject, String> {
+        match name {
+            "sentence" => Ok(ReviewSubject::Sentence),
+            "structure" => Ok(ReviewSubject::Structure),
+            _ => Err(format!("unknown review subject {}", name)),
+        }
+    }
+}
+
+/// A review to make of a sentence or of its structure.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewReview {
+    #[serde(default)]
+    pub subject: ReviewSubject,
+    /// the new status of the subject
+    pub status: ReviewStatus,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// A review made of a sentence or of its structure.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Review {
+    pub id: i32,
+    pub sentence_id: Uuid,
+    pub subject: ReviewSubject,
+    /// the status given to the subject
+    pub status: ReviewStatus,
+    pub comment: Option<String>,
+    /// the user who has made the review, None if removed since
+    pub reviewer_id: Option<i32>,
+    /// the name of the user
+    pub reviewer: Option<String>,
+    /// UTC date
+    pub reviewed_at: String,
+}
+
+/// An RFC 7807 problem, the body of the error responses.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Problem {
+    /// `urn:sentence-aligner:<code>`
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    /// explanation of this occurrence of the problem
+    pub detail: Option<String>,
+    pub code: String,
+    /// id of the request, as in its `X-Request-Id` header, to find it in the logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// the language the text is almost certainly written in,
+    /// for the `language-mismatch` problems
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_language: Option<Candidate>,
+}
+
+impl Problem {
+
+    /// Creates the problem of a code.
+    ///
+    /// Args:
+    ///
+    /// `status` - the response status
+    /// `title` - the reason phrase of the status
+    /// `code` - the problem code
+    /// `detail` - the explanation of this occurrence of the problem
+    pub fn new(status: u16, title: &str, code: &str, detail: Option<String>) -> Problem {
+        Problem {
+            problem_type: format!("urn:sentence-aligner:{}", code),
+            title: title.to_string(),
+            status: status,
+            detail: detail,
+            code: code.to_string(),
+            request_id: None,
+            detected_language: None,
+        }
+    }
+}
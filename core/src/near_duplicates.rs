@@ -0,0 +1,286 @@
+//! Near-duplicate sentences: same language variant, contents differing
+//! only by punctuation, casing or a few characters.
+//!
+//! The contents are lowercased and stripped of punctuation, cut in
+//! character trigrams, and compared with MinHash signatures: sentences
+//! sharing a band of their signature are candidates, kept if the Jaccard
+//! similarity of their trigrams reaches `MIN_SIMILARITY`.
+
+use uuid::Uuid;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use models::Sentence;
+
+/// Number of hash functions of a signature.
+const SIGNATURE_LENGTH: usize = 64;
+
+/// Number of signature values per band, the signature holding 16 bands.
+const BAND_LENGTH: usize = 4;
+
+/// Jaccard similarity from which two sentences are near-duplicates.
+const MIN_SIMILARITY: f64 = 0.7;
+
+/// Groups the near-duplicate sentences, per language variant.
+///
+/// Args:
+///
+/// `sentences` - the sentences to compare, the ones without id being ignored
+///
+/// Returns:
+///
+/// the groups of at least two sentences, each one a list of sentence uuids
+pub fn group(sentences: Vec<Sentence>) -> Vec<Vec<Uuid>> {
+
+    let mut variants: HashMap<(String, Option<String>, Option<String>), Vec<(Uuid, HashSet<String>)>> = HashMap::new();
+
+    for sentence in sentences {
+        let sentence_uuid = match sentence.id {
+            Some(sentence_uuid) => sentence_uuid,
+            None => continue,
+        };
+        let shingles = shingles(&sentence.text);
+        variants
+            .entry((sentence.iso639_3, sentence.script, sentence.region))
+            .or_insert_with(Vec::new)
+            .push((sentence_uuid, shingles));
+    }
+
+    variants.values()
+        .flat_map(|sentences| cluster(sentences))
+        .collect()
+}
+
+/// Groups the sentences of one language variant whose shingles
+/// are similar enough, directly or through other sentences.
+///
+/// Returns:
+///
+/// the groups of at least two sentences, in the order of the given sentences
+fn cluster(sentences: &[(Uuid, HashSet<String>)]) -> Vec<Vec<Uuid>> {
+
+    let signatures: Vec<Vec<u64>> = sentences.iter()
+        .map(|&(_, ref shingles)| signature(shingles))
+        .collect();
+
+    let mut parents: Vec<usize> = (0..sentences.len()).collect();
+
+    let mut buckets: HashMap<(usize, &[u64]), Vec<usize>> = HashMap::new();
+    for (index, signature) in signatures.iter().enumerate() {
+        for (band, values) in signature.chunks(BAND_LENGTH).enumerate() {
+            buckets.entry((band, values)).or_insert_with(Vec::new).push(index);
+        }
+    }
+
+    for candidates in buckets.values() {
+        for (position, &first) in candidates.iter().enumerate() {
+            for &second in candidates[position + 1..].iter() {
+                if find(&mut parents, first) == find(&mut parents, second) {
+                    continue;
+                }
+                if similarity(&sentences[first].1, &sentences[second].1) >= MIN_SIMILARITY {
+                    let first_root = find(&mut parents, first);
+                    let second_root = find(&mut parents, second);
+                    parents[second_root.max(first_root)] = second_root.min(first_root);
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<Uuid>> = BTreeMap::new();
+    for index in 0..sentences.len() {
+        let root = find(&mut parents, index);
+        groups.entry(root).or_insert_with(Vec::new).push(sentences[index].0);
+    }
+
+    groups.into_iter()
+        .map(|(_, group)| group)
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+/// Returns the representative of the group of a sentence.
+fn find(parents: &mut Vec<usize>, index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[index] = root;
+    root
+}
+
+/// Cuts a content in character trigrams, ignoring casing and punctuation.
+fn shingles(content: &str) -> HashSet<String> {
+
+    let words: Vec<String> = content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+
+    let chars: Vec<char> = words.join(" ").chars().collect();
+
+    if chars.len() < 3 {
+        let mut shingles = HashSet::new();
+        shingles.insert(chars.into_iter().collect());
+        return shingles;
+    }
+
+    chars.windows(3)
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+/// Computes the MinHash signature of a set of shingles,
+/// each hash function being the default hasher with a different seed.
+fn signature(shingles: &HashSet<String>) -> Vec<u64> {
+
+    (0..SIGNATURE_LENGTH)
+        .map(|seed| {
+            shingles.iter()
+                .map(|shingle| {
+                    let mut hasher = DefaultHasher::new();
+                    seed.hash(&mut hasher);
+                    shingle.hash(&mut hasher);
+                    hasher.finish()
+                })
+                .min()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Jaccard similarity of two sets of shingles.
+fn similarity(first: &HashSet<String>, second: &HashSet<String>) -> f64 {
+
+    let union = first.union(second).count();
+    if union == 0 {
+        return 1.0;
+    }
+
+    first.intersection(second).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn sentence(text: &str, iso639_3: &str, region: Option<&str>) -> Sentence {
+        Sentence {
+            id: Some(Uuid::new_v4()),
+            text: text.to_string(),
+            iso639_3: iso639_3.to_string(),
+            structure: None,
+            script: None,
+            region: region.map(String::from),
+            created_by: None,
+            updated_by: None,
+            review_status: Default::default(),
+            structure_review_status: None,
+        }
+    }
+
+    /// Groups the given sentences, returning their indexes instead of their uuids.
+    fn group_indexes(sentences: Vec<Sentence>) -> Vec<Vec<usize>> {
+
+        let uuids: Vec<Uuid> = sentences.iter().map(|sentence| sentence.id.unwrap()).collect();
+
+        let mut groups: Vec<Vec<usize>> = group(sentences).into_iter()
+            .map(|group| {
+                let mut indexes: Vec<usize> = group.iter()
+                    .map(|uuid| uuids.iter().position(|other| other == uuid).unwrap())
+                    .collect();
+                indexes.sort();
+                indexes
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn test_shingles_ignore_casing_and_punctuation() {
+
+        assert_eq!(shingles("Hello, World!"), shingles("hello world"));
+        assert_eq!(shingles("ab").len(), 1);
+        assert_eq!(shingles(""), shingles("?!"));
+    }
+
+    #[test]
+    fn test_identical_shingles_have_identical_signature() {
+
+        let signature = signature(&shingles("The quick brown fox."));
+
+        assert_eq!(signature.len(), SIGNATURE_LENGTH);
+        assert_eq!(signature, super::signature(&shingles("the quick brown fox")));
+    }
+
+    #[test]
+    fn test_similarity_threshold_boundaries() {
+
+        // 7 shared trigrams out of 10
+        let at_threshold = similarity(&shingles("abcdefghij"), &shingles("abcdefghixy"));
+        // 7 shared trigrams out of 11
+        let below_threshold = similarity(&shingles("abcdefghij"), &shingles("abcdefghixyz"));
+
+        assert_eq!(at_threshold, 0.7);
+        assert!(at_threshold >= MIN_SIMILARITY);
+        assert!(below_threshold < MIN_SIMILARITY);
+        assert_eq!(similarity(&HashSet::new(), &HashSet::new()), 1.0);
+    }
+
+    #[test]
+    fn test_group_of_no_sentence() {
+
+        assert!(group(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_group_joins_sentences_differing_by_casing_punctuation_or_a_word() {
+
+        let groups = group_indexes(vec![
+            sentence("The quick brown fox jumps over the lazy dog.", "eng", None),
+            sentence("the quick brown fox jumps over the lazy dog!", "eng", None),
+            sentence("The quick brown fox jumps over the lazy cat.", "eng", None),
+            sentence("I have a red car.", "eng", None),
+        ]);
+
+        assert_eq!(groups, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_group_keeps_different_sentences_apart() {
+
+        let groups = group_indexes(vec![
+            sentence("I have a red car.", "eng", None),
+            sentence("I have a blue car.", "eng", None),
+        ]);
+
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn test_group_keeps_language_variants_apart() {
+
+        let groups = group_indexes(vec![
+            sentence("The colour of the car.", "eng", Some("GB")),
+            sentence("The colour of the car.", "eng", Some("US")),
+            sentence("The colour of the car.", "sco", Some("GB")),
+        ]);
+
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn test_group_ignores_sentences_without_id() {
+
+        let mut unsaved = sentence("The colour of the car.", "eng", None);
+        unsaved.id = None;
+
+        let groups = group(vec![sentence("The colour of the car.", "eng", None), unsaved]);
+
+        assert!(groups.is_empty());
+    }
+}
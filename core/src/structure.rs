@@ -0,0 +1,310 @@
+//! Helpers around the XML structure attached to a sentence.
+//!
+//! The text of a structure (the concatenation of all its text nodes)
+//! must always be equal to the content of the sentence it belongs to.
+
+use xml::reader::EventReader;
+use xml::reader::XmlEvent as ReaderEvent;
+use xml::writer::EmitterConfig;
+use xml::writer::XmlEvent as WriterEvent;
+
+use diff::{diff_chars, Operation};
+
+/// Name of the response header telling what happened to the structure
+/// of a sentence when its text got edited.
+pub const STATUS_HEADER: &str = "X-Structure-Status";
+
+/// What happened to the structure of a sentence after a text edit.
+pub enum StructureUpdate {
+    /// the sentence has no structure, nothing to do
+    Absent,
+    /// the structure has been rewritten to match the new text
+    Reprojected(String),
+    /// the edit crossed element boundaries, the structure has been removed
+    Invalidated,
+}
+
+impl StructureUpdate {
+
+    /// Computes the structure update needed when the text of a sentence changes.
+    ///
+    /// Args:
+    ///
+    /// `structure` - the current structure of the sentence, if any
+    /// `old_text` - the current text of the sentence
+    /// `new_text` - the text replacing it
+    pub fn from_text_edit(
+        structure: Option<&str>,
+        old_text: &str,
+        new_text: &str,
+    ) -> StructureUpdate {
+
+        match structure {
+            None => StructureUpdate::Absent,
+            Some(structure) => match reproject(structure, old_text, new_text) {
+                Some(updated) => StructureUpdate::Reprojected(updated),
+                None => StructureUpdate::Invalidated,
+            },
+        }
+    }
+
+    /// Returns the structure to store once the edit is applied.
+    pub fn structure(&self) -> Option<&str> {
+        match *self {
+            StructureUpdate::Reprojected(ref structure) => Some(structure),
+            StructureUpdate::Absent | StructureUpdate::Invalidated => None,
+        }
+    }
+
+    /// Returns the value of the `X-Structure-Status` response header.
+    pub fn status(&self) -> &'static str {
+        match *self {
+            StructureUpdate::Absent => "absent",
+            StructureUpdate::Reprojected(_) => "reprojected",
+            StructureUpdate::Invalidated => "invalidated",
+        }
+    }
+}
+
+/// Returns the text of a structure, that is all its text nodes put together.
+///
+/// Args:
+///
+/// `structure` - the XML structure
+pub fn extract_text(structure: &str) -> String {
+
+    let parser = EventReader::from_str(structure);
+    let mut text = String::new();
+
+    for word in parser {
+        match word {
+            Ok(ReaderEvent::Characters(value)) | Ok(ReaderEvent::Whitespace(value)) => {
+                text += &value;
+            },
+            _ => {}
+        }
+    }
+
+    text
+}
+
+/// Tells whether a structure is well-formed XML content, as the `XML` type
+/// of PostgreSQL accepts it: text and elements, without a single root needed.
+///
+/// Args:
+///
+/// `structure` - the XML structure
+pub fn is_well_formed(structure: &str) -> bool {
+
+    let wrapped = format!("<structure>{}</structure>", structure);
+
+    EventReader::from_str(&wrapped)
+        .into_iter()
+        .all(|event| event.is_ok())
+}
+
+/// Rewrites the text nodes of a structure so it matches an edited text.
+///
+/// The old and new texts are compared character by character, every
+/// changed chunk must fall inside a single text node, which then receives
+/// the new characters. An insertion exactly between two text nodes
+/// goes to the first one.
+///
+/// Args:
+///
+/// `structure` - the structure matching `old_text`
+/// `old_text` - the text before the edit
+/// `new_text` - the text after the edit
+///
+/// Returns:
+///
+/// the updated structure, or None if an edit spans several text nodes,
+/// the structure cannot be parsed or its text is not `old_text`
+pub fn reproject(
+    structure: &str,
+    old_text: &str,
+    new_text: &str,
+) -> Option<String> {
+
+    let mut events = Vec::new();
+    for event in EventReader::from_str(structure) {
+        match event {
+            Ok(event) => events.push(event),
+            Err(_) => return None,
+        }
+    }
+
+    // character range, in the old text, of every text node
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut text = String::new();
+    let mut position = 0;
+    for event in events.iter() {
+        match *event {
+            ReaderEvent::Characters(ref value) | ReaderEvent::Whitespace(ref value) => {
+                let length = value.chars().count();
+                ranges.push((position, position + length));
+                position += length;
+                text += value;
+            },
+            _ => {}
+        }
+    }
+
+    if text != old_text {
+        return None;
+    }
+
+    let mut texts = vec![String::new(); ranges.len()];
+    let operations = diff_chars(old_text, new_text);
+
+    let mut position = 0;
+    let mut index = 0;
+    while index < operations.len() {
+
+        if let Operation::Equal(character) = operations[index] {
+            let node = ranges.iter().position(
+                |&(start, end)| start <= position && position < end
+            )?;
+            texts[node].push(character);
+            position += 1;
+            index += 1;
+            continue;
+        }
+
+        let start = position;
+        let mut inserted = String::new();
+        while index < operations.len() {
+            match operations[index] {
+                Operation::Equal(_) => break,
+                Operation::Delete(_) => position += 1,
+                Operation::Insert(character) => inserted.push(character),
+            }
+            index += 1;
+        }
+
+        let node = ranges.iter().position(
+            |&(node_start, node_end)| node_start <= start && position <= node_end
+        )?;
+        texts[node].push_str(&inserted);
+    }
+
+    let mut output: Vec<u8> = Vec::new();
+    {
+        let mut writer = EmitterConfig::new()
+            .write_document_declaration(false)
+            .create_writer(&mut output);
+
+        let mut node = 0;
+        for event in events.iter() {
+            let result = match *event {
+                ReaderEvent::StartDocument { .. } | ReaderEvent::EndDocument => continue,
+                ReaderEvent::Characters(_) | ReaderEvent::Whitespace(_) => {
+                    node += 1;
+                    writer.write(WriterEvent::characters(&texts[node - 1]))
+                },
+                ref event => match event.as_writer_event() {
+                    Some(event) => writer.write(event),
+                    None => continue,
+                },
+            };
+            if result.is_err() {
+                return None;
+            }
+        }
+    }
+
+    String::from_utf8(output).ok()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const STRUCTURE: &str = "<s><w>A</w> <w>cat</w> <w>sat.</w></s>";
+
+    #[test]
+    fn test_extract_text_joins_text_nodes() {
+
+        assert_eq!(extract_text(STRUCTURE), "A cat sat.");
+        assert_eq!(extract_text(""), "");
+    }
+
+    #[test]
+    fn test_is_well_formed() {
+
+        assert!(is_well_formed(STRUCTURE));
+        assert!(is_well_formed("no element at all"));
+        assert!(is_well_formed(""));
+        assert!(!is_well_formed("<w>A</x>"));
+        assert!(!is_well_formed("<w>A"));
+    }
+
+    #[test]
+    fn test_reproject_identical_text_keeps_structure() {
+
+        assert_eq!(reproject(STRUCTURE, "A cat sat.", "A cat sat."), Some(STRUCTURE.to_string()));
+    }
+
+    #[test]
+    fn test_reproject_edit_inside_text_node() {
+
+        assert_eq!(
+            reproject(STRUCTURE, "A cat sat.", "A bat sat."),
+            Some("<s><w>A</w> <w>bat</w> <w>sat.</w></s>".to_string()),
+        );
+        assert_eq!(
+            reproject(STRUCTURE, "A cat sat.", "A cat sat!"),
+            Some("<s><w>A</w> <w>cat</w> <w>sat!</w></s>".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_reproject_insertion_between_nodes_goes_to_first_one() {
+
+        assert_eq!(
+            reproject("<w>a</w><w>b</w>", "ab", "axb"),
+            Some("<w>ax</w><w>b</w>".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_reproject_edit_across_element_boundaries() {
+
+        assert_eq!(reproject(STRUCTURE, "A cat sat.", "A caxat."), None);
+        assert_eq!(reproject(STRUCTURE, "A cat sat.", "A casat."), None);
+        assert_eq!(reproject(STRUCTURE, "A cat sat.", ""), None);
+    }
+
+    #[test]
+    fn test_reproject_text_not_matching_structure() {
+
+        assert_eq!(reproject(STRUCTURE, "A dog sat.", "A dog sat!"), None);
+        assert_eq!(reproject("<w>A</x>", "A", "B"), None);
+    }
+
+    #[test]
+    fn test_reproject_counts_characters_not_bytes() {
+
+        assert_eq!(
+            reproject("<s><w>Un</w> <w>café</w></s>", "Un café", "Un cafés"),
+            Some("<s><w>Un</w> <w>cafés</w></s>".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_structure_update_from_text_edit() {
+
+        let absent = StructureUpdate::from_text_edit(None, "A cat sat.", "A bat sat.");
+        assert_eq!(absent.status(), "absent");
+        assert_eq!(absent.structure(), None);
+
+        let reprojected = StructureUpdate::from_text_edit(Some(STRUCTURE), "A cat sat.", "A bat sat.");
+        assert_eq!(reprojected.status(), "reprojected");
+        assert_eq!(reprojected.structure(), Some("<s><w>A</w> <w>bat</w> <w>sat.</w></s>"));
+
+        let invalidated = StructureUpdate::from_text_edit(Some(STRUCTURE), "A cat sat.", "A casat.");
+        assert_eq!(invalidated.status(), "invalidated");
+        assert_eq!(invalidated.structure(), None);
+    }
+}
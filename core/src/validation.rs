@@ -0,0 +1,143 @@
+//! Validation of the sentences before they are stored.
+
+use std::fmt;
+
+use canonical::Canonicalizer;
+use iso639::Registry;
+use language_tag;
+use language_tag::LanguageTag;
+use models::{ReviewStatus, Sentence};
+use structure;
+
+/// A rule broken by a sentence, with an explanation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError(pub String);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+/// Normalises the language variant of a new sentence and canonicalises
+/// its content and structure, as they are stored. A new sentence is either
+/// a draft or pending review, only the reviews approve or reject it.
+///
+/// Args:
+///
+/// `sentence` - the sentence as given
+/// `registry` - the ISO 639-3 registry
+/// `canonicalizer` - the canonicalisation of the contents
+///
+/// Returns:
+///
+/// the sentence to store, an error if it breaks a rule
+pub fn prepare_sentence(
+    mut sentence: Sentence,
+    registry: &Registry,
+    canonicalizer: &Canonicalizer,
+) -> Result<Sentence, ValidationError> {
+
+    match sentence.review_status {
+        ReviewStatus::Draft | ReviewStatus::PendingReview => {},
+        status => {
+            return Err(ValidationError(
+                format!("a sentence cannot be created as {}", status.as_str())
+            ));
+        },
+    }
+
+    /* unknown codes are kept as is, the insertion then fails
+       as for a code missing from the language table;
+       the script and region of a BCP 47 tag are used
+       unless they are given separately */
+    if let Some(tag) = LanguageTag::parse(registry, &sentence.iso639_3) {
+        sentence.iso639_3 = tag.iso639_3;
+        sentence.script = sentence.script.or(tag.script);
+        sentence.region = sentence.region.or(tag.region);
+    }
+
+    if let Some(script) = sentence.script.take() {
+        match language_tag::normalise_script(&script) {
+            Some(script) => sentence.script = Some(script),
+            None => {
+                return Err(ValidationError(format!("unknown script {}", script)));
+            }
+        }
+    }
+
+    if let Some(region) = sentence.region.take() {
+        match language_tag::normalise_region(&region) {
+            Some(region) => sentence.region = Some(region),
+            None => {
+                return Err(ValidationError(format!("unknown region {}", region)));
+            }
+        }
+    }
+
+    sentence.text = canonicalizer.text(&sentence.text);
+
+    if let Some(structure) = sentence.structure.take() {
+        match canonicalizer.structure(&structure) {
+            Some(structure) => sentence.structure = Some(structure),
+            None => {
+                return Err(ValidationError(
+                    "the structure cannot be canonicalised".to_string()
+                ));
+            }
+        }
+    }
+
+    if let Some(ref content) = sentence.structure {
+
+        if sentence.text != structure::extract_text(content) {
+            return Err(ValidationError(
+                "the structure text does not match the sentence text".to_string()
+            ));
+        }
+    }
+
+    Ok(sentence)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn sentence(review_status: ReviewStatus) -> Sentence {
+        Sentence {
+            id: None,
+            text: "Hello.".to_string(),
+            iso639_3: "eng".to_string(),
+            structure: None,
+            script: None,
+            region: None,
+            created_by: None,
+            updated_by: None,
+            review_status: review_status,
+            structure_review_status: None,
+        }
+    }
+
+    fn prepare(sentence: Sentence) -> Result<Sentence, ValidationError> {
+        prepare_sentence(sentence, &Registry::load(), &Canonicalizer::new(true, true, true, false))
+    }
+
+    #[test]
+    fn test_prepare_sentence_keeps_draft_and_pending_review() {
+
+        assert!(prepare(sentence(ReviewStatus::Draft)).is_ok());
+        assert!(prepare(sentence(ReviewStatus::PendingReview)).is_ok());
+    }
+
+    #[test]
+    fn test_prepare_sentence_refuses_reviewed_sentence() {
+
+        assert_eq!(
+            prepare(sentence(ReviewStatus::Approved)).unwrap_err(),
+            ValidationError("a sentence cannot be created as approved".to_string()),
+        );
+        assert!(prepare(sentence(ReviewStatus::Rejected)).is_err());
+    }
+}
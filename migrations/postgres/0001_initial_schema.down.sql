@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS sentence;
+DROP TABLE IF EXISTS language;
@@ -0,0 +1,20 @@
+-- the tables the server used to create at startup, as it created them
+-- before the schema had migrations, so that these databases are adopted
+-- as they are and brought up to date by the following migrations
+
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE IF NOT EXISTS language (
+    id SERIAL PRIMARY KEY,
+    iso639_3 VARCHAR(3) UNIQUE NOT NULL
+    CONSTRAINT iso639_3_length CHECK (CHAR_LENGTH(iso639_3) = 3)
+);
+
+CREATE TABLE IF NOT EXISTS sentence (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    language_id INTEGER REFERENCES language (id) ON DELETE SET NULL,
+    added_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    content TEXT NOT NULL,
+    structure XML DEFAULT NULL,
+    UNIQUE (language_id, content)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE sentence_revision (
+    id SERIAL PRIMARY KEY,
+    sentence_id UUID NOT NULL REFERENCES sentence (id) ON DELETE CASCADE,
+    revised_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    author TEXT DEFAULT NULL,
+    old_content TEXT NOT NULL,
+    old_iso639_3 VARCHAR(3) DEFAULT NULL,
+    old_structure XML DEFAULT NULL,
+    new_content TEXT NOT NULL,
+    new_iso639_3 VARCHAR(3) DEFAULT NULL,
+    new_structure XML DEFAULT NULL
+);
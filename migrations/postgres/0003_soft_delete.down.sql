@@ -0,0 +1,4 @@
+-- the deleted sentences would come back, they are purged instead
+DELETE FROM sentence WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE sentence DROP COLUMN deleted_at;
@@ -0,0 +1 @@
+ALTER TABLE sentence ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
@@ -0,0 +1,6 @@
+-- a language with sentences is merged into another one, not deleted
+ALTER TABLE sentence DROP CONSTRAINT sentence_language_id_fkey;
+
+ALTER TABLE sentence
+ADD CONSTRAINT sentence_language_id_fkey
+FOREIGN KEY (language_id) REFERENCES language (id) ON DELETE RESTRICT;
@@ -0,0 +1,16 @@
+ALTER TABLE sentence_revision
+DROP COLUMN old_script,
+DROP COLUMN old_region,
+DROP COLUMN new_script,
+DROP COLUMN new_region;
+
+DROP INDEX sentence_variant_content;
+
+-- fails while a content exists in several variants of a language,
+-- which have to be merged first
+ALTER TABLE sentence
+ADD CONSTRAINT sentence_language_id_content_key UNIQUE (language_id, content);
+
+ALTER TABLE sentence
+DROP COLUMN script,
+DROP COLUMN region;
@@ -0,0 +1,21 @@
+ALTER TABLE sentence
+ADD COLUMN script CHAR(4) DEFAULT NULL,
+ADD COLUMN region VARCHAR(3) DEFAULT NULL;
+
+-- the same content can exist once per language variant,
+-- a missing script or region being a variant of its own
+ALTER TABLE sentence DROP CONSTRAINT sentence_language_id_content_key;
+
+CREATE UNIQUE INDEX sentence_variant_content
+ON sentence (
+    language_id,
+    COALESCE(script, ''),
+    COALESCE(region, ''),
+    content
+);
+
+ALTER TABLE sentence_revision
+ADD COLUMN old_script CHAR(4) DEFAULT NULL,
+ADD COLUMN old_region VARCHAR(3) DEFAULT NULL,
+ADD COLUMN new_script CHAR(4) DEFAULT NULL,
+ADD COLUMN new_region VARCHAR(3) DEFAULT NULL;
@@ -0,0 +1,4 @@
+CREATE TABLE near_duplicate (
+    sentence_id UUID PRIMARY KEY REFERENCES sentence (id) ON DELETE CASCADE,
+    group_id INTEGER NOT NULL
+);
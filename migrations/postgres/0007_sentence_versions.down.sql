@@ -0,0 +1,4 @@
+DROP TRIGGER sentence_version ON sentence;
+DROP FUNCTION increment_sentence_version();
+
+ALTER TABLE sentence DROP COLUMN version;
@@ -0,0 +1,14 @@
+ALTER TABLE sentence ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- any change of a sentence gives it a new version,
+-- whatever the query doing it, so its ETag always changes
+CREATE FUNCTION increment_sentence_version() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER sentence_version
+BEFORE UPDATE ON sentence
+FOR EACH ROW EXECUTE PROCEDURE increment_sentence_version();
@@ -0,0 +1,9 @@
+-- the keys themselves are never stored, only their SHA-256
+CREATE TABLE api_key (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_hash CHAR(64) UNIQUE NOT NULL,
+    role TEXT NOT NULL
+    CONSTRAINT api_key_role CHECK (role IN ('reader', 'contributor', 'reviewer', 'admin')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
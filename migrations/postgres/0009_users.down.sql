@@ -0,0 +1,14 @@
+DROP TABLE IF EXISTS revision_attribution;
+DROP TABLE IF EXISTS sentence_attribution;
+
+ALTER TABLE api_key ADD COLUMN name TEXT;
+
+UPDATE api_key
+SET name = user_account.name
+FROM user_account
+WHERE user_account.id = api_key.user_id;
+
+ALTER TABLE api_key ALTER COLUMN name SET NOT NULL;
+ALTER TABLE api_key DROP COLUMN user_id;
+
+DROP TABLE IF EXISTS user_account;
@@ -0,0 +1,37 @@
+CREATE TABLE user_account (
+    id SERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- the existing keys are given to users named as the keys were
+INSERT INTO user_account (name)
+SELECT DISTINCT name FROM api_key;
+
+ALTER TABLE api_key ADD COLUMN user_id INTEGER REFERENCES user_account (id) ON DELETE CASCADE;
+
+UPDATE api_key
+SET user_id = user_account.id
+FROM user_account
+WHERE user_account.name = api_key.name;
+
+ALTER TABLE api_key ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE api_key DROP COLUMN name;
+
+-- who has added and who has last changed a sentence,
+-- unknown for the sentences added before the users
+CREATE TABLE sentence_attribution (
+    sentence_id UUID PRIMARY KEY REFERENCES sentence (id) ON DELETE CASCADE,
+    created_by INTEGER DEFAULT NULL REFERENCES user_account (id) ON DELETE SET NULL,
+    updated_by INTEGER DEFAULT NULL REFERENCES user_account (id) ON DELETE SET NULL
+);
+
+CREATE INDEX sentence_attribution_created_by ON sentence_attribution (created_by);
+
+-- who has made a revision
+CREATE TABLE revision_attribution (
+    revision_id INTEGER PRIMARY KEY REFERENCES sentence_revision (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES user_account (id) ON DELETE CASCADE
+);
+
+CREATE INDEX revision_attribution_user ON revision_attribution (user_id);
@@ -0,0 +1,12 @@
+-- a deleted sentence no longer holds its content, which can be
+-- given to another sentence, restoring it being then refused
+DROP INDEX sentence_variant_content;
+
+CREATE UNIQUE INDEX sentence_variant_content
+ON sentence (
+    language_id,
+    COALESCE(script, ''),
+    COALESCE(region, ''),
+    content
+)
+WHERE deleted_at IS NULL;
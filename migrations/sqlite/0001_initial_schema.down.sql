@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS near_duplicate;
+DROP TABLE IF EXISTS sentence_revision;
+DROP TABLE IF EXISTS sentence;
+DROP TABLE IF EXISTS language;
@@ -0,0 +1,60 @@
+-- the initial schema of the SQLite storage, which came after the
+-- revisions, soft deletion, language variants, near duplicates and
+-- versions, which PostgreSQL gets from migrations of their own
+
+CREATE TABLE IF NOT EXISTS language (
+    id INTEGER PRIMARY KEY,
+    iso639_3 TEXT UNIQUE NOT NULL
+    CONSTRAINT iso639_3_length CHECK (LENGTH(iso639_3) = 3)
+);
+
+CREATE TABLE IF NOT EXISTS sentence (
+    id TEXT PRIMARY KEY NOT NULL,
+    language_id INTEGER REFERENCES language (id) ON DELETE RESTRICT,
+    added_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    content TEXT NOT NULL,
+    structure TEXT DEFAULT NULL,
+    deleted_at TEXT DEFAULT NULL,
+    script TEXT DEFAULT NULL,
+    region TEXT DEFAULT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+-- any change of a sentence gives it a new version,
+-- the condition stops the trigger from running on its own update
+CREATE TRIGGER IF NOT EXISTS sentence_version
+AFTER UPDATE ON sentence
+FOR EACH ROW WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sentence SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+CREATE UNIQUE INDEX IF NOT EXISTS sentence_variant_content
+ON sentence (
+    language_id,
+    COALESCE(script, ''),
+    COALESCE(region, ''),
+    content
+);
+
+CREATE TABLE IF NOT EXISTS sentence_revision (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sentence_id TEXT NOT NULL REFERENCES sentence (id) ON DELETE CASCADE,
+    revised_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    author TEXT DEFAULT NULL,
+    old_content TEXT NOT NULL,
+    old_iso639_3 TEXT DEFAULT NULL,
+    old_structure TEXT DEFAULT NULL,
+    old_script TEXT DEFAULT NULL,
+    old_region TEXT DEFAULT NULL,
+    new_content TEXT NOT NULL,
+    new_iso639_3 TEXT DEFAULT NULL,
+    new_structure TEXT DEFAULT NULL,
+    new_script TEXT DEFAULT NULL,
+    new_region TEXT DEFAULT NULL
+);
+
+CREATE TABLE IF NOT EXISTS near_duplicate (
+    sentence_id TEXT PRIMARY KEY REFERENCES sentence (id) ON DELETE CASCADE,
+    group_id INTEGER NOT NULL
+);
@@ -0,0 +1,9 @@
+-- the keys themselves are never stored, only their SHA-256
+CREATE TABLE api_key (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT UNIQUE NOT NULL,
+    role TEXT NOT NULL
+    CONSTRAINT api_key_role CHECK (role IN ('reader', 'contributor', 'reviewer', 'admin')),
+    created_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
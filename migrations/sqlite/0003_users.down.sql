@@ -0,0 +1,21 @@
+DROP TABLE IF EXISTS revision_attribution;
+DROP TABLE IF EXISTS sentence_attribution;
+
+CREATE TABLE old_api_key (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT UNIQUE NOT NULL,
+    role TEXT NOT NULL
+    CONSTRAINT api_key_role CHECK (role IN ('reader', 'contributor', 'reviewer', 'admin')),
+    created_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+INSERT INTO old_api_key (id, name, key_hash, role, created_at)
+SELECT api_key.id, user_account.name, api_key.key_hash, api_key.role, api_key.created_at
+FROM api_key
+JOIN user_account ON (user_account.id = api_key.user_id);
+
+DROP TABLE api_key;
+ALTER TABLE old_api_key RENAME TO api_key;
+
+DROP TABLE IF EXISTS user_account;
@@ -0,0 +1,45 @@
+CREATE TABLE user_account (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- the existing keys are given to users named as the keys were
+INSERT INTO user_account (name)
+SELECT DISTINCT name FROM api_key;
+
+-- a column cannot be dropped, the table is rebuilt instead
+CREATE TABLE new_api_key (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES user_account (id) ON DELETE CASCADE,
+    key_hash TEXT UNIQUE NOT NULL,
+    role TEXT NOT NULL
+    CONSTRAINT api_key_role CHECK (role IN ('reader', 'contributor', 'reviewer', 'admin')),
+    created_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+INSERT INTO new_api_key (id, user_id, key_hash, role, created_at)
+SELECT api_key.id, user_account.id, api_key.key_hash, api_key.role, api_key.created_at
+FROM api_key
+JOIN user_account ON (user_account.name = api_key.name);
+
+DROP TABLE api_key;
+ALTER TABLE new_api_key RENAME TO api_key;
+
+-- who has added and who has last changed a sentence,
+-- unknown for the sentences added before the users
+CREATE TABLE sentence_attribution (
+    sentence_id TEXT PRIMARY KEY REFERENCES sentence (id) ON DELETE CASCADE,
+    created_by INTEGER DEFAULT NULL REFERENCES user_account (id) ON DELETE SET NULL,
+    updated_by INTEGER DEFAULT NULL REFERENCES user_account (id) ON DELETE SET NULL
+);
+
+CREATE INDEX sentence_attribution_created_by ON sentence_attribution (created_by);
+
+-- who has made a revision
+CREATE TABLE revision_attribution (
+    revision_id INTEGER PRIMARY KEY REFERENCES sentence_revision (id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES user_account (id) ON DELETE CASCADE
+);
+
+CREATE INDEX revision_attribution_user ON revision_attribution (user_id);
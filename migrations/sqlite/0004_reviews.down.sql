@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS sentence_review;
+DROP TABLE IF EXISTS sentence_status;
@@ -0,0 +1,37 @@
+-- where a sentence and its structure are in the review process,
+-- the sentences added before the reviews being considered approved
+CREATE TABLE sentence_status (
+    sentence_id TEXT PRIMARY KEY REFERENCES sentence (id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending_review'
+    CONSTRAINT sentence_status_status CHECK (
+        status IN ('draft', 'pending_review', 'approved', 'rejected')
+    ),
+    structure_status TEXT DEFAULT NULL
+    CONSTRAINT sentence_status_structure_status CHECK (
+        structure_status IN ('draft', 'pending_review', 'approved', 'rejected')
+    )
+);
+
+INSERT INTO sentence_status (sentence_id, status, structure_status)
+SELECT
+    id,
+    'approved',
+    CASE WHEN structure IS NULL THEN NULL ELSE 'approved' END
+FROM sentence;
+
+-- the reviews made, with the comments of the reviewers
+CREATE TABLE sentence_review (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sentence_id TEXT NOT NULL REFERENCES sentence (id) ON DELETE CASCADE,
+    reviewer_id INTEGER DEFAULT NULL REFERENCES user_account (id) ON DELETE SET NULL,
+    subject TEXT NOT NULL
+    CONSTRAINT sentence_review_subject CHECK (subject IN ('sentence', 'structure')),
+    status TEXT NOT NULL
+    CONSTRAINT sentence_review_status CHECK (
+        status IN ('draft', 'pending_review', 'approved', 'rejected')
+    ),
+    comment TEXT DEFAULT NULL,
+    reviewed_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX sentence_review_sentence ON sentence_review (sentence_id);
@@ -0,0 +1,11 @@
+DROP INDEX sentence_variant_content;
+
+-- fails while a deleted sentence has the content of another one,
+-- one of them having to be purged first
+CREATE UNIQUE INDEX sentence_variant_content
+ON sentence (
+    language_id,
+    COALESCE(script, ''),
+    COALESCE(region, ''),
+    content
+);
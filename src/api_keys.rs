@@ -0,0 +1,71 @@
+use rocket::Response;
+use rocket::http::{ContentType, Status};
+use rocket_contrib::Json;
+
+use std::io::Cursor;
+
+use auth::{self, Admin};
+use error::Error;
+use models::NewApiKey;
+use repository::RequestRepository;
+
+/// Creates an API key. The key is only returned by this request,
+/// as only its hash is stored.
+#[post("/api-keys", format="application/json", data="<new_key>")]
+fn create_api_key<'r>(
+    _admin: Admin,
+    repository: RequestRepository,
+    new_key: Json<NewApiKey>,
+) -> Result<Response<'r>, Error> {
+
+    let new_key = new_key.into_inner();
+
+    let name = new_key.name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidInput("the name of a key cannot be empty".to_string()));
+    }
+
+    let key = auth::generate_key();
+
+    let mut api_key = repository.create_api_key(name, new_key.role, &auth::hash_key(&key))?;
+    api_key.key = Some(key);
+
+    Ok(Response::build()
+        .status(Status::Created)
+        .header(ContentType::JSON)
+        .raw_header("Location", format!("/api-keys/{}", api_key.id))
+        .sized_body(Cursor::new(json!(api_key).to_string()))
+        .finalize())
+}
+
+/// Lists the API keys, without their secret.
+#[get("/api-keys")]
+fn get_api_keys<'r>(
+    _admin: Admin,
+    repository: RequestRepository,
+) -> Result<Response<'r>, Error> {
+
+    let api_keys = repository.get_api_keys()?;
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(api_keys).to_string()))
+        .finalize())
+}
+
+/// Revokes an API key, refused from then on.
+#[delete("/api-keys/<api_key_id>")]
+fn revoke_api_key<'r>(
+    _admin: Admin,
+    repository: RequestRepository,
+    api_key_id: i32,
+) -> Result<Response<'r>, Error> {
+
+    if !repository.revoke_api_key(api_key_id)? {
+        return Err(Error::NotFound);
+    }
+
+    Ok(Response::build()
+        .status(Status::NoContent)
+        .finalize())
+}
@@ -0,0 +1,159 @@
+//! Authentication by API key, and authorisation by role.
+//!
+//! A client sends its key in `Authorization: Bearer <key>`. Only the SHA-256
+//! of the keys is stored, so a key is shown once, when it is created.
+//! The handlers ask for the least role they need with the `Reader`,
+//! `Contributor`, `Reviewer` or `Admin` guard, any greater role being
+//! allowed too: a request without a known key is refused with a 401,
+//! one whose key has a lesser role with a 403. Each key is given to a user,
+//! the author of the changes made with it.
+//!
+//! The guards read the keys from the storage, so they come before the
+//! `RequestRepository` in the arguments of the handlers, the SQLite
+//! connection being held by one of them at a time.
+
+use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
+use rocket::http::Status;
+use rocket::request::{self, FromRequest};
+use rocket::{Request, State, Outcome};
+
+use models::{ApiKey, Role};
+use repository::Storage;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Number of random bytes of a key.
+const KEY_BYTES: usize = 32;
+
+/// Returns the hash of a key, as stored: its SHA-256 in hexadecimal.
+///
+/// Args:
+///
+/// `key` - the key sent by a client
+pub fn hash_key(key: &str) -> String {
+    sha256(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Generates a new key, `sa_` followed by random bytes in hexadecimal.
+pub fn generate_key() -> String {
+
+    let mut bytes = [0; KEY_BYTES];
+
+    // no key can be given out without a working random generator
+    rand_bytes(&mut bytes).expect("can't generate random bytes");
+
+    let hexadecimal: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    format!("sa_{}", hexadecimal)
+}
+
+/// Author of a change: the user of the API key of the request,
+/// nobody for the changes made from the command line.
+#[derive(Default)]
+pub struct Author {
+    pub user_id: Option<i32>,
+    /// the name of the user, recorded with the revisions
+    pub name: Option<String>,
+}
+
+/// The caller of a request, known by its API key.
+pub struct Caller(pub ApiKey);
+
+impl Caller {
+
+    /// Returns the author of the changes made by the caller, its user.
+    pub fn author(&self) -> Author {
+        Author {
+            user_id: Some(self.0.user_id),
+            name: Some(self.0.name.clone()),
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Caller {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Caller, ()> {
+
+        let key = match request.headers().get_one("Authorization") {
+            Some(value) if value.starts_with(BEARER_PREFIX) => value[BEARER_PREFIX.len()..].trim(),
+            _ => return Outcome::Failure((Status::Unauthorized, ())),
+        };
+
+        let storage = request.guard::<State<Storage>>()?;
+        let api_key = storage.inner()
+            .repository()
+            .and_then(|repository| repository.get_api_key(&hash_key(key)));
+
+        match api_key {
+            Ok(Some(api_key)) => Outcome::Success(Caller(api_key)),
+            Ok(None) => Outcome::Failure((Status::Unauthorized, ())),
+            Err(_) => Outcome::Failure((Status::ServiceUnavailable, ())),
+        }
+    }
+}
+
+/// Returns the caller of a request, if its role is at least the given one.
+fn caller_with_role(request: &Request, role: Role) -> request::Outcome<Caller, ()> {
+
+    let caller = request.guard::<Caller>()?;
+
+    if caller.0.role < role {
+        return Outcome::Failure((Status::Forbidden, ()));
+    }
+
+    Outcome::Success(caller)
+}
+
+/// Guard of the routes reading the corpus.
+pub struct Reader(pub Caller);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Reader {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Reader, ()> {
+        caller_with_role(request, Role::Reader).map(Reader)
+    }
+}
+
+/// Guard of the routes adding and editing sentences.
+pub struct Contributor(pub Caller);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Contributor {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Contributor, ()> {
+        caller_with_role(request, Role::Contributor).map(Contributor)
+    }
+}
+
+/// Guard of the routes restoring, merging and reverting sentences.
+pub struct Reviewer(pub Caller);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Reviewer {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Reviewer, ()> {
+        caller_with_role(request, Role::Reviewer).map(Reviewer)
+    }
+}
+
+/// Guard of the routes managing the languages and the API keys.
+pub struct Admin(pub Caller);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Admin, ()> {
+        caller_with_role(request, Role::Admin).map(Admin)
+    }
+}
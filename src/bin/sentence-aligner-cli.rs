@@ -0,0 +1,589 @@
+//! Administration of the sentence aligner, directly on its storage
+//! rather than through the HTTP API, for cron jobs and scripts.
+//!
+//! The storage is selected by the same configuration as the server.
+//! Every command but `migrate` refuses to run on a schema which is not up to date.
+//! The exit code is 0 on success, 1 on failure and 2 on a usage error.
+
+extern crate sentence_aligner;
+extern crate serde_json;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process;
+
+use sentence_aligner::auth;
+use sentence_aligner::canonical::Canonicalizer;
+use sentence_aligner::config::Config;
+use sentence_aligner::error::Error;
+use sentence_aligner::iso639::Registry;
+use sentence_aligner::migrations::{self, MigrationError};
+use sentence_aligner::near_duplicates;
+use sentence_aligner::repository::{Creation, Repository, Storage};
+use sentence_aligner::models::{ReviewStatus, Role, Sentence};
+use sentence_aligner::structure;
+use sentence_aligner::validation;
+
+const USAGE: &str = r#"usage: sentence-aligner-cli COMMAND
+
+commands:
+    language create CODE    creates a language (ISO 639-3, ISO 639-1, ISO 639-2/B or BCP 47 code)
+    key create NAME ROLE    creates an API key for the user NAME (reader, contributor, reviewer or admin) and prints it
+    key list                lists the API keys
+    key revoke ID           revokes an API key
+    import [FILE]           creates the sentences of a JSON lines file (standard input by default)
+    export [--approved] [FILE]
+                            writes the visible sentences as JSON lines (standard output by default),
+                            with --approved only the approved sentences and structures
+    job near-duplicates     recomputes the near-duplicate groups, the only job
+                            as there are no alignments to compute yet
+    migrate up [VERSION]    applies the pending migrations
+    migrate down [VERSION]  reverts the last migration, or the ones above VERSION
+    migrate status          lists the migrations
+    stats                   counts the languages and sentences
+    validate                checks the stored structures"#;
+
+/// Why a command failed.
+enum Failure {
+    Usage,
+    Storage(Error),
+    Schema(MigrationError),
+    Io(io::Error),
+    /// the command ran but found problems, already reported
+    Reported,
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Failure {
+        Failure::Storage(error)
+    }
+}
+
+impl From<MigrationError> for Failure {
+    fn from(error: MigrationError) -> Failure {
+        Failure::Schema(error)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(error: io::Error) -> Failure {
+        Failure::Io(error)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Failure::Usage => write!(formatter, "{}", USAGE),
+            Failure::Storage(ref error) => write!(formatter, "{}", error),
+            Failure::Schema(ref error) => write!(formatter, "{}", error),
+            Failure::Io(ref error) => write!(formatter, "{}", error),
+            Failure::Reported => Ok(()),
+        }
+    }
+}
+
+fn main() {
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        },
+    };
+
+    let storage = match Storage::open(&config.storage) {
+        Ok(storage) => storage,
+        Err(error) => {
+            eprintln!("can't connect to database: {}", error);
+            process::exit(1);
+        },
+    };
+
+    let result = match args.first() {
+        Some(&"migrate") => {
+            let store = storage.migration_store().map_err(Failure::from);
+            match store {
+                Ok(store) => {
+                    let args: Vec<String> = args[1..].iter().map(|arg| arg.to_string()).collect();
+                    process::exit(migrations::run_command(&*store, &args));
+                },
+                Err(failure) => Err(failure),
+            }
+        },
+        Some(_) => run(&storage, &config.canonicalizer, &args),
+        None => Err(Failure::Usage),
+    };
+
+    process::exit(exit_code(result));
+}
+
+/// Returns the exit code of a command, telling why it failed if not reported yet.
+fn exit_code(result: Result<(), Failure>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(Failure::Usage) => {
+            eprintln!("{}", USAGE);
+            2
+        },
+        Err(Failure::Reported) => 1,
+        Err(failure) => {
+            eprintln!("{}", failure);
+            1
+        },
+    }
+}
+
+/// Runs a command other than `migrate`, once the schema is checked.
+fn run(storage: &Storage, canonicalizer: &Canonicalizer, args: &[&str]) -> Result<(), Failure> {
+
+    migrations::check(&*storage.migration_store()?)?;
+
+    let repository = storage.repository()?;
+
+    match args {
+        &["language", "create", code] => create_language(&*repository, code),
+        &["key", "create", name, role] => create_api_key(&*repository, name, role),
+        &["key", "list"] => {
+            for api_key in repository.get_api_keys()? {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    api_key.id,
+                    api_key.role.as_str(),
+                    api_key.name,
+                    api_key.created_at,
+                );
+            }
+            Ok(())
+        },
+        &["key", "revoke", id] => revoke_api_key(&*repository, id),
+        &["import"] => import(&*repository, canonicalizer, None),
+        &["import", path] => import(&*repository, canonicalizer, Some(path)),
+        &["export"] => export(&*repository, false, None),
+        &["export", "--approved"] => export(&*repository, true, None),
+        &["export", "--approved", path] => export(&*repository, true, Some(path)),
+        &["export", path] => export(&*repository, false, Some(path)),
+        &["job", "near-duplicates"] => {
+            let groups = near_duplicates::refresh(&*repository)?;
+            println!("{} groups", groups.len());
+            Ok(())
+        },
+        &["stats"] => stats(&*repository),
+        &["validate"] => validate(&*repository),
+        _ => Err(Failure::Usage),
+    }
+}
+
+/// Creates a language, the code being normalised to ISO 639-3 as by the API.
+fn create_language(repository: &Repository, code: &str) -> Result<(), Failure> {
+
+    let iso639_3 = match Registry::load().normalise(code) {
+        Some(iso639_3) => iso639_3,
+        None => {
+            return Err(Error::InvalidInput(format!("unknown language code {}", code)).into());
+        }
+    };
+
+    repository.create_language(&iso639_3)?;
+    println!("{}", iso639_3);
+
+    Ok(())
+}
+
+/// Creates an API key and prints it, the only time it is shown,
+/// e.g. to give a first admin key to the API.
+fn create_api_key(repository: &Repository, name: &str, role: &str) -> Result<(), Failure> {
+
+    let role: Role = role.parse().map_err(Error::InvalidInput)?;
+
+    let key = auth::generate_key();
+    let api_key = repository.create_api_key(name, role, &auth::hash_key(&key))?;
+
+    eprintln!("created key {} for {} ({})", api_key.id, api_key.name, api_key.role.as_str());
+    println!("{}", key);
+
+    Ok(())
+}
+
+fn revoke_api_key(repository: &Repository, id: &str) -> Result<(), Failure> {
+
+    let id: i32 = id.parse().map_err(|_| Failure::Usage)?;
+
+    if !repository.revoke_api_key(id)? {
+        return Err(Error::NotFound.into());
+    }
+
+    Ok(())
+}
+
+/// Creates the sentences read as JSON lines, one sentence as sent to
+/// `POST /sentences` per line. They are canonicalised as by the API,
+/// but not checked by the language detection.
+///
+/// Args:
+///
+/// `repository` - the storage
+/// `canonicalizer` - the canonicalisation of the contents
+/// `path` - the file to read, the standard input if None
+fn import(
+    repository: &Repository,
+    canonicalizer: &Canonicalizer,
+    path: Option<&str>,
+) -> Result<(), Failure> {
+
+    let reader: Box<BufRead> = match path {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+
+    let registry = Registry::load();
+
+    // the imported sentences are attributed to nobody
+    let author = auth::Author::default();
+
+    let mut created = 0;
+    let mut restored = 0;
+    let mut refused = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let outcome = serde_json::from_str::<Sentence>(&line)
+            .map_err(|error| Error::InvalidInput(error.to_string()))
+            .and_then(|sentence| {
+                validation::prepare_sentence(sentence, &registry, canonicalizer).map_err(Error::from)
+            })
+            .and_then(|sentence| repository.create_sentence(&sentence, &author));
+
+        match outcome {
+            Ok(Creation::Created(_)) => created += 1,
+            Ok(Creation::Restored(_)) => restored += 1,
+            Ok(Creation::Conflict(sentence)) => {
+                refused += 1;
+                eprintln!(
+                    "line {}: conflicts with sentence {}",
+                    index + 1,
+                    sentence.id.map_or(String::new(), |id| id.to_string()),
+                );
+            },
+            Err(error) => {
+                refused += 1;
+                eprintln!("line {}: {}", index + 1, error);
+            },
+        }
+    }
+
+    println!("{} created, {} restored, {} refused", created, restored, refused);
+
+    if refused > 0 {
+        return Err(Failure::Reported);
+    }
+
+    Ok(())
+}
+
+/// Writes the visible sentences as JSON lines, in the order they have been added.
+///
+/// Args:
+///
+/// `repository` - the storage
+/// `approved` - true to write only the approved sentences,
+///              without their structure unless approved too
+/// `path` - the file to write, the standard output if None
+fn export(repository: &Repository, approved: bool, path: Option<&str>) -> Result<(), Failure> {
+
+    let mut writer: Box<Write> = match path {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+
+    for mut sentence in repository.get_all_sentences(false)? {
+
+        if approved {
+            if sentence.review_status != ReviewStatus::Approved {
+                continue;
+            }
+            if sentence.structure_review_status != Some(ReviewStatus::Approved) {
+                sentence.structure = None;
+                sentence.structure_review_status = None;
+            }
+        }
+
+        writeln!(writer, "{}", json_line(&sentence))?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn json_line(sentence: &Sentence) -> String {
+    serde_json::to_string(sentence).expect("a sentence is always serialisable")
+}
+
+/// Prints the number of languages and sentences, and the sentences per language.
+fn stats(repository: &Repository) -> Result<(), Failure> {
+
+    let languages = repository.get_languages()?;
+    let visible = repository.get_all_sentences(false)?;
+    let all = repository.get_all_sentences(true)?.len();
+
+    let structured = visible.iter()
+        .filter(|sentence| sentence.structure.is_some())
+        .count();
+
+    println!("languages: {}", languages.len());
+    println!("sentences: {}", visible.len());
+    println!("deleted sentences: {}", all - visible.len());
+    println!("sentences with a structure: {}", structured);
+
+    let mut scripts: BTreeMap<&str, usize> = BTreeMap::new();
+    for sentence in visible.iter() {
+        let script = sentence.script.as_ref().map_or("-", |script| script.as_str());
+        *scripts.entry(script).or_insert(0) += 1;
+    }
+
+    println!();
+    for language in languages {
+        println!("{}\t{}", language.iso639_3, language.sentences);
+    }
+
+    println!();
+    for (script, sentences) in scripts {
+        println!("{}\t{}", script, sentences);
+    }
+
+    Ok(())
+}
+
+/// Checks the structures of all the sentences, deleted ones included as they
+/// can be restored: each must be well-formed and hold the sentence text.
+fn validate(repository: &Repository) -> Result<(), Failure> {
+
+    let mut invalid = 0;
+
+    for sentence in repository.get_all_sentences(true)? {
+
+        let content = match sentence.structure {
+            Some(ref content) => content,
+            None => continue,
+        };
+
+        let id = sentence.id.map_or(String::new(), |id| id.to_string());
+
+        if !structure::is_well_formed(content) {
+            invalid += 1;
+            println!("{}: the structure is not well-formed", id);
+        } else if structure::extract_text(content) != sentence.text {
+            invalid += 1;
+            println!("{}: the structure text does not match the sentence text", id);
+        }
+    }
+
+    if invalid > 0 {
+        println!("{} invalid structures", invalid);
+        return Err(Failure::Reported);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    use sentence_aligner::auth::Author;
+    use sentence_aligner::config::StorageConfig;
+    use sentence_aligner::models::{NewReview, ReviewSubject};
+
+    use super::*;
+
+    const SENTENCES: &str = r#"
+{"text": "A cat sat.", "iso639_3": "eng", "structure": "<s><w>A</w> <w>cat</w> <w>sat.</w></s>"}
+{"text": "Un chat.", "iso639_3": "fr"}
+"#;
+
+    const REFUSED: &str = r#"
+{"text": "Hallo.", "iso639_3": "deu"}
+not json
+"#;
+
+    fn storage() -> Storage {
+        let storage = Storage::open(&StorageConfig::Memory).unwrap();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        repository.create_language("fra").unwrap();
+        storage
+    }
+
+    fn canonicalizer() -> Canonicalizer {
+        Canonicalizer::new(true, true, true, false)
+    }
+
+    /// Returns a file of the temporary directory, only used by this test.
+    fn temporary_file(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("sentence-aligner-cli-{}-{}", process::id(), name))
+    }
+
+    fn write_file(path: &PathBuf, content: &str) {
+        File::create(path).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+
+    fn read_file(path: &PathBuf) -> String {
+        let mut content = String::new();
+        File::open(path).unwrap().read_to_string(&mut content).unwrap();
+        content
+    }
+
+    fn command(storage: &Storage, args: &[&str]) -> i32 {
+        exit_code(run(storage, &canonicalizer(), args))
+    }
+
+    fn sentence(text: &str, structure: &str) -> Sentence {
+        Sentence {
+            id: None,
+            text: text.to_string(),
+            iso639_3: "eng".to_string(),
+            structure: Some(structure.to_string()),
+            script: None,
+            region: None,
+            created_by: None,
+            updated_by: None,
+            review_status: ReviewStatus::PendingReview,
+            structure_review_status: None,
+        }
+    }
+
+    #[test]
+    fn test_import_export_round_trip() {
+
+        let input = temporary_file("round-trip.jsonl");
+        let exported = temporary_file("round-trip-exported.jsonl");
+        let exported_again = temporary_file("round-trip-exported-again.jsonl");
+        write_file(&input, SENTENCES);
+
+        let storage = storage();
+        assert_eq!(command(&storage, &["import", input.to_str().unwrap()]), 0);
+        assert_eq!(command(&storage, &["validate"]), 0);
+        assert_eq!(command(&storage, &["stats"]), 0);
+        assert_eq!(command(&storage, &["export", exported.to_str().unwrap()]), 0);
+
+        let lines: Vec<Sentence> = read_file(&exported)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "A cat sat.");
+        assert_eq!(lines[1].iso639_3, "fra");
+
+        // the export is imported as is, ids included
+        let other = storage();
+        assert_eq!(command(&other, &["import", exported.to_str().unwrap()]), 0);
+        assert_eq!(command(&other, &["validate"]), 0);
+        assert_eq!(command(&other, &["export", exported_again.to_str().unwrap()]), 0);
+        assert_eq!(read_file(&exported_again), read_file(&exported));
+
+        for path in [input, exported, exported_again].iter() {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_import_reports_refused_lines() {
+
+        let input = temporary_file("refused.jsonl");
+        write_file(&input, &format!("{}{}", SENTENCES, REFUSED));
+
+        let storage = storage();
+        assert_eq!(command(&storage, &["import", input.to_str().unwrap()]), 1);
+        assert_eq!(storage.repository().unwrap().get_all_sentences(false).unwrap().len(), 2);
+
+        fs::remove_file(&input).unwrap();
+    }
+
+    #[test]
+    fn test_export_approved() {
+
+        let input = temporary_file("approved.jsonl");
+        let exported = temporary_file("approved-exported.jsonl");
+        write_file(&input, SENTENCES);
+
+        let storage = storage();
+        assert_eq!(command(&storage, &["import", input.to_str().unwrap()]), 0);
+
+        let repository = storage.repository().unwrap();
+        let approved = repository.get_all_sentences(false).unwrap()[0].id.unwrap();
+        let review = NewReview {
+            subject: ReviewSubject::Sentence,
+            status: ReviewStatus::Approved,
+            comment: None,
+        };
+        repository.review_sentence(&approved, &review, &Author::default()).unwrap();
+
+        assert_eq!(command(&storage, &["export", "--approved", exported.to_str().unwrap()]), 0);
+
+        let lines: Vec<Sentence> = read_file(&exported)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].id, Some(approved));
+        // the structure is not approved yet
+        assert_eq!(lines[0].structure, None);
+
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&exported).unwrap();
+    }
+
+    #[test]
+    fn test_validate_reports_invalid_structures() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+
+        // stored as is, as by an older version not checking the structures
+        for sentence in [
+            sentence("A cat sat.", "<s><w>A</w> <w>cat</w> <w>sat.</w></s>"),
+            sentence("A dog sat.", "<s><w>A</w> <w>cat</w> <w>sat.</w></s>"),
+        ].iter() {
+            match repository.create_sentence(sentence, &Author::default()).unwrap() {
+                Creation::Created(_) => {},
+                _ => panic!("the sentence {:?} has not been created", sentence.text),
+            }
+        }
+
+        assert_eq!(command(&storage, &["validate"]), 1);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+
+        let storage = storage();
+
+        assert_eq!(command(&storage, &["language", "create", "de"]), 0);
+        assert_eq!(command(&storage, &["language", "create", "deu"]), 1);
+        assert_eq!(command(&storage, &["key", "revoke", "12"]), 1);
+        assert_eq!(command(&storage, &["key", "revoke", "twelve"]), 2);
+        assert_eq!(command(&storage, &["align"]), 2);
+        assert_eq!(command(&storage, &["import", "/nonexistent/sentences.jsonl"]), 1);
+
+        assert_eq!(exit_code(Ok(())), 0);
+        assert_eq!(exit_code(Err(Failure::Storage(Error::Unavailable))), 1);
+        assert_eq!(exit_code(Err(Failure::Reported)), 1);
+        assert_eq!(exit_code(Err(Failure::Usage)), 2);
+    }
+}
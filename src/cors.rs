@@ -1,28 +1,154 @@
-use rocket::{Request, Response};
+use rocket::{Request, Response, Rocket};
 use rocket::fairing::{Fairing, Info, Kind};
-use rocket::http::{Header, ContentType, Method, Status};
+use rocket::http::{Header, Method, Status};
 use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::RwLock;
 
-pub struct CORS();
+use config::CorsConfig;
+
+pub struct CORS {
+    config: CorsConfig,
+    /// the method and path of the mounted routes, known once launched
+    routes: RwLock<Vec<(Method, String)>>,
+}
+
+impl CORS {
+
+    /// Creates the fairing of a CORS policy.
+    ///
+    /// Args:
+    ///
+    /// `config` - the CORS configuration
+    pub fn new(config: CorsConfig) -> CORS {
+        CORS {
+            config: config,
+            routes: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Returns the `Access-Control-Allow-Origin` of a request,
+    /// None if it has no origin or if its origin is not allowed.
+    fn allowed_origin(&self, request: &Request) -> Option<String> {
+
+        let origin = request.headers().get_one("Origin")?;
+
+        if self.config.allowed_origins.iter().any(|allowed| allowed == "*") {
+            return Some("*".to_string());
+        }
+
+        self.config.allowed_origins
+            .iter()
+            .find(|allowed| allowed.as_str() == origin)
+            .cloned()
+    }
+
+    /// Returns the allowed methods of the routes matching a path.
+    fn methods_of(&self, path: &str) -> Vec<String> {
+
+        let routes = match self.routes.read() {
+            Ok(routes) => routes,
+            Err(_) => return Vec::new(),
+        };
+
+        self.config.allowed_methods
+            .iter()
+            .filter(|allowed| {
+                routes.iter().any(|&(ref method, ref route)| {
+                    method.as_str() == allowed.as_str() && matches(route, path)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Answers a preflight request of a path some routes match,
+    /// telling which of their methods and which headers can be used.
+    fn preflight(&self, request: &Request, response: &mut Response) {
+
+        let requested_method = request.headers()
+            .get_one("Access-Control-Request-Method")
+            .and_then(|method| Method::from_str(method).ok());
+
+        let methods = self.methods_of(request.uri().path());
+
+        // the preflight of a path no allowed route matches stays a 404
+        if requested_method.is_none() || methods.is_empty() {
+            return;
+        }
+
+        // replaces the problem of the 404
+        response.set_status(Status::NoContent);
+        response.remove_header("Content-Type");
+        response.set_sized_body(Cursor::new(""));
+        response.set_header(Header::new("Access-Control-Allow-Methods", methods.join(", ")));
+        response.set_header(Header::new(
+            "Access-Control-Allow-Headers",
+            self.config.allowed_headers.join(", "),
+        ));
+        if self.config.max_age > 0 {
+            response.set_header(Header::new("Access-Control-Max-Age", self.config.max_age.to_string()));
+        }
+    }
+}
+
+/// Tells whether a path is matched by the path of a route,
+/// e.g. `/sentences/<sentence_uuid>`, its query being ignored.
+fn matches(route: &str, path: &str) -> bool {
+
+    let route = route.split('?').next().unwrap_or("");
+
+    let mut route_segments = route.split('/').filter(|segment| !segment.is_empty());
+    let mut path_segments = path.split('/').filter(|segment| !segment.is_empty());
+
+    loop {
+        match (route_segments.next(), path_segments.next()) {
+            (None, None) => return true,
+            (Some(segment), _) if segment.starts_with('<') && segment.ends_with("..>") => return true,
+            (Some(segment), Some(_)) if segment.starts_with('<') => {},
+            (Some(segment), Some(path_segment)) if segment == path_segment => {},
+            _ => return false,
+        }
+    }
+}
 
 impl Fairing for CORS {
     fn info(&self) -> Info {
         Info {
             name: "Add CORS headers to requests",
-            kind: Kind::Response
+            kind: Kind::Launch | Kind::Response
+        }
+    }
+
+    fn on_launch(&self, rocket: &Rocket) {
+        if let Ok(mut routes) = self.routes.write() {
+            *routes = rocket.routes()
+                .map(|route| (route.method, route.uri.path().to_string()))
+                .collect();
         }
     }
 
     fn on_response(&self, request: &Request, response: &mut Response) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
-        response.set_header(Header::new("Access-Control-Allow-Methods", "POST, GET, OPTIONS, PUT"));
-        response.set_header(Header::new("Access-Control-Allow-Headers", "Content-Type"));
-        response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
-
-        if request.method() == Method::Options && request.route().is_none(){
-            response.set_status(Status::NoContent);
-            response.set_header(ContentType::Plain);
-            response.set_sized_body(Cursor::new(""));
+
+        let origin = match self.allowed_origin(request) {
+            Some(origin) => origin,
+            None => return,
+        };
+
+        if origin != "*" {
+            response.set_header(Header::new("Vary", "Origin"));
+        }
+        response.set_header(Header::new("Access-Control-Allow-Origin", origin));
+        response.set_header(Header::new(
+            "Access-Control-Expose-Headers",
+            self.config.exposed_headers.join(", "),
+        ));
+        if self.config.allow_credentials {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+
+        if request.method() == Method::Options && request.route().is_none() {
+            self.preflight(request, response);
         }
     }
 }
@@ -1,81 +1,108 @@
-
-use std::ops::Deref;
-use std::env;
-
-use rocket::http::Status;
-use rocket::request::{self, FromRequest};
-use rocket::{Request, State, Outcome};
-
-use postgres;
-use postgres::params::{ConnectParams, Host};
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use postgres::tls::openssl::OpenSsl;
 
 use r2d2;
 use r2d2_postgres::{TlsMode, PostgresConnectionManager};
 
+use std::cmp;
+use std::thread;
+use std::time::Duration;
 
-type Pool = r2d2::Pool<PostgresConnectionManager>;
+use config::{DatabaseConfig, Tls};
 
 
-/// Create the connection parameters from environment variables
-/// DB_USER
-/// DB_PASSWORD
-/// DB_HOST
-/// DB_NAME
-/// we fail if any is missing
-fn create_connection_params_from_env() -> ConnectParams {
-    ConnectParams::builder()
-        .user(
-            &env::var("DB_USER").expect("missing DB_USER"),
-            Some(&env::var("DB_PASSWORD").expect("missing DB_PASSWORD")),
-        )
-        .database(&env::var("DB_NAME").expect("missing DB_NAME"))
-        .build(Host::Tcp(env::var("DB_HOST").expect("missing DB_HOST")))
-}
+pub type Pool = r2d2::Pool<PostgresConnectionManager>;
 
-/// Create the r2d2 connection pool of Postgresql Connection
-pub fn init_pool() -> Pool {
+/// Longest wait between two connection attempts at startup, in seconds.
+const MAX_RETRY_DELAY: u64 = 30;
 
-    let manager = PostgresConnectionManager::new(
-        create_connection_params_from_env(),
-        TlsMode::None
-    )
-    .expect("db manager");
 
-    let config = r2d2::Config::default();
+/// Returns the TLS handshake of the connections, None if they are not
+/// encrypted, verifying the server as the configured mode asks,
+/// against the configured certificates if any
+fn tls_handshake(config: &DatabaseConfig) -> Result<Option<OpenSsl>, String> {
 
-    Pool::new(
-        config,
-        manager
-    )
-    .expect("db pool")
-}
+    if config.tls == Tls::Disable {
+        return Ok(None);
+    }
 
-// Connection request guard type: a wrapper around an r2d2 pooled connection.
-pub struct DbConnection(pub r2d2::PooledConnection<PostgresConnectionManager>);
+    let mut connector = SslConnector::builder(SslMethod::tls())
+        .map_err(|error| format!("TLS initialisation: {}", error))?;
 
-/// Attempts to retrieve a single connection from the managed database pool. If
-/// no pool is currently managed, fails with an `InternalServerError` status. If
-/// no connections are available, fails with a `ServiceUnavailable` status.
-impl<'a, 'r> FromRequest<'a, 'r> for DbConnection {
+    if let Some(ref path) = config.ca_certificate {
+        connector.set_ca_file(path)
+            .map_err(|error| format!("{}: {}", path, error))?;
+    }
 
-    type Error = ();
+    let verify_certificate = config.tls == Tls::VerifyCa || config.tls == Tls::VerifyFull;
+    if !verify_certificate {
+        connector.set_verify(SslVerifyMode::NONE);
+    }
 
-    fn from_request(request: &'a Request<'r>) -> request::Outcome<DbConnection, ()> {
+    let mut handshake = OpenSsl::from(connector.build());
+    handshake.danger_disable_hostname_verification(config.tls != Tls::VerifyFull);
 
-        let pool = request.guard::<State<Pool>>()?;
-        match pool.get() {
-            Ok(conn) => Outcome::Success(DbConnection(conn)),
-            Err(_) => Outcome::Failure((Status::ServiceUnavailable, ()))
-        }
+    Ok(Some(handshake))
+}
 
+/// Returns the TLS mode of the connections of a pool.
+fn tls_mode(config: &DatabaseConfig, handshake: &Option<OpenSsl>) -> TlsMode {
+    match *handshake {
+        None => TlsMode::None,
+        Some(ref handshake) if config.tls == Tls::Prefer => TlsMode::Prefer(Box::new(handshake.clone())),
+        Some(ref handshake) => TlsMode::Require(Box::new(handshake.clone())),
     }
 }
 
-// For the convenience of using an &DbConn as an &postgres::Connection.
-impl Deref for DbConnection {
-    type Target = postgres::Connection;
+/// Create the r2d2 connection pool of Postgresql Connection.
+/// The database may still be starting along with the server,
+/// so the connection is retried, waiting twice as long each time.
+///
+/// Args:
+///
+/// `config` - the database configuration
+///
+/// Returns:
+///
+/// the pool, or why the database cannot be reached
+pub fn init_pool(config: &DatabaseConfig) -> Result<Pool, String> {
+
+    let mut delay = Duration::from_secs(1);
+    let mut retries = 0;
+
+    let handshake = tls_handshake(config)?;
+
+    loop {
+
+        let manager = PostgresConnectionManager::new(
+            config.params.clone(),
+            tls_mode(config, &handshake),
+        )
+        .map_err(|error| error.to_string())?;
+
+        let pool_config = r2d2::Config::builder()
+            .pool_size(config.pool_size)
+            .connection_timeout(config.connection_timeout)
+            .idle_timeout(config.idle_timeout)
+            .build();
+
+        let error = match Pool::new(pool_config, manager) {
+            Ok(pool) => return Ok(pool),
+            Err(error) => error,
+        };
+
+        if retries == config.connect_retries {
+            return Err(error.to_string());
+        }
+
+        eprintln!(
+            "can't connect to the database ({}), retrying in {} seconds",
+            error,
+            delay.as_secs(),
+        );
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+        thread::sleep(delay);
+        delay = cmp::min(delay * 2, Duration::from_secs(MAX_RETRY_DELAY));
+        retries += 1;
     }
 }
@@ -0,0 +1,273 @@
+//! Errors of the handlers, sent as RFC 7807 `application/problem+json` bodies.
+//!
+//! Every problem has a `code`, documented in `doc/openapi.json`
+//! (`definitions/Problem`), its `type` being `urn:sentence-aligner:<code>`.
+
+use rocket::Request;
+use rocket::http::{ContentType, Header, Status};
+use rocket::response::{self, Responder, Response};
+use postgres;
+use rusqlite;
+use rusqlite::ffi;
+
+use std::fmt;
+use std::io::Cursor;
+
+use logging;
+use models::{Candidate, Problem};
+use validation::ValidationError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// the resource does not exist
+    NotFound,
+    /// the role of the caller does not allow the request
+    Forbidden,
+    /// the resource, or one with the same unique fields, already exists
+    AlreadyExists,
+    /// the request is malformed or breaks a rule, with an explanation
+    InvalidInput(String),
+    /// the resource has been modified since the version given in `If-Match`
+    PreconditionFailed,
+    /// a structure is not well-formed XML
+    InvalidXml,
+    /// the text is almost certainly written in this language rather than the declared one
+    LanguageMismatch(Candidate),
+    /// no database connection can be had
+    Unavailable,
+    /// the database schema is not the one of the server, with the reason
+    SchemaNotCurrent(String),
+    /// a value read from the database is not a valid one, with the reason
+    InvalidStoredValue(String),
+    /// a PostgreSQL query failed
+    Database(postgres::Error),
+    /// a SQLite query failed
+    Sqlite(rusqlite::Error),
+}
+
+impl From<postgres::Error> for Error {
+    fn from(error: postgres::Error) -> Error {
+        Error::Database(error)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Error {
+        Error::Sqlite(error)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Error {
+        Error::InvalidInput(error.0)
+    }
+}
+
+impl fmt::Display for Error {
+
+    /// Writes the explanation of the problem, in full as it is not
+    /// meant for the clients, or its code if there is none.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidInput(ref detail) => write!(formatter, "{}", detail),
+            Error::SchemaNotCurrent(ref detail) => write!(formatter, "{}", detail),
+            Error::InvalidStoredValue(ref detail) => write!(formatter, "{}", detail),
+            Error::Database(ref error) => write!(formatter, "{}", error),
+            Error::Sqlite(ref error) => write!(formatter, "{}", error),
+            _ => write!(formatter, "{}", self.status_and_code().1),
+        }
+    }
+}
+
+impl Error {
+
+    /// Returns the status and the code of the problem.
+    fn status_and_code(&self) -> (Status, &'static str) {
+        match *self {
+            Error::NotFound => (Status::NotFound, "not-found"),
+            Error::Forbidden => (Status::Forbidden, "forbidden"),
+            Error::AlreadyExists => (Status::Conflict, "unique-violation"),
+            Error::InvalidInput(_) => (Status::BadRequest, "invalid-input"),
+            Error::PreconditionFailed => (Status::PreconditionFailed, "precondition-failed"),
+            Error::InvalidXml => (Status::BadRequest, "invalid-xml"),
+            Error::LanguageMismatch(_) => (Status::BadRequest, "language-mismatch"),
+            Error::Unavailable => (Status::ServiceUnavailable, "database-unavailable"),
+            Error::SchemaNotCurrent(_) => (Status::ServiceUnavailable, "schema-not-current"),
+            Error::InvalidStoredValue(_) => (Status::InternalServerError, "database-error"),
+            Error::Database(ref error) => database_status_and_code(error),
+            Error::Sqlite(ref error) => sqlite_status_and_code(error),
+        }
+    }
+
+    /// Returns the explanation of the problem, if any can be given to the client.
+    fn detail(&self) -> Option<String> {
+        match *self {
+            Error::InvalidInput(ref detail) => Some(detail.clone()),
+            Error::SchemaNotCurrent(ref detail) => Some(detail.clone()),
+            Error::LanguageMismatch(ref candidate) => Some(format!(
+                "the text is almost certainly written in {} (score {:.3})",
+                candidate.iso639_3,
+                candidate.score,
+            )),
+            Error::Database(ref error) => {
+                // the messages of the constraint violations tell which rule
+                // is broken, the other ones are of no use to the client
+                let (status, _) = database_status_and_code(error);
+                if status.code < 500 {
+                    error.as_db().map(|error| error.message.clone())
+                } else {
+                    None
+                }
+            },
+            Error::Sqlite(ref error) => {
+                match (sqlite_status_and_code(error), error) {
+                    ((status, _), &rusqlite::Error::SqliteFailure(_, Some(ref message)))
+                        if status.code < 500 => Some(message.clone()),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Maps the SQLSTATE of a database error to a status and a problem code.
+fn database_status_and_code(error: &postgres::Error) -> (Status, &'static str) {
+
+    if error.as_io().is_some() {
+        return (Status::ServiceUnavailable, "database-unavailable");
+    }
+
+    let code = match error.code() {
+        Some(code) => code.code(),
+        None => return (Status::InternalServerError, "database-error"),
+    };
+
+    match code {
+        "23505" => (Status::Conflict, "unique-violation"),
+        "23503" => (Status::BadRequest, "foreign-key-violation"),
+        "23514" => (Status::BadRequest, "check-violation"),
+        "23502" => (Status::BadRequest, "not-null-violation"),
+        // invalid XML content, document or comment
+        "2200M" | "2200N" | "2200S" => (Status::BadRequest, "invalid-xml"),
+        // invalid text representation, string data right truncation
+        "22P02" | "22001" => (Status::BadRequest, "invalid-value"),
+        // serialization failure, deadlock
+        "40001" | "40P01" => (Status::Conflict, "concurrent-modification"),
+        // connection exceptions, server shutting down
+        code if code.starts_with("08") || code.starts_with("57P") => {
+            (Status::ServiceUnavailable, "database-unavailable")
+        },
+        _ => (Status::InternalServerError, "database-error"),
+    }
+}
+
+/// Maps the extended result code of a SQLite error to a status and a problem code,
+/// the same as the matching PostgreSQL error.
+fn sqlite_status_and_code(error: &rusqlite::Error) -> (Status, &'static str) {
+
+    let error = match *error {
+        rusqlite::Error::SqliteFailure(ref error, _) => error,
+        _ => return (Status::InternalServerError, "database-error"),
+    };
+
+    match error.extended_code {
+        // primary key, unique
+        1555 | 2067 => (Status::Conflict, "unique-violation"),
+        787 => (Status::BadRequest, "foreign-key-violation"),
+        275 => (Status::BadRequest, "check-violation"),
+        1299 => (Status::BadRequest, "not-null-violation"),
+        _ => match error.code {
+            ffi::ErrorCode::DatabaseBusy | ffi::ErrorCode::DatabaseLocked => {
+                (Status::Conflict, "concurrent-modification")
+            },
+            ffi::ErrorCode::CannotOpen => (Status::ServiceUnavailable, "database-unavailable"),
+            _ => (Status::InternalServerError, "database-error"),
+        },
+    }
+}
+
+/// Builds a problem+json response.
+///
+/// Args:
+///
+/// `request` - the refused request
+/// `status` - the response status
+/// `code` - the problem code
+/// `detail` - the explanation of this occurrence of the problem
+pub fn problem<'r>(
+    request: &Request,
+    status: Status,
+    code: &str,
+    detail: Option<String>,
+) -> Response<'r> {
+
+    respond_with(request, status, Problem::new(status.code, status.reason, code, detail))
+}
+
+/// Builds the problem+json response of a problem, giving it the id of the request.
+fn respond_with<'r>(request: &Request, status: Status, mut problem: Problem) -> Response<'r> {
+
+    problem.request_id = logging::request_id(request);
+
+    let body = json!(problem);
+
+    Response::build()
+        .status(status)
+        .header(ContentType::new("application", "problem+json"))
+        .sized_body(Cursor::new(body.to_string()))
+        .finalize()
+}
+
+impl<'r> Responder<'r> for Error {
+
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+
+        let (status, code) = self.status_and_code();
+
+        if status == Status::InternalServerError {
+            logging::log_error(request, &self);
+        }
+
+        let mut problem = Problem::new(status.code, status.reason, code, self.detail());
+
+        if let Error::LanguageMismatch(candidate) = self {
+            problem.detected_language = Some(candidate);
+        }
+
+        Ok(respond_with(request, status, problem))
+    }
+}
+
+#[error(400)]
+fn bad_request<'r>(request: &Request) -> Response<'r> {
+    problem(request, Status::BadRequest, "invalid-input", None)
+}
+
+/// Refuses a request without a known API key, telling how to give one.
+#[error(401)]
+fn unauthorized<'r>(request: &Request) -> Response<'r> {
+    let mut response = problem(request, Status::Unauthorized, "unauthorized", None);
+    response.set_header(Header::new("WWW-Authenticate", "Bearer"));
+    response
+}
+
+#[error(403)]
+fn forbidden<'r>(request: &Request) -> Response<'r> {
+    problem(request, Status::Forbidden, "forbidden", None)
+}
+
+#[error(404)]
+fn not_found<'r>(request: &Request) -> Response<'r> {
+    problem(request, Status::NotFound, "not-found", None)
+}
+
+#[error(500)]
+fn internal_error<'r>(request: &Request) -> Response<'r> {
+    problem(request, Status::InternalServerError, "internal-error", None)
+}
+
+#[error(503)]
+fn service_unavailable<'r>(request: &Request) -> Response<'r> {
+    problem(request, Status::ServiceUnavailable, "database-unavailable", None)
+}
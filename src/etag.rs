@@ -0,0 +1,89 @@
+//! Conditional requests on sentences.
+//!
+//! Every change of a sentence row increments its `version` column
+//! (see the `sentence_version` trigger of each storage), the entity tag
+//! of a sentence is this version between double quotes.
+
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Outcome};
+
+pub const HEADER: &str = "ETag";
+
+/// Returns the entity tag of a sentence version.
+pub fn format(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Tells whether an `If-Match` or `If-None-Match` header value
+/// lists the entity tag of the given version.
+///
+/// Args:
+///
+/// `header` - the header value, a list of entity tags or `*`
+/// `version` - the current version
+/// `weak` - true if weak tags (`W/"3"`) are compared too
+fn lists(header: &str, version: i32, weak: bool) -> bool {
+
+    let current = format(version);
+
+    header.split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| {
+            if tag == "*" || tag == current {
+                return true;
+            }
+            weak && tag.starts_with("W/") && tag[2..] == current
+        })
+}
+
+/// The `If-Match` header of a modification: the change
+/// is only applied if the sentence is still at one of these versions.
+pub struct IfMatch(Option<String>);
+
+impl IfMatch {
+
+    /// Tells whether the modification can be applied, always true without the header.
+    pub fn matches(&self, version: i32) -> bool {
+        match self.0 {
+            Some(ref header) => lists(header, version, false),
+            None => true,
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for IfMatch {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<IfMatch, ()> {
+        Outcome::Success(
+            IfMatch(request.headers().get_one("If-Match").map(String::from))
+        )
+    }
+}
+
+/// The `If-None-Match` header of a read: the sentence is only
+/// sent back if it is not at one of these versions anymore.
+pub struct IfNoneMatch(Option<String>);
+
+impl IfNoneMatch {
+
+    /// Tells whether the client already has this version, always false without the header.
+    pub fn matches(&self, version: i32) -> bool {
+        match self.0 {
+            Some(ref header) => lists(header, version, true),
+            None => false,
+        }
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for IfNoneMatch {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<IfNoneMatch, ()> {
+        Outcome::Success(
+            IfNoneMatch(request.headers().get_one("If-None-Match").map(String::from))
+        )
+    }
+}
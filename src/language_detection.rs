@@ -0,0 +1,139 @@
+//! Language identification with character trigrams.
+//!
+//! A naive Bayes model is trained from the sentences already stored
+//! for every language: the corpus itself is the reference, so languages
+//! are only detected once they have enough sentences.
+//!
+//! The model is trained by a background job when the server starts,
+//! no language being detected until then, and kept by the server. The job
+//! trains it again every `language_detection.interval` seconds of the
+//! configuration (ten minutes by default, `0` disables it), and it can be
+//! trained again on demand.
+
+use rocket::{Response, State};
+use rocket::http::{ContentType, Status};
+
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use auth::{Reader, Reviewer};
+use error::Error;
+use sentence_aligner_core::language_detection::{Candidate, Detector};
+use repository::{Repository, RequestRepository, Storage};
+
+/// Languages with fewer sentences are not candidates.
+const MIN_SENTENCES: i64 = 10;
+
+/// Number of sentences read per language to train its model.
+const MAX_SENTENCES: i64 = 2000;
+
+/// Number of candidates returned by the detection endpoint.
+const MAX_CANDIDATES: usize = 5;
+
+/// Header telling which language was detected instead of the declared one.
+pub const WARNING_HEADER: &str = "X-Detected-Language";
+
+/// The detector trained from the stored sentences, shared by the requests.
+pub struct Models {
+    detector: RwLock<Arc<Detector>>,
+}
+
+impl Models {
+
+    /// Creates the models, the detector knowing no language until it is trained.
+    pub fn new() -> Models {
+        Models {
+            detector: RwLock::new(Arc::new(Detector::train(Vec::new()))),
+        }
+    }
+
+    /// Returns the last trained detector, without waiting for a training.
+    pub fn detector(&self) -> Arc<Detector> {
+        self.detector.read().unwrap().clone()
+    }
+
+    /// Trains the detector again from the stored sentences, replacing the current one.
+    ///
+    /// Args:
+    ///
+    /// `repository` - the stored sentences
+    ///
+    /// Returns:
+    ///
+    /// the new detector, knowing no language if the corpus is too small
+    pub fn refresh(&self, repository: &Repository) -> Result<Arc<Detector>, Error> {
+
+        let corpus = repository.get_training_corpus(MAX_SENTENCES, MIN_SENTENCES)?;
+        let detector = Arc::new(Detector::train(corpus));
+
+        *self.detector.write().unwrap() = detector.clone();
+
+        Ok(detector)
+    }
+}
+
+/// Starts the background job training the detector, at once and then again,
+/// so that no request waits for the training.
+///
+/// Args:
+///
+/// `storage` - the storage of the sentences
+/// `models` - the models of the server
+/// `interval` - the seconds between two runs, 0 to only run it at once
+pub fn spawn_job(storage: Storage, models: Arc<Models>, interval: u64) {
+
+    thread::spawn(move || {
+        loop {
+            // a failed run, e.g. the database being unreachable,
+            // is retried at the next interval
+            if let Ok(repository) = storage.repository() {
+                let _ = models.refresh(&*repository);
+            }
+
+            if interval == 0 {
+                return;
+            }
+
+            thread::sleep(Duration::from_secs(interval));
+        }
+    });
+}
+
+/// Returns the languages the given text is most likely written in.
+#[post("/detect-language", format="text/plain", data="<text>")]
+fn detect_language<'r>(
+    _reader: Reader,
+    models: State<Arc<Models>>,
+    text: String,
+) -> Result<Response<'r>, Error> {
+
+    let detector = models.detector();
+
+    let candidates: Vec<Candidate> = detector.detect(&text)
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .collect();
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(candidates).to_string()))
+        .finalize())
+}
+
+/// Trains the detector again without waiting for the background job,
+/// e.g. after an import.
+#[post("/maintenance/language-detection")]
+fn refresh_language_detection<'r>(
+    _reviewer: Reviewer,
+    repository: RequestRepository,
+    models: State<Arc<Models>>,
+) -> Result<Response<'r>, Error> {
+
+    models.refresh(&*repository)?;
+
+    Ok(Response::build()
+        .status(Status::NoContent)
+        .finalize())
+}
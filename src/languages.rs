@@ -1,86 +1,313 @@
-use rocket::Response;
+use rocket::{Response, State};
 use rocket::http::ContentType;
 use rocket::http::Status;
 
-use postgres::error::UNIQUE_VIOLATION;
-
 use std::io::Cursor;
 
-use db;
-use sentences::Sentence;
+use auth::{Admin, Reader};
+use config::Pagination;
+use error::Error;
+use iso639::Registry;
+use language_tag;
+use models::{
+    Language,
+    LanguageDeletionReport,
+    LanguageMergeReport,
+    MergedDuplicate,
+};
+use repository::{
+    LanguageDeletion,
+    RequestRepository,
+    SentenceQuery,
+};
+use sentences;
+
+/// Returns the ISO 639-3 code of a language code given to a route,
+/// in any of the forms accepted when creating a language.
+///
+/// Args:
+///
+/// `registry` - the ISO 639-3 codes
+/// `language_code` - the code given
+///
+/// Returns:
+///
+/// the ISO 639-3 code, the code as given if unknown: it then matches no language
+pub fn iso639_3(registry: &Registry, language_code: String) -> String {
+    registry.normalise(&language_code).unwrap_or(language_code)
+}
+
+#[derive(FromForm)]
+struct VariantFilters {
+    script: Option<String>,
+    region: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(FromForm)]
+struct DeleteOptions {
+    cascade: bool,
+}
 
+/// Creates a language. The code can be given as ISO 639-3, ISO 639-1,
+/// ISO 639-2/B or BCP 47, it is stored as ISO 639-3.
 #[post("/languages", format="text/plain", data="<iso639_3>")]
 fn create_language<'r>(
-    connection: db::DbConnection,
+    _admin: Admin,
+    repository: RequestRepository,
+    registry: State<Registry>,
     iso639_3: String,
-) -> Response<'r> {
+) -> Result<Response<'r>, Error> {
+
+    let iso639_3 = match registry.normalise(&iso639_3) {
+        Some(iso639_3) => iso639_3,
+        None => {
+            return Err(Error::InvalidInput(format!("unknown language code {}", iso639_3)));
+        }
+    };
+
+    repository.create_language(&iso639_3)?;
+
+    Ok(Response::build()
+        .status(Status::Created)
+        .raw_header("Location", format!("/languages/{}", iso639_3))
+        .finalize())
+}
+
+#[get("/languages/<language_code>/sentences")]
+fn get_all_sentences_of_language<'r>(
+    reader: Reader,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    pagination: State<Pagination>,
+    language_code: String,
+) -> Result<Response<'r>, Error> {
+
+    get_all_sentences_of_language_with_filters(
+        reader,
+        VariantFilters { script: None, region: None, limit: None },
+        repository,
+        registry,
+        pagination,
+        language_code,
+    )
+}
+
+/// Lists the sentences of a language, optionally only
+/// the ones written in a given script or for a given region.
+#[get("/languages/<language_code>/sentences?<filters>")]
+fn get_all_sentences_of_language_with_filters<'r>(
+    _reader: Reader,
+    filters: VariantFilters,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    pagination: State<Pagination>,
+    language_code: String,
+) -> Result<Response<'r>, Error> {
+
+    let iso639_3 = iso639_3(&registry, language_code);
+    let limit = sentences::page_size(filters.limit, &pagination)?;
+
+    /* unknown codes are kept as is, they then match no sentence */
+    let script = filters.script
+        .map(|script| language_tag::normalise_script(&script).unwrap_or(script));
+    let region = filters.region
+        .map(|region| language_tag::normalise_region(&region).unwrap_or(region));
+
+    let sentences = repository.get_sentences(&SentenceQuery {
+        starting_after_id: None,
+        iso639_3: Some(iso639_3),
+        script: script,
+        region: region,
+        limit: limit,
+    })?;
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(sentences).to_string()))
+        .finalize())
+}
+
+#[get("/languages")]
+fn get_all_languages<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    registry: State<Registry>,
+) -> Result<Response<'r>, Error> {
+
+    let languages: Vec<Language> = repository
+        .get_languages()?
+        .into_iter()
+        .map(|usage| Language::new(&registry, usage.iso639_3, usage.sentences))
+        .collect();
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(languages).to_string()))
+        .finalize())
+}
+
+#[get("/languages/<language_code>")]
+fn get_language<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    language_code: String,
+) -> Result<Response<'r>, Error> {
+
+    match repository.get_language(&iso639_3(&registry, language_code))? {
+        Some(usage) => {
+            let language = Language::new(&registry, usage.iso639_3, usage.sentences);
+            Ok(Response::build()
+                .header(ContentType::JSON)
+                .sized_body(Cursor::new(json!(language).to_string()))
+                .finalize())
+        },
+        None => Err(Error::NotFound),
+    }
+}
+
+#[delete("/languages/<language_code>")]
+fn delete_language<'r>(
+    admin: Admin,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    language_code: String,
+) -> Result<Response<'r>, Error> {
 
-    let result = connection.query(
-        r#"
-        INSERT INTO language(iso639_3)
-        VALUES ($1)
-        "#,
-        &[&iso639_3],
-    );
+    delete_language_with_options(
+        admin,
+        DeleteOptions { cascade: false },
+        repository,
+        registry,
+        language_code,
+    )
+}
 
-    if result.is_err() {
+/// Deletes a language. A language still used by sentences,
+/// even deleted ones, is only removed with `cascade=true`,
+/// in which case its sentences are purged along with it.
+#[delete("/languages/<language_code>?<options>")]
+fn delete_language_with_options<'r>(
+    _admin: Admin,
+    options: DeleteOptions,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    language_code: String,
+) -> Result<Response<'r>, Error> {
 
-        let error = result.unwrap_err();
+    let iso639_3 = iso639_3(&registry, language_code);
 
-        if error.code() == Some(&UNIQUE_VIOLATION) {
-            return Response::build()
+    let sentences_removed = match repository.delete_language(&iso639_3, options.cascade)? {
+        LanguageDeletion::Deleted(sentences_removed) => sentences_removed,
+        LanguageDeletion::InUse(usage) => {
+            let language = Language::new(&registry, usage.iso639_3, usage.sentences);
+            return Ok(Response::build()
                 .status(Status::Conflict)
-                .finalize();
+                .header(ContentType::JSON)
+                .sized_body(Cursor::new(json!(language).to_string()))
+                .finalize());
+        },
+    };
+
+    let report = LanguageDeletionReport {
+        iso639_3: iso639_3,
+        sentences_removed: sentences_removed,
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(report).to_string()))
+        .finalize())
+}
+
+/// Gives a language another code, which can be given in the same forms
+/// as when creating a language, moving its sentences to it.
+/// A code already used is refused, the languages being merged instead.
+#[put("/languages/<language_code>", format="text/plain", data="<new_code>")]
+fn rename_language<'r>(
+    admin: Admin,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    language_code: String,
+    new_code: String,
+) -> Result<Response<'r>, Error> {
+
+    let author = admin.0.author();
+    let language_code = iso639_3(&registry, language_code);
+
+    let new_iso639_3 = match registry.normalise(&new_code) {
+        Some(new_iso639_3) => new_iso639_3,
+        None => {
+            return Err(Error::InvalidInput(format!("unknown language code {}", new_code)));
         }
+    };
 
-        panic!(format!("{}", error));
+    if language_code == new_iso639_3 {
+        return Err(Error::InvalidInput(
+            format!("the language already has the code {}", new_iso639_3)
+        ));
     }
 
-    Response::build()
-        .status(Status::Created)
-        .finalize()
+    repository.rename_language(&language_code, &new_iso639_3, &author)?;
+
+    let language = match repository.get_language(&new_iso639_3)? {
+        Some(usage) => Language::new(&registry, usage.iso639_3, usage.sentences),
+        None => return Err(Error::NotFound),
+    };
+
+    Ok(Response::build()
+        .raw_header("Location", format!("/languages/{}", new_iso639_3))
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(language).to_string()))
+        .finalize())
 }
 
-#[get("/languages/<language_code>/sentences")]
-fn get_all_sentences_of_language<'r>(
-    connection: db::DbConnection,
+/// Moves all the sentences of a language to another one, then deletes it.
+///
+/// As the content of a visible sentence is unique per language variant,
+/// a visible sentence whose content already exists in the same variant
+/// of the target language cannot be moved: the target one is kept
+/// and the other one is removed. The moved sentences are to review again.
+#[post("/languages/<language_code>/merge-into/<target_code>")]
+fn merge_language<'r>(
+    admin: Admin,
+    repository: RequestRepository,
+    registry: State<Registry>,
     language_code: String,
-) -> Response<'r> {
-
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                sentence.content,
-                iso639_3,
-                sentence.structure::text
-            FROM language
-            JOIN sentence ON (sentence.language_id = language.id)
-            WHERE iso639_3 = $1
-            ORDER BY
-                sentence.added_at,
-                sentence.id
-            LIMIT 100
-        "#,
-        &[&language_code],
-    );
-
-    let rows = result.expect("problem while getting sentence");
-
-    let mut sentences : Vec<Sentence> = Vec::with_capacity(100);
-
-    for row in rows.iter() {
-        let sentence = Sentence {
-            id: row.get(0),
-            text: row.get(1),
-            iso639_3: row.get(2),
-            structure: row.get(3),
-        };
-        sentences.push(sentence);
+    target_code: String,
+) -> Result<Response<'r>, Error> {
+
+    let author = admin.0.author();
+    let language_code = iso639_3(&registry, language_code);
+    let target_code = iso639_3(&registry, target_code);
+
+    if language_code == target_code {
+        return Err(Error::InvalidInput(
+            "a language cannot be merged into itself".to_string()
+        ));
     }
 
-    Response::build()
+    let merge = repository.merge_language(&language_code, &target_code, &author)?;
+
+    let duplicates: Vec<MergedDuplicate> = merge.duplicates.into_iter()
+        .map(|(removed, kept)| {
+            MergedDuplicate {
+                removed: removed,
+                kept: kept,
+            }
+        })
+        .collect();
+
+    let report = LanguageMergeReport {
+        from: language_code,
+        into: target_code,
+        moved: merge.moved,
+        duplicates: duplicates,
+    };
+
+    Ok(Response::build()
         .header(ContentType::JSON)
-        .sized_body(Cursor::new(json!(sentences).to_string()))
-        .finalize()
+        .sized_body(Cursor::new(json!(report).to_string()))
+        .finalize())
 }
@@ -0,0 +1,257 @@
+//! The sentence aligner server, shared by its binary (`src/main.rs`)
+//! and the administration command line (`src/bin/sentence-aligner-cli.rs`),
+//! on top of `sentence-aligner-core` (`core/`) whose modules it re-exports.
+
+#![feature(plugin, custom_derive)]
+#![plugin(rocket_codegen)]
+
+extern crate rocket;
+extern crate openssl;
+extern crate postgres;
+extern crate uuid;
+extern crate serde_json;
+extern crate sentence_aligner_core;
+
+#[macro_use] extern crate rocket_contrib;
+
+extern crate r2d2;
+extern crate r2d2_postgres;
+extern crate rusqlite;
+extern crate toml;
+
+pub use sentence_aligner_core::{
+    canonical,
+    diff,
+    iso639,
+    language_tag,
+    models,
+    structure,
+    validation,
+};
+
+pub mod config;
+mod db;
+mod cors;
+pub mod auth;
+mod api_keys;
+mod users;
+mod sentences;
+mod one_sentence;
+mod languages;
+mod revisions;
+mod reviews;
+mod language_detection;
+mod maintenance;
+mod monitoring;
+mod logging;
+pub mod near_duplicates;
+mod etag;
+pub mod error;
+pub mod repository;
+pub mod migrations;
+
+use std::sync::Arc;
+
+use config::Config;
+use repository::Storage;
+
+/// Builds the server on a storage whose schema is up to date,
+/// and starts its background jobs.
+///
+/// Args:
+///
+/// `config` - the configuration
+/// `storage` - the storage of the sentences
+pub fn server(config: Config, storage: Storage) -> rocket::Rocket {
+
+    let features = config.features;
+
+    if features.near_duplicates {
+        near_duplicates::spawn_job(storage.clone(), config.near_duplicates_interval);
+    }
+
+    let language_models = Arc::new(language_detection::Models::new());
+
+    if features.language_detection {
+        language_detection::spawn_job(
+            storage.clone(),
+            language_models.clone(),
+            config.language_detection_interval,
+        );
+    }
+
+    let metrics = Arc::new(monitoring::Metrics::new());
+
+    logging::log_panics();
+
+    let mut server = rocket::ignite()
+        .attach(logging::RequestLog())
+        .attach(cors::CORS::new(config.cors))
+        .attach(monitoring::RequestMetrics(metrics.clone()))
+        .manage(metrics)
+        .manage(language_models)
+        .manage(storage)
+        .manage(iso639::Registry::load())
+        .manage(config.canonicalizer)
+        .manage(config.pagination)
+        .manage(features)
+        .mount(
+            "/",
+            routes![
+                sentences::create_sentence,
+                sentences::get_all_sentences,
+                sentences::get_all_sentences_with_last_uuid,
+                one_sentence::get_sentence,
+                one_sentence::edit_sentence_text,
+                one_sentence::edit_sentence_structure,
+                one_sentence::edit_sentence_language,
+                one_sentence::patch_sentence,
+                one_sentence::delete_sentence,
+                one_sentence::delete_sentence_with_options,
+                one_sentence::restore_sentence,
+                one_sentence::merge_sentence,
+                revisions::get_sentence_history,
+                revisions::revert_sentence,
+                reviews::review_sentence,
+                reviews::get_sentence_reviews,
+                reviews::submit_sentence,
+                reviews::get_review_queue,
+                reviews::get_review_queue_with_filters,
+                maintenance::get_content_collisions,
+                languages::create_language,
+                languages::get_all_languages,
+                languages::get_language,
+                languages::delete_language,
+                languages::delete_language_with_options,
+                languages::rename_language,
+                languages::merge_language,
+                languages::get_all_sentences_of_language,
+                languages::get_all_sentences_of_language_with_filters,
+                monitoring::health,
+                monitoring::ready,
+                monitoring::metrics,
+                api_keys::create_api_key,
+                api_keys::get_api_keys,
+                api_keys::revoke_api_key,
+                users::get_users,
+                users::get_user,
+                users::get_user_contributions,
+            ]
+        );
+
+    if features.language_detection {
+        server = server.mount(
+            "/",
+            routes![
+                language_detection::detect_language,
+                language_detection::refresh_language_detection,
+            ],
+        );
+    }
+
+    if features.near_duplicates {
+        server = server.mount(
+            "/",
+            routes![
+                near_duplicates::refresh_near_duplicates,
+                near_duplicates::get_near_duplicates_of_language,
+            ],
+        );
+    }
+
+    server
+        .catch(errors![
+            error::bad_request,
+            error::unauthorized,
+            error::forbidden,
+            error::not_found,
+            error::internal_error,
+            error::service_unavailable,
+        ])
+}
+
+#[cfg(test)]
+mod tests {
+
+    use rocket::http::{ContentType, Header, Status};
+    use rocket::local::Client;
+
+    use canonical::Canonicalizer;
+    use config::{CorsConfig, Features, Pagination, StorageConfig};
+    use models::Role;
+
+    use super::*;
+
+    const CONTRIBUTOR_KEY: &str = "contributor-key";
+
+    /// Returns a client of a server on the memory storage, without background jobs.
+    fn client() -> Client {
+
+        let storage = Storage::open(&StorageConfig::Memory).unwrap();
+        {
+            let repository = storage.repository().unwrap();
+            repository.create_language("eng").unwrap();
+            repository
+                .create_api_key("contributor", Role::Contributor, &auth::hash_key(CONTRIBUTOR_KEY))
+                .unwrap();
+        }
+
+        let config = Config {
+            storage: StorageConfig::Memory,
+            pagination: Pagination {
+                default_page_size: 100,
+                max_page_size: 1000,
+            },
+            cors: CorsConfig {
+                allowed_origins: vec!["*".to_string()],
+                allowed_methods: vec!["GET".to_string()],
+                allowed_headers: Vec::new(),
+                exposed_headers: Vec::new(),
+                max_age: 0,
+                allow_credentials: false,
+            },
+            canonicalizer: Canonicalizer::new(true, true, true, false),
+            features: Features {
+                language_detection: false,
+                near_duplicates: false,
+            },
+            near_duplicates_interval: 0,
+            language_detection_interval: 0,
+        };
+
+        Client::new(server(config, storage)).unwrap()
+    }
+
+    #[test]
+    fn test_post_and_get_sentence_on_memory_storage() {
+
+        let client = client();
+
+        let response = client.post("/sentences")
+            .header(ContentType::JSON)
+            .header(Header::new("Authorization", format!("Bearer {}", CONTRIBUTOR_KEY)))
+            .body(r#"{"text": "Hello.", "iso639_3": "eng"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Created);
+        let location = response.headers().get_one("Location").unwrap().to_string();
+
+        let mut response = client.get(location)
+            .header(Header::new("Authorization", format!("Bearer {}", CONTRIBUTOR_KEY)))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.body_string().unwrap().contains("Hello."));
+    }
+
+    #[test]
+    fn test_post_sentence_without_key_returns_401() {
+
+        let response = client().post("/sentences")
+            .header(ContentType::JSON)
+            .body(r#"{"text": "Hello.", "iso639_3": "eng"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+}
@@ -0,0 +1,183 @@
+//! Request logging: every request gets an id, taken from its `X-Request-Id`
+//! header or generated, sent back in the same header and in the problems,
+//! and is logged as one JSON line on the standard output.
+
+use rocket::{Data, Request, Response};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+
+use uuid::Uuid;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Longest request id accepted from a client.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+thread_local! {
+    /// Start of the request being handled, Rocket handling
+    /// a request on one thread from its start to its end.
+    static STARTED_AT: Cell<Option<Instant>> = Cell::new(None);
+    /// Id of the request being handled, for the panics.
+    static REQUEST_ID: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Returns the id of a request.
+///
+/// Args:
+///
+/// `request` - the request, once seen by the `RequestLog` fairing
+pub fn request_id(request: &Request) -> Option<String> {
+    request.headers().get_one(REQUEST_ID_HEADER).map(|id| id.to_string())
+}
+
+/// Tells whether an id given by a client can be kept:
+/// short, and printable without escaping.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() &&
+        id.len() <= MAX_REQUEST_ID_LENGTH &&
+        id.chars().all(|character| {
+            character.is_ascii_alphanumeric() || "-_.:".contains(character)
+        })
+}
+
+/// Returns the sentence a request is about: the first UUID of its path,
+/// else the one of the `Location` of a created sentence.
+fn sentence_id(request: &Request, response: &Response) -> Option<Uuid> {
+
+    let from_path = request.uri()
+        .segments()
+        .filter_map(|segment| Uuid::parse_str(segment).ok())
+        .next();
+
+    from_path.or_else(|| {
+        let location = response.headers().get_one("Location")?;
+        if !location.starts_with("/sentences/") {
+            return None;
+        }
+        Uuid::parse_str(&location["/sentences/".len()..]).ok()
+    })
+}
+
+/// Fairing giving an id to every request and logging it once answered.
+pub struct RequestLog();
+
+impl Fairing for RequestLog {
+
+    fn info(&self) -> Info {
+        Info {
+            name: "Log the requests with their id",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+
+        let id = match request.headers().get_one(REQUEST_ID_HEADER) {
+            Some(id) if is_valid_request_id(id) => id.to_string(),
+            _ => Uuid::new_v4().to_string(),
+        };
+
+        STARTED_AT.with(|started_at| started_at.set(Some(Instant::now())));
+        REQUEST_ID.with(|request_id| *request_id.borrow_mut() = Some(id.clone()));
+
+        request.replace_header(Header::new(REQUEST_ID_HEADER, id));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+
+        let id = request_id(request);
+
+        let latency_ms = STARTED_AT.with(|started_at| started_at.take()).map(|started_at| {
+            let elapsed = started_at.elapsed();
+            elapsed.as_secs() as f64 * 1e3 + elapsed.subsec_nanos() as f64 / 1e6
+        });
+
+        let route = request.route().map(|route| route.uri.path().to_string());
+
+        println!(
+            "{}",
+            json!({
+                "timestamp": timestamp(),
+                "request_id": id,
+                "method": request.method().as_str(),
+                "path": request.uri().path(),
+                "route": route,
+                "status": response.status().code,
+                "latency_ms": latency_ms,
+                "sentence_id": sentence_id(request, response),
+            }),
+        );
+
+        REQUEST_ID.with(|request_id| *request_id.borrow_mut() = None);
+
+        if let Some(id) = id {
+            response.set_header(Header::new(REQUEST_ID_HEADER, id));
+        }
+    }
+}
+
+/// Logs an error answered as a server error, as a JSON line
+/// with the id of its request.
+///
+/// Args:
+///
+/// `request` - the request that has failed
+/// `error` - the cause of the failure
+pub fn log_error(request: &Request, error: &fmt::Debug) {
+
+    println!(
+        "{}",
+        json!({
+            "timestamp": timestamp(),
+            "request_id": request_id(request),
+            "error": format!("{:?}", error),
+        }),
+    );
+}
+
+/// Logs the panics as JSON lines too, with the id of the request
+/// being handled, if any, before Rocket reports them.
+pub fn log_panics() {
+
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+
+        let message = info.payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned());
+
+        let request_id = REQUEST_ID
+            .try_with(|request_id| request_id.borrow().clone())
+            .unwrap_or(None);
+
+        println!(
+            "{}",
+            json!({
+                "timestamp": timestamp(),
+                "request_id": request_id,
+                "panic": message,
+                "location": info.location().map(|location| {
+                    format!("{}:{}", location.file(), location.line())
+                }),
+            }),
+        );
+
+        default_hook(info);
+    }));
+}
+
+/// Returns the current time, in seconds since the Unix epoch.
+fn timestamp() -> f64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => {
+            since_epoch.as_secs() as f64 + since_epoch.subsec_nanos() as f64 / 1e9
+        },
+        Err(_) => 0.0,
+    }
+}
@@ -0,0 +1,61 @@
+//! Maintenance jobs run on demand over the whole corpus.
+
+use rocket::{Response, State};
+use rocket::http::ContentType;
+
+use uuid::Uuid;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+use auth::Reviewer;
+use canonical::Canonicalizer;
+use error::Error;
+use models::ContentCollision;
+use repository::RequestRepository;
+
+/// Lists the stored sentences, deleted ones included, that would collide
+/// if their content was canonicalised as it now is on creation and edition.
+/// Nothing is modified, the duplicates are left to be merged or deleted.
+#[get("/maintenance/content-collisions")]
+fn get_content_collisions<'r>(
+    _reviewer: Reviewer,
+    repository: RequestRepository,
+    canonicalizer: State<Canonicalizer>,
+) -> Result<Response<'r>, Error> {
+
+    let sentences = repository.get_all_sentences(true)?;
+
+    let mut groups: BTreeMap<(String, Option<String>, Option<String>, String), Vec<Uuid>> = BTreeMap::new();
+
+    for sentence in sentences {
+        let sentence_uuid = match sentence.id {
+            Some(sentence_uuid) => sentence_uuid,
+            None => continue,
+        };
+        let key = (
+            sentence.iso639_3,
+            sentence.script,
+            sentence.region,
+            canonicalizer.text(&sentence.text),
+        );
+        groups.entry(key).or_insert_with(Vec::new).push(sentence_uuid);
+    }
+
+    let collisions: Vec<ContentCollision> = groups.into_iter()
+        .filter(|&(_, ref sentences)| sentences.len() > 1)
+        .map(|((iso639_3, script, region, canonical), sentences)| {
+            ContentCollision {
+                iso639_3: iso639_3,
+                script: script,
+                region: region,
+                canonical: canonical,
+                sentences: sentences,
+            }
+        })
+        .collect();
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(collisions).to_string()))
+        .finalize())
+}
@@ -0,0 +1,641 @@
+//! Versioned schema migrations.
+//!
+//! Each storage has its numbered migrations in `migrations/<storage>/`,
+//! embedded in the binary: `NNNN_name.up.sql` applies one and
+//! `NNNN_name.down.sql` reverts it. The applied ones are recorded in the
+//! `schema_migrations` table with a checksum of their up script, so that
+//! a migration edited after having been applied is noticed.
+//!
+//! The server never changes the schema by itself: `sentence-aligner migrate up`
+//! does, and the server refuses to start while the schema is not the one
+//! it has been built for.
+
+use std::fmt;
+
+use error::Error;
+
+/// A schema change, with the script undoing it.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+impl Migration {
+
+    /// Returns the checksum of the up script, as 16 hexadecimal digits.
+    ///
+    /// It is a 64 bits FNV-1a hash, which unlike the hasher
+    /// of the standard library does not change between Rust versions.
+    pub fn checksum(&self) -> String {
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.up.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        format!("{:016x}", hash)
+    }
+}
+
+/// The PostgreSQL migrations, by version.
+pub const POSTGRES: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: include_str!("../migrations/postgres/0001_initial_schema.up.sql"),
+        down: include_str!("../migrations/postgres/0001_initial_schema.down.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "sentence_revisions",
+        up: include_str!("../migrations/postgres/0002_sentence_revisions.up.sql"),
+        down: include_str!("../migrations/postgres/0002_sentence_revisions.down.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "soft_delete",
+        up: include_str!("../migrations/postgres/0003_soft_delete.up.sql"),
+        down: include_str!("../migrations/postgres/0003_soft_delete.down.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "restrict_language_deletion",
+        up: include_str!("../migrations/postgres/0004_restrict_language_deletion.up.sql"),
+        down: include_str!("../migrations/postgres/0004_restrict_language_deletion.down.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "language_variants",
+        up: include_str!("../migrations/postgres/0005_language_variants.up.sql"),
+        down: include_str!("../migrations/postgres/0005_language_variants.down.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "near_duplicates",
+        up: include_str!("../migrations/postgres/0006_near_duplicates.up.sql"),
+        down: include_str!("../migrations/postgres/0006_near_duplicates.down.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "sentence_versions",
+        up: include_str!("../migrations/postgres/0007_sentence_versions.up.sql"),
+        down: include_str!("../migrations/postgres/0007_sentence_versions.down.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "api_keys",
+        up: include_str!("../migrations/postgres/0008_api_keys.up.sql"),
+        down: include_str!("../migrations/postgres/0008_api_keys.down.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "users",
+        up: include_str!("../migrations/postgres/0009_users.up.sql"),
+        down: include_str!("../migrations/postgres/0009_users.down.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "reviews",
+        up: include_str!("../migrations/postgres/0010_reviews.up.sql"),
+        down: include_str!("../migrations/postgres/0010_reviews.down.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "visible_sentence_content",
+        up: include_str!("../migrations/postgres/0011_visible_sentence_content.up.sql"),
+        down: include_str!("../migrations/postgres/0011_visible_sentence_content.down.sql"),
+    },
+];
+
+/// The SQLite migrations, by version.
+pub const SQLITE: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: include_str!("../migrations/sqlite/0001_initial_schema.up.sql"),
+        down: include_str!("../migrations/sqlite/0001_initial_schema.down.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "api_keys",
+        up: include_str!("../migrations/sqlite/0002_api_keys.up.sql"),
+        down: include_str!("../migrations/sqlite/0002_api_keys.down.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "users",
+        up: include_str!("../migrations/sqlite/0003_users.up.sql"),
+        down: include_str!("../migrations/sqlite/0003_users.down.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "reviews",
+        up: include_str!("../migrations/sqlite/0004_reviews.up.sql"),
+        down: include_str!("../migrations/sqlite/0004_reviews.down.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "visible_sentence_content",
+        up: include_str!("../migrations/sqlite/0005_visible_sentence_content.up.sql"),
+        down: include_str!("../migrations/sqlite/0005_visible_sentence_content.down.sql"),
+    },
+];
+
+/// A migration recorded as applied.
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    /// UTC date
+    pub applied_at: String,
+}
+
+/// Runs the migrations on a storage.
+pub trait MigrationStore {
+
+    /// Returns the migrations of the storage, by version.
+    fn migrations(&self) -> &'static [Migration];
+
+    /// Returns the applied migrations, by version,
+    /// creating the `schema_migrations` table if it does not exist yet.
+    fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error>;
+
+    /// Runs the up script of a migration and records it, in one transaction.
+    fn apply(&self, migration: &Migration) -> Result<(), Error>;
+
+    /// Runs the down script of a migration and forgets it, in one transaction.
+    fn revert(&self, migration: &Migration) -> Result<(), Error>;
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// a query failed
+    Storage(Error),
+    /// the migration of this version has been modified since it has been applied
+    Modified(i64),
+    /// the applied migration of this version is unknown, the schema being
+    /// more recent than the binary
+    Unknown(i64),
+    /// the schema is behind, starting with the migration of this version
+    Pending(i64),
+    /// there is no migration of this version
+    NoSuchVersion(i64),
+}
+
+impl From<Error> for MigrationError {
+    fn from(error: Error) -> MigrationError {
+        MigrationError::Storage(error)
+    }
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MigrationError::Storage(ref error) => {
+                write!(formatter, "migration failed: {}", error)
+            },
+            MigrationError::Modified(version) => {
+                write!(formatter, "migration {} has been modified since it has been applied", version)
+            },
+            MigrationError::Unknown(version) => {
+                write!(formatter, "migration {} is applied but unknown to this version of the server", version)
+            },
+            MigrationError::Pending(version) => {
+                write!(
+                    formatter,
+                    "the schema is behind, migration {} is not applied (see `sentence-aligner migrate up`)",
+                    version,
+                )
+            },
+            MigrationError::NoSuchVersion(version) => {
+                write!(formatter, "there is no migration {}", version)
+            },
+        }
+    }
+}
+
+/// Where a migration stands.
+pub enum State {
+    Applied(AppliedMigration),
+    /// applied, but with another checksum
+    Modified(AppliedMigration),
+    Pending,
+}
+
+/// Where the schema of a storage stands.
+pub struct Status {
+    /// the migrations of the storage, by version
+    pub migrations: Vec<(&'static Migration, State)>,
+    /// the applied migrations which are not in the binary
+    pub unknown: Vec<AppliedMigration>,
+}
+
+impl Status {
+
+    /// Checks that the applied migrations are all known and unchanged.
+    fn check_applied(&self) -> Result<(), MigrationError> {
+
+        if let Some(applied) = self.unknown.first() {
+            return Err(MigrationError::Unknown(applied.version));
+        }
+
+        for &(migration, ref state) in self.migrations.iter() {
+            if let State::Modified(_) = *state {
+                return Err(MigrationError::Modified(migration.version));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the applied migrations, latest first.
+    fn applied(&self) -> Vec<&'static Migration> {
+        self.migrations.iter()
+            .rev()
+            .filter(|&&(_, ref state)| !is_pending(state))
+            .map(|&(migration, _)| migration)
+            .collect()
+    }
+
+    /// Tells whether the storage has a migration of this version.
+    fn has_version(&self, version: i64) -> bool {
+        self.migrations.iter().any(|&(migration, _)| migration.version == version)
+    }
+}
+
+fn is_pending(state: &State) -> bool {
+    match *state {
+        State::Pending => true,
+        _ => false,
+    }
+}
+
+/// Compares the migrations of a storage with the applied ones.
+///
+/// Args:
+///
+/// `store` - the storage
+pub fn status(store: &MigrationStore) -> Result<Status, Error> {
+
+    let mut applied = store.applied_migrations()?;
+
+    let migrations: Vec<(&'static Migration, State)> = store.migrations().iter()
+        .map(|migration| {
+            let position = applied.iter().position(|applied| applied.version == migration.version);
+            let state = match position {
+                Some(position) => {
+                    let applied = applied.remove(position);
+                    if applied.checksum == migration.checksum() {
+                        State::Applied(applied)
+                    } else {
+                        State::Modified(applied)
+                    }
+                },
+                None => State::Pending,
+            };
+            (migration, state)
+        })
+        .collect();
+
+    Ok(Status {
+        migrations: migrations,
+        unknown: applied,
+    })
+}
+
+/// Checks that the schema of a storage is the one of the binary:
+/// all its migrations applied, unchanged, and no other one.
+///
+/// Args:
+///
+/// `store` - the storage
+pub fn check(store: &MigrationStore) -> Result<(), MigrationError> {
+
+    let status = status(store)?;
+    status.check_applied()?;
+
+    match status.migrations.iter().find(|&&(_, ref state)| is_pending(state)) {
+        Some(&(migration, _)) => Err(MigrationError::Pending(migration.version)),
+        None => Ok(()),
+    }
+}
+
+/// Applies the pending migrations, by version.
+///
+/// Args:
+///
+/// `store` - the storage
+/// `target` - the last version to apply, all of them if None
+///
+/// Returns:
+///
+/// the applied migrations
+pub fn up(store: &MigrationStore, target: Option<i64>) -> Result<Vec<&'static Migration>, MigrationError> {
+
+    let status = status(store)?;
+    status.check_applied()?;
+
+    if let Some(target) = target {
+        if !status.has_version(target) {
+            return Err(MigrationError::NoSuchVersion(target));
+        }
+    }
+
+    let mut applied = Vec::new();
+
+    for &(migration, ref state) in status.migrations.iter() {
+        if !is_pending(state) || target.map_or(false, |target| migration.version > target) {
+            continue;
+        }
+        store.apply(migration)?;
+        applied.push(migration);
+    }
+
+    Ok(applied)
+}
+
+/// Reverts applied migrations, latest first.
+///
+/// Args:
+///
+/// `store` - the storage
+/// `target` - the version to go back to, all the migrations above it
+///            being reverted (`0` reverts them all), only the latest one if None
+///
+/// Returns:
+///
+/// the reverted migrations
+pub fn down(store: &MigrationStore, target: Option<i64>) -> Result<Vec<&'static Migration>, MigrationError> {
+
+    let status = status(store)?;
+    status.check_applied()?;
+
+    let to_revert: Vec<&'static Migration> = match target {
+        Some(target) => {
+            if target != 0 && !status.has_version(target) {
+                return Err(MigrationError::NoSuchVersion(target));
+            }
+            status.applied().into_iter()
+                .filter(|migration| migration.version > target)
+                .collect()
+        },
+        None => status.applied().into_iter().take(1).collect(),
+    };
+
+    for migration in to_revert.iter() {
+        store.revert(migration)?;
+    }
+
+    Ok(to_revert)
+}
+
+/// Runs the `migrate` command: `up [VERSION]`, `down [VERSION]` or `status`.
+///
+/// Args:
+///
+/// `store` - the storage
+/// `args` - the arguments following `migrate`
+///
+/// Returns:
+///
+/// the exit code of the process
+pub fn run_command(store: &MigrationStore, args: &[String]) -> i32 {
+
+    let usage = "usage: sentence-aligner migrate up [VERSION] | down [VERSION] | status";
+
+    let target = match args.get(1).map(|version| version.parse::<i64>()) {
+        Some(Ok(version)) => Some(version),
+        Some(Err(_)) => {
+            eprintln!("{}", usage);
+            return 2;
+        },
+        None => None,
+    };
+
+    let command = args.first().map(|command| command.as_str());
+
+    let result = match command {
+        Some("up") => up(store, target).map(|migrations| {
+            for migration in migrations {
+                println!("applied {:04} {}", migration.version, migration.name);
+            }
+        }),
+        Some("down") => down(store, target).map(|migrations| {
+            for migration in migrations {
+                println!("reverted {:04} {}", migration.version, migration.name);
+            }
+        }),
+        Some("status") if target.is_none() => print_status(store),
+        _ => {
+            eprintln!("{}", usage);
+            return 2;
+        },
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{}", error);
+            1
+        },
+    }
+}
+
+/// Prints one line per migration, known or not, with its state.
+fn print_status(store: &MigrationStore) -> Result<(), MigrationError> {
+
+    let status = status(store)?;
+
+    for (migration, state) in status.migrations {
+        match state {
+            State::Applied(applied) => {
+                println!("{:04} {} applied at {}", migration.version, migration.name, applied.applied_at);
+            },
+            State::Modified(applied) => {
+                println!(
+                    "{:04} {} applied at {}, MODIFIED since",
+                    migration.version,
+                    migration.name,
+                    applied.applied_at,
+                );
+            },
+            State::Pending => {
+                println!("{:04} {} pending", migration.version, migration.name);
+            },
+        }
+    }
+
+    for applied in status.unknown {
+        println!("{:04} {} applied at {}, UNKNOWN", applied.version, applied.name, applied.applied_at);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::cell::RefCell;
+
+    use super::*;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "languages",
+            up: "CREATE TABLE language (iso639_3 TEXT);",
+            down: "DROP TABLE language;",
+        },
+        Migration {
+            version: 2,
+            name: "sentences",
+            up: "CREATE TABLE sentence (content TEXT);",
+            down: "DROP TABLE sentence;",
+        },
+        Migration {
+            version: 3,
+            name: "reviews",
+            up: "CREATE TABLE review (status TEXT);",
+            down: "DROP TABLE review;",
+        },
+    ];
+
+    /// Records the applied migrations without running them.
+    struct TestStore {
+        applied: RefCell<Vec<(i64, String, String)>>,
+    }
+
+    impl TestStore {
+
+        /// Returns a store with the migrations applied up to a version included.
+        fn applied_up_to(version: i64) -> TestStore {
+            TestStore {
+                applied: RefCell::new(
+                    MIGRATIONS.iter()
+                        .filter(|migration| migration.version <= version)
+                        .map(|migration| (migration.version, migration.name.to_string(), migration.checksum()))
+                        .collect()
+                ),
+            }
+        }
+
+        fn versions(&self) -> Vec<i64> {
+            self.applied.borrow().iter().map(|&(version, _, _)| version).collect()
+        }
+    }
+
+    impl MigrationStore for TestStore {
+
+        fn migrations(&self) -> &'static [Migration] {
+            MIGRATIONS
+        }
+
+        fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+            Ok(
+                self.applied.borrow().iter()
+                    .map(|&(version, ref name, ref checksum)| {
+                        AppliedMigration {
+                            version: version,
+                            name: name.clone(),
+                            checksum: checksum.clone(),
+                            applied_at: "2018-01-01T00:00:00Z".to_string(),
+                        }
+                    })
+                    .collect()
+            )
+        }
+
+        fn apply(&self, migration: &Migration) -> Result<(), Error> {
+            self.applied.borrow_mut().push(
+                (migration.version, migration.name.to_string(), migration.checksum()),
+            );
+            Ok(())
+        }
+
+        fn revert(&self, migration: &Migration) -> Result<(), Error> {
+            self.applied.borrow_mut().retain(|&(version, _, _)| version != migration.version);
+            Ok(())
+        }
+    }
+
+    fn versions(migrations: Vec<&'static Migration>) -> Vec<i64> {
+        migrations.iter().map(|migration| migration.version).collect()
+    }
+
+    #[test]
+    fn test_pending() {
+
+        let store = TestStore::applied_up_to(1);
+
+        match check(&store) {
+            Err(MigrationError::Pending(2)) => {},
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert_eq!(versions(up(&store, None).unwrap()), vec![2, 3]);
+        assert_eq!(store.versions(), vec![1, 2, 3]);
+        assert!(check(&store).is_ok());
+    }
+
+    #[test]
+    fn test_modified() {
+
+        let store = TestStore::applied_up_to(2);
+        store.applied.borrow_mut()[1].2 = "0000000000000000".to_string();
+
+        match check(&store) {
+            Err(MigrationError::Modified(2)) => {},
+            other => panic!("unexpected {:?}", other),
+        }
+        match up(&store, None) {
+            Err(MigrationError::Modified(2)) => {},
+            other => panic!("unexpected {:?}", other.map(versions)),
+        }
+        assert_eq!(store.versions(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_unknown() {
+
+        let store = TestStore::applied_up_to(3);
+        store.applied.borrow_mut().push((4, "alignments".to_string(), "0000000000000000".to_string()));
+
+        match check(&store) {
+            Err(MigrationError::Unknown(4)) => {},
+            other => panic!("unexpected {:?}", other),
+        }
+        match down(&store, None) {
+            Err(MigrationError::Unknown(4)) => {},
+            other => panic!("unexpected {:?}", other.map(versions)),
+        }
+    }
+
+    #[test]
+    fn test_up_to_version() {
+
+        let store = TestStore::applied_up_to(0);
+
+        assert_eq!(versions(up(&store, Some(2)).unwrap()), vec![1, 2]);
+        assert_eq!(store.versions(), vec![1, 2]);
+
+        match up(&store, Some(7)) {
+            Err(MigrationError::NoSuchVersion(7)) => {},
+            other => panic!("unexpected {:?}", other.map(versions)),
+        }
+    }
+
+    #[test]
+    fn test_down() {
+
+        let store = TestStore::applied_up_to(3);
+
+        assert_eq!(versions(down(&store, None).unwrap()), vec![3]);
+        assert_eq!(store.versions(), vec![1, 2]);
+
+        assert_eq!(versions(down(&store, Some(0)).unwrap()), vec![2, 1]);
+        assert!(store.versions().is_empty());
+    }
+}
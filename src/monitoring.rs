@@ -0,0 +1,256 @@
+//! Endpoints of the operations: liveness, readiness, and the metrics
+//! of the requests, of the connection pool and of the corpus,
+//! in the Prometheus text format.
+
+use rocket::{Data, Request, Response, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::ContentType;
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use error::Error;
+use migrations::{self, MigrationError};
+use repository::Storage;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+thread_local! {
+    /// Start of the request being handled, Rocket handling
+    /// a request on one thread from its start to its end.
+    static STARTED_AT: Cell<Option<Instant>> = Cell::new(None);
+}
+
+/// Counts and latencies of the requests of a route.
+struct RouteMetrics {
+    /// number of responses, by status
+    responses: BTreeMap<u16, u64>,
+    /// number of requests handled within each bucket bound
+    buckets: [u64; 11],
+    count: u64,
+    seconds: f64,
+}
+
+/// Metrics of the requests, by method and route.
+pub struct Metrics {
+    routes: Mutex<BTreeMap<(String, String), RouteMetrics>>,
+}
+
+impl Metrics {
+
+    pub fn new() -> Metrics {
+        Metrics { routes: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Records a handled request.
+    ///
+    /// Args:
+    ///
+    /// `method` - the request method
+    /// `route` - the path of the matched route, e.g. `/sentences/<sentence_uuid>`
+    /// `status` - the response status
+    /// `seconds` - the time taken to handle the request
+    fn record(&self, method: &str, route: &str, status: u16, seconds: f64) {
+
+        let mut routes = match self.routes.lock() {
+            Ok(routes) => routes,
+            Err(_) => return,
+        };
+
+        let metrics = routes
+            .entry((route.to_string(), method.to_string()))
+            .or_insert_with(|| RouteMetrics {
+                responses: BTreeMap::new(),
+                buckets: [0; 11],
+                count: 0,
+                seconds: 0.0,
+            });
+
+        *metrics.responses.entry(status).or_insert(0) += 1;
+        for (bound, bucket) in BUCKETS.iter().zip(metrics.buckets.iter_mut()) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        metrics.count += 1;
+        metrics.seconds += seconds;
+    }
+
+    /// Writes the request metrics in the Prometheus text format.
+    fn write(&self, text: &mut String) {
+
+        let routes = match self.routes.lock() {
+            Ok(routes) => routes,
+            Err(_) => return,
+        };
+
+        text.push_str("# HELP sentence_aligner_http_requests_total Requests handled, by method, route and status.\n");
+        text.push_str("# TYPE sentence_aligner_http_requests_total counter\n");
+        for (&(ref route, ref method), metrics) in routes.iter() {
+            for (status, count) in &metrics.responses {
+                let _ = writeln!(
+                    text,
+                    "sentence_aligner_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                    method,
+                    route,
+                    status,
+                    count,
+                );
+            }
+        }
+
+        text.push_str("# HELP sentence_aligner_http_request_duration_seconds Time taken to handle the requests, by method and route.\n");
+        text.push_str("# TYPE sentence_aligner_http_request_duration_seconds histogram\n");
+        for (&(ref route, ref method), metrics) in routes.iter() {
+            for (bound, bucket) in BUCKETS.iter().zip(metrics.buckets.iter()) {
+                let _ = writeln!(
+                    text,
+                    "sentence_aligner_http_request_duration_seconds_bucket{{method=\"{}\",route=\"{}\",le=\"{}\"}} {}",
+                    method,
+                    route,
+                    bound,
+                    bucket,
+                );
+            }
+            let _ = writeln!(
+                text,
+                "sentence_aligner_http_request_duration_seconds_bucket{{method=\"{}\",route=\"{}\",le=\"+Inf\"}} {}",
+                method,
+                route,
+                metrics.count,
+            );
+            let _ = writeln!(
+                text,
+                "sentence_aligner_http_request_duration_seconds_sum{{method=\"{}\",route=\"{}\"}} {}",
+                method,
+                route,
+                metrics.seconds,
+            );
+            let _ = writeln!(
+                text,
+                "sentence_aligner_http_request_duration_seconds_count{{method=\"{}\",route=\"{}\"}} {}",
+                method,
+                route,
+                metrics.count,
+            );
+        }
+    }
+}
+
+/// Fairing recording the metrics of every request.
+pub struct RequestMetrics(pub Arc<Metrics>);
+
+impl Fairing for RequestMetrics {
+
+    fn info(&self) -> Info {
+        Info {
+            name: "Record the request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    fn on_request(&self, _: &mut Request, _: &Data) {
+        STARTED_AT.with(|started_at| started_at.set(Some(Instant::now())));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+
+        let started_at = match STARTED_AT.with(|started_at| started_at.take()) {
+            Some(started_at) => started_at,
+            None => return,
+        };
+
+        let elapsed = started_at.elapsed();
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+
+        // the paths of the routes rather than the requested ones,
+        // which would make a series per sentence
+        let route = match request.route() {
+            Some(route) => route.uri.path().to_string(),
+            None => "unmatched".to_string(),
+        };
+
+        self.0.record(request.method().as_str(), &route, response.status().code, seconds);
+    }
+}
+
+/// Tells that the process is up, whatever the state of the database.
+#[get("/health")]
+fn health<'r>() -> Response<'r> {
+
+    Response::build()
+        .header(ContentType::Plain)
+        .sized_body(Cursor::new("ok"))
+        .finalize()
+}
+
+/// Tells that the server can handle requests: a database connection
+/// can be had and the schema is up to date.
+#[get("/ready")]
+fn ready<'r>(storage: State<Storage>) -> Result<Response<'r>, Error> {
+
+    let store = storage.migration_store()?;
+
+    match migrations::check(&*store) {
+        Ok(()) => {
+            Ok(Response::build()
+                .header(ContentType::Plain)
+                .sized_body(Cursor::new("ready"))
+                .finalize())
+        },
+        Err(MigrationError::Storage(error)) => Err(error),
+        Err(error) => Err(Error::SchemaNotCurrent(error.to_string())),
+    }
+}
+
+/// Returns the metrics in the Prometheus text format. The ones read from
+/// the database are left out while it cannot be reached.
+#[get("/metrics")]
+fn metrics<'r>(
+    metrics: State<Arc<Metrics>>,
+    storage: State<Storage>,
+) -> Response<'r> {
+
+    let mut text = String::new();
+
+    metrics.write(&mut text);
+
+    if let Storage::Postgres(ref pool) = *storage {
+        let state = pool.state();
+        text.push_str("# HELP sentence_aligner_db_pool_connections Connections of the pool, in use or idle.\n");
+        text.push_str("# TYPE sentence_aligner_db_pool_connections gauge\n");
+        let _ = writeln!(text, "sentence_aligner_db_pool_connections {}", state.connections);
+        text.push_str("# HELP sentence_aligner_db_pool_idle_connections Idle connections of the pool.\n");
+        text.push_str("# TYPE sentence_aligner_db_pool_idle_connections gauge\n");
+        let _ = writeln!(text, "sentence_aligner_db_pool_idle_connections {}", state.idle_connections);
+    }
+
+    let languages = storage.repository().and_then(|repository| repository.get_languages());
+
+    text.push_str("# HELP sentence_aligner_database_up Whether the database could be queried.\n");
+    text.push_str("# TYPE sentence_aligner_database_up gauge\n");
+    let _ = writeln!(text, "sentence_aligner_database_up {}", if languages.is_ok() { 1 } else { 0 });
+
+    if let Ok(languages) = languages {
+        text.push_str("# HELP sentence_aligner_sentences Visible sentences, by language.\n");
+        text.push_str("# TYPE sentence_aligner_sentences gauge\n");
+        for language in languages {
+            let _ = writeln!(
+                text,
+                "sentence_aligner_sentences{{iso639_3=\"{}\"}} {}",
+                language.iso639_3,
+                language.sentences,
+            );
+        }
+    }
+
+    Response::build()
+        .header(ContentType::with_params("text", "plain", ("version", "0.0.4")))
+        .sized_body(Cursor::new(text))
+        .finalize()
+}
@@ -0,0 +1,129 @@
+//! Near-duplicate sentences, found by `sentence_aligner_core::near_duplicates`.
+//!
+//! The groups are recomputed by a background job every
+//! `near_duplicates.interval` seconds of the configuration
+//! (one hour by default, `0` disables it) or on demand,
+//! and stored by the repository.
+
+use rocket::{Response, State};
+use rocket::http::ContentType;
+
+use uuid::Uuid;
+use std::io::Cursor;
+use std::thread;
+use std::time::Duration;
+
+use auth::{Reader, Reviewer};
+use error::Error;
+use iso639::Registry;
+use languages;
+use repository::{Repository, RequestRepository, Storage};
+use models::{DuplicateGroup, RefreshReport};
+use sentence_aligner_core::near_duplicates;
+
+/// Starts the background job recomputing the near-duplicate groups.
+///
+/// Args:
+///
+/// `storage` - the storage of the sentences
+/// `interval` - the seconds between two runs, 0 to never run it
+pub fn spawn_job(storage: Storage, interval: u64) {
+
+    if interval == 0 {
+        return;
+    }
+
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_secs(interval));
+
+            // a failed run, e.g. the database being unreachable,
+            // is retried at the next interval
+            if let Ok(repository) = storage.repository() {
+                let _ = refresh(&*repository);
+            }
+        }
+    });
+}
+
+/// Recomputes all the near-duplicate groups, replacing the stored ones.
+///
+/// Args:
+///
+/// `repository` - the storage of the sentences
+///
+/// Returns:
+///
+/// the stored groups, each one a list of sentence uuids
+pub fn refresh(repository: &Repository) -> Result<Vec<Vec<Uuid>>, Error> {
+
+    let sentences = repository.get_all_sentences(false)?;
+
+    let groups = near_duplicates::group(sentences);
+
+    repository.replace_near_duplicates(&groups)?;
+
+    Ok(groups)
+}
+
+/// Recomputes the near-duplicate groups without waiting for the background job.
+#[post("/maintenance/near-duplicates")]
+fn refresh_near_duplicates<'r>(
+    _reviewer: Reviewer,
+    repository: RequestRepository,
+) -> Result<Response<'r>, Error> {
+
+    let groups = refresh(&*repository)?;
+
+    let report = RefreshReport {
+        groups: groups.len(),
+        sentences: groups.iter().map(|group| group.len()).sum(),
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(report).to_string()))
+        .finalize())
+}
+
+/// Lists the near-duplicate groups of a language, as found by the last refresh.
+/// The sentences deleted or merged since then are left out.
+#[get("/languages/<language_code>/near-duplicates")]
+fn get_near_duplicates_of_language<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    language_code: String,
+) -> Result<Response<'r>, Error> {
+
+    let iso639_3 = languages::iso639_3(&registry, language_code);
+
+    if repository.get_language(&iso639_3)?.is_none() {
+        return Err(Error::NotFound);
+    }
+
+    let rows = repository.get_near_duplicates(&iso639_3)?;
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+
+    for (group, sentence) in rows {
+
+        let is_new_group = groups.last().map_or(true, |last| last.group != group);
+        if is_new_group {
+            groups.push(DuplicateGroup {
+                group: group,
+                sentences: Vec::new(),
+            });
+        }
+        groups.last_mut().unwrap().sentences.push(sentence);
+    }
+
+    let groups: Vec<DuplicateGroup> = groups.into_iter()
+        .filter(|group| group.sentences.len() > 1)
+        .collect();
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(groups).to_string()))
+        .finalize())
+}
@@ -1,343 +1,530 @@
-use rocket::Response;
+use rocket::{Response, State};
 use rocket::http::Status;
 use rocket::http::ContentType;
 use rocket_contrib::UUID;
-use postgres::error::UNIQUE_VIOLATION;
-use xml::reader::EventReader;
-use xml::reader::XmlEvent::{Characters, Whitespace};
+use serde_json;
+use serde_json::{Map, Value};
 
 use uuid::Uuid;
 use std::io::Cursor;
 
-use db;
-use sentences::Sentence;
+use auth::{Contributor, Reader, Reviewer};
+use canonical::Canonicalizer;
+use error::Error;
+use etag;
+use etag::{IfMatch, IfNoneMatch};
+use iso639::Registry;
+use language_tag;
+use language_tag::LanguageTag;
+use models::{DeletionReport, Role, SentenceMergeReport, Snapshot};
+use repository::{
+    Modification,
+    RequestRepository,
+};
+use structure;
+use structure::StructureUpdate;
 
 #[get("/sentences/<sentence_uuid>")]
 fn get_sentence<'r>(
-    connection: db::DbConnection,
-    sentence_uuid: UUID
-) -> Response<'r> {
+    _reader: Reader,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+    if_none_match: IfNoneMatch,
+) -> Result<Response<'r>, Error> {
     // little trick needed as we can't directly
     // convert from url's string param to 'standard' uuid
     // we need to go through an intermediate rocket's UUID
     let real_uuid : Uuid = *sentence_uuid;
 
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                content,
-                language.iso639_3,
-                structure::text
-            FROM sentence
-            JOIN language ON (sentence.language_id = language.id)
-            WHERE sentence.id = $1
-        "#,
-        &[&real_uuid]
-    );
-
-    let rows = result.expect("problem while getting sentence");
-
-    if rows.is_empty() {
-        return  Response::build()
-            .status(Status::NotFound)
-            .finalize()
-        ;
-    }
-
-    let row = rows
-        .iter()
-        .next() // there's only 1 result
-        .expect("0 results, expected one...")
-    ;
-
-    let sentence = Sentence {
-        id: row.get(0),
-        text: row.get(1),
-        iso639_3: row.get(2),
-        structure: row.get(3),
+    let (sentence, version) = match repository.get_sentence(&real_uuid)? {
+        Some(sentence) => sentence,
+        None => return Err(Error::NotFound),
     };
 
+    if if_none_match.matches(version) {
+        return Ok(Response::build()
+            .status(Status::NotModified)
+            .raw_header(etag::HEADER, etag::format(version))
+            .finalize());
+    }
 
-    Response::build()
+    Ok(Response::build()
         .header(ContentType::JSON)
+        .raw_header(etag::HEADER, etag::format(version))
         .sized_body(Cursor::new(json!(sentence).to_string()))
-        .finalize()
+        .finalize())
+}
+
+/// Returns the version of a modified sentence, or the response
+/// telling which other sentence has the same content in the same language variant.
+///
+/// Args:
+///
+/// `modification` - what the modification has done
+pub fn modified_version<'r>(modification: Modification) -> Result<i32, Response<'r>> {
+    match modification {
+        Modification::Updated(version) | Modification::Unchanged(version) => Ok(version),
+        Modification::Conflict(sentence) => {
+            Err(Response::build()
+                .status(Status::Conflict)
+                .header(ContentType::JSON)
+                .sized_body(Cursor::new(json!(sentence).to_string()))
+                .finalize())
+        },
+    }
 }
 
 #[put("/sentences/<sentence_uuid>/text", format="text/plain", data="<text>")]
 fn edit_sentence_text<'r>(
-    connection: db::DbConnection,
+    contributor: Contributor,
+    repository: RequestRepository,
+    canonicalizer: State<Canonicalizer>,
     sentence_uuid: UUID,
     text: String,
-) -> Response<'r> {
+    if_match: IfMatch,
+) -> Result<Response<'r>, Error> {
 
     let real_uuid : Uuid = *sentence_uuid;
+    let author = contributor.0.author();
 
-    let result = connection.execute(
-        r#"
-            UPDATE sentence
-            SET content = $1
-            WHERE id = $2
-        "#,
-        &[
-            &text,
-            &real_uuid,
-        ],
-    );
-
-    let not_found = match result {
-        Ok(nbr_row_updated) => nbr_row_updated == 0,
-        Err(ref e) => {
-            if e.code() == Some(&UNIQUE_VIOLATION) {
-
-                let sentence = get_sentence_by_content(
-                    &connection,
-                    &text,
-                );
-
-                return Response::build()
-                    .status(Status::Conflict)
-                    .header(ContentType::JSON)
-                    .sized_body(Cursor::new(json!(sentence).to_string()))
-                    .finalize();
-            }
-            panic!(format!("{}", e));
+    let text = canonicalizer.text(&text);
+    let mut structure_status = "absent";
+
+    let modification = repository.modify_sentence(&real_uuid, &author, &mut |previous, version| {
+
+        if !if_match.matches(version) {
+            return Err(Error::PreconditionFailed);
         }
-    };
 
-    if not_found {
-        return  Response::build()
-            .status(Status::NotFound)
-            .finalize()
-        ;
-    }
+        /* the structure text must stay equal to the content,
+           so it is either updated along with it or removed */
+        let structure_update = StructureUpdate::from_text_edit(
+            previous.structure.as_ref().map(|structure| structure.as_str()),
+            &previous.text,
+            &text,
+        );
+        structure_status = structure_update.status();
+
+        let mut next = previous.clone();
+        next.text = text.clone();
+        next.structure = structure_update.structure().map(String::from);
+        Ok(next)
+    })?;
+
+    let version = match modified_version(modification) {
+        Ok(version) => version,
+        Err(conflict) => return Ok(conflict),
+    };
 
-    Response::build()
+    Ok(Response::build()
         .status(Status::NoContent)
-        .finalize()
+        .raw_header(structure::STATUS_HEADER, structure_status)
+        .raw_header(etag::HEADER, etag::format(version))
+        .finalize())
 }
 
 #[put("/sentences/<sentence_uuid>/structure", format="text/xml", data="<text>")]
 fn edit_sentence_structure<'r>(
-    connection: db::DbConnection,
+    contributor: Contributor,
+    repository: RequestRepository,
+    canonicalizer: State<Canonicalizer>,
     sentence_uuid: UUID,
     text: String,
-) -> Response<'r> {
+    if_match: IfMatch,
+) -> Result<Response<'r>, Error> {
 
     let real_uuid : Uuid = *sentence_uuid;
-
-    let result = connection.query(
-        r#"
-            SELECT content
-            FROM sentence
-            WHERE id = $1
-        "#,
-        &[&real_uuid],
-    );
-
-    let rows = match result {
-        Ok(rows) => rows,
-        Err(ref e) => {
-            panic!(format!("{}", e));
+    let author = contributor.0.author();
+
+    let text = match canonicalizer.structure(&text) {
+        Some(text) => text,
+        None => {
+            return Err(Error::InvalidInput(
+                "the structure cannot be canonicalised".to_string()
+            ));
         }
     };
 
-    if rows.len() != 1 {
-        return Response::build()
-            .status(Status::NotFound)
-            .finalize();
-    }
+    let modification = repository.modify_sentence(&real_uuid, &author, &mut |previous, version| {
 
-    let content: String = rows.get(0).get("content");
-    let parser = EventReader::from_str(&text);
-    let mut structure = String::new();
+        if !if_match.matches(version) {
+            return Err(Error::PreconditionFailed);
+        }
 
-    for word in parser {
-        match word {
-            Ok(Characters(value)) | Ok(Whitespace(value)) => {
-                structure += &value;
-            },
-            _ => {}
+        if previous.text != structure::extract_text(&text) {
+            return Err(Error::InvalidInput(
+                "the structure text does not match the sentence text".to_string()
+            ));
         }
-    }
 
-    if content != structure {
-        return Response::build()
-            .status(Status::BadRequest)
-            .finalize();
-    }
+        let mut next = previous.clone();
+        next.structure = Some(text.clone());
+        Ok(next)
+    })?;
 
-    /* we add ::TEXT::XML because Postgresql query parameters need explicit cast:
-       https://github.com/sfackler/rust-postgres/issues/309#issuecomment-351063887 */
-    let _ = connection.execute(
-        r#"
-            UPDATE sentence
-            SET structure = $1::TEXT::XML
-            WHERE id = $2
-        "#,
-        &[
-            &text,
-            &real_uuid,
-        ],
-    ).unwrap();
+    let version = match modified_version(modification) {
+        Ok(version) => version,
+        Err(conflict) => return Ok(conflict),
+    };
 
-    Response::build()
+    Ok(Response::build()
         .status(Status::NoContent)
-        .finalize()
+        .raw_header(etag::HEADER, etag::format(version))
+        .finalize())
 }
 
 #[put("/sentences/<sentence_uuid>/language", format="text/plain", data="<text>")]
 fn edit_sentence_language<'r>(
-    connection: db::DbConnection,
+    contributor: Contributor,
+    repository: RequestRepository,
+    registry: State<Registry>,
     sentence_uuid: UUID,
     text: String,
-) -> Response<'r> {
+    if_match: IfMatch,
+) -> Result<Response<'r>, Error> {
 
     let real_uuid : Uuid = *sentence_uuid;
+    let author = contributor.0.author();
+
+    /* unknown codes are kept as is, they are then not found in the language table;
+       the script and region of a BCP 47 tag replace the ones of the sentence */
+    let tag = LanguageTag::parse(&registry, &text).unwrap_or(LanguageTag {
+        iso639_3: text,
+        script: None,
+        region: None,
+    });
+
+    if repository.get_language(&tag.iso639_3)?.is_none() {
+        return Err(Error::NotFound);
+    }
 
-    let result = connection.execute(
-        r#"
-            UPDATE sentence
-            SET language_id = language.id
-            FROM language
-            WHERE language.iso639_3 = $1 AND sentence.id = $2
-        "#,
-        &[
-            &text,
-            &real_uuid,
-        ],
-    );
+    let modification = repository.modify_sentence(&real_uuid, &author, &mut |previous, version| {
 
-    let status = match result {
-        Ok(nbr_row_updated) if nbr_row_updated == 1 => {
-            Status::NoContent
-        },
-        Ok(_) => {
-            Status::NotFound
-        },
-        Err(ref e) => {
-            if e.code() == Some(&UNIQUE_VIOLATION) {
-
-                let sentence = get_sentence_by_sentence_content_and_language(
-                    &connection,
-                    &real_uuid,
-                    &text,
-                );
-
-                return Response::build()
-                    .status(Status::Conflict)
-                    .header(ContentType::JSON)
-                    .sized_body(Cursor::new(json!(sentence).to_string()))
-                    .finalize();
-            }
+        if !if_match.matches(version) {
+            return Err(Error::PreconditionFailed);
+        }
+
+        let mut next = previous.clone();
+        next.iso639_3 = Some(tag.iso639_3.clone());
+        next.script = tag.script.clone();
+        next.region = tag.region.clone();
+        Ok(next)
+    })?;
+
+    let version = match modified_version(modification) {
+        Ok(version) => version,
+        Err(conflict) => return Ok(conflict),
+    };
+
+    Ok(Response::build()
+        .status(Status::NoContent)
+        .raw_header(etag::HEADER, etag::format(version))
+        .finalize())
+}
+
+/// Fields of a sentence that can be changed by a merge patch.
+const PATCHABLE_FIELDS: [&str; 5] = ["text", "iso639_3", "structure", "script", "region"];
+
+/// Updates several fields of a sentence at once, the body being
+/// a JSON merge patch (RFC 7396) of the sentence: missing fields
+/// are left unchanged, `null` removes the structure, script or region.
+/// The result is validated as a whole, so a text and its structure
+/// can be changed together.
+#[patch("/sentences/<sentence_uuid>", format="application/merge-patch+json", data="<patch>")]
+fn patch_sentence<'r>(
+    contributor: Contributor,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    canonicalizer: State<Canonicalizer>,
+    sentence_uuid: UUID,
+    patch: String,
+    if_match: IfMatch,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+    let author = contributor.0.author();
+
+    let patch: Map<String, Value> = match serde_json::from_str(&patch) {
+        Ok(Value::Object(patch)) => patch,
+        _ => {
+            return Err(Error::InvalidInput(
+                "the body is not a JSON object".to_string()
+            ));
+        }
+    };
+
+    let mut structure_status = None;
 
-            panic!(format!("{}", e));
+    let modification = repository.modify_sentence(&real_uuid, &author, &mut |previous, version| {
+
+        if !if_match.matches(version) {
+            return Err(Error::PreconditionFailed);
+        }
+
+        match apply_patch(previous, &patch, &registry, &canonicalizer) {
+            Some((next, status)) => {
+                structure_status = status;
+                Ok(next)
+            },
+            None => {
+                Err(Error::InvalidInput(
+                    format!(
+                        "only {} can be patched, with string values fitting the text",
+                        PATCHABLE_FIELDS.join(", "),
+                    )
+                ))
+            }
         }
+    })?;
+
+    let (version, changed) = match modification {
+        Modification::Unchanged(version) => (version, false),
+        modification => match modified_version(modification) {
+            Ok(version) => (version, true),
+            Err(conflict) => return Ok(conflict),
+        },
     };
 
-    return Response::build()
-        .status(status)
-        .finalize()
+    let mut response = Response::build()
+        .status(Status::NoContent)
+        .raw_header(etag::HEADER, etag::format(version))
+        .finalize();
+
+    if let (true, Some(status)) = (changed, structure_status) {
+        response.set_raw_header(structure::STATUS_HEADER, status);
+    }
+
+    Ok(response)
 }
 
-/// Returns a sentence object by its content. The function panics if no sentence is found.
+/// Computes the state of a sentence once a merge patch is applied.
+///
+/// A new language given as a BCP 47 tag also sets the script and region,
+/// unless they are patched too. A new text without new structure
+/// reprojects or removes the current structure, as a text edit does.
 ///
 /// Args:
 ///
-/// `connection` - database connection handler
-/// `text` - the content of the sentence to find
+/// `previous` - the current state of the sentence
+/// `patch` - the merge patch
+/// `registry` - the ISO 639-3 registry
+/// `canonicalizer` - the canonicalisation of the contents
 ///
-/// Return:
+/// Returns:
 ///
-/// the sentence with the given content
-fn get_sentence_by_content(
-    connection: &db::DbConnection,
-    text: &str,
-) -> Sentence {
-
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                content,
-                language.iso639_3,
-                structure::text
-            FROM sentence
-            JOIN language ON (sentence.language_id = language.id)
-            WHERE sentence.content = $1
-        "#,
-        &[&text],
-    );
-
-    let rows = result.expect("problem while getting sentence");
-
-    let row = rows
-        .iter()
-        .next() // there's only 1 result
-        .expect("0 results, expected one...")
-    ;
-
-    Sentence {
-        id: row.get(0),
-        text: row.get(1),
-        iso639_3: row.get(2),
-        structure: row.get(3),
+/// the new state and the `X-Structure-Status` of an implicit structure update,
+/// None if the patch is invalid
+fn apply_patch(
+    previous: &Snapshot,
+    patch: &Map<String, Value>,
+    registry: &Registry,
+    canonicalizer: &Canonicalizer,
+) -> Option<(Snapshot, Option<&'static str>)> {
+
+    if patch.keys().any(|field| !PATCHABLE_FIELDS.contains(&field.as_str())) {
+        return None;
     }
+
+    let mut next = previous.clone();
+
+    match patched_field(patch, "text")? {
+        Some(Some(text)) => next.text = canonicalizer.text(&text),
+        Some(None) => return None,
+        None => {},
+    }
+
+    match patched_field(patch, "iso639_3")? {
+        Some(Some(code)) => {
+            let tag = LanguageTag::parse(registry, &code)?;
+            next.iso639_3 = Some(tag.iso639_3);
+            next.script = tag.script;
+            next.region = tag.region;
+        },
+        Some(None) => return None,
+        None => {},
+    }
+
+    match patched_field(patch, "script")? {
+        Some(Some(script)) => next.script = Some(language_tag::normalise_script(&script)?),
+        Some(None) => next.script = None,
+        None => {},
+    }
+
+    match patched_field(patch, "region")? {
+        Some(Some(region)) => next.region = Some(language_tag::normalise_region(&region)?),
+        Some(None) => next.region = None,
+        None => {},
+    }
+
+    let structure_status = match patched_field(patch, "structure")? {
+        Some(Some(structure)) => {
+            let structure = canonicalizer.structure(&structure)?;
+            if structure::extract_text(&structure) != next.text {
+                return None;
+            }
+            next.structure = Some(structure);
+            None
+        },
+        Some(None) => {
+            next.structure = None;
+            None
+        },
+        None if next.text != previous.text => {
+            let structure_update = StructureUpdate::from_text_edit(
+                previous.structure.as_ref().map(|structure| structure.as_str()),
+                &previous.text,
+                &next.text,
+            );
+            next.structure = structure_update.structure().map(String::from);
+            Some(structure_update.status())
+        },
+        None => None,
+    };
+
+    Some((next, structure_status))
 }
 
-/// Return a sentence by its language and its content (got from another sentence). Panics if the sentence is not found.
-///
-/// Args:
-///
-/// `connection` - database connection handler
-/// `sentence_uuid` - the sentence uuid from which to get the content
-/// `language` - the language of the sentence
+/// Reads a field of a merge patch.
 ///
 /// Returns:
 ///
-/// a sentence object
-fn get_sentence_by_sentence_content_and_language(
-    connection: &db::DbConnection,
-    sentence_uuid: &Uuid,
-    language: &str,
-) -> Sentence {
-
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                content,
-                language.iso639_3,
-                structure::text
-            FROM sentence
-            JOIN language ON (sentence.language_id = language.id)
-            WHERE
-            sentence.content = (
-                SELECT content
-                FROM sentence
-                WHERE id = $1
-            ) AND
-            language.iso639_3 = $2
-        "#,
-        &[
-            &sentence_uuid,
-            &language,
-        ],
-    );
-
-    let rows = result.expect("problem while getting sentence");
-
-    let row = rows
-        .iter()
-        .next() // there's only 1 result
-        .expect("0 results, expected one...")
-    ;
-
-    Sentence {
-        id: row.get(0),
-        text: row.get(1),
-        iso639_3: row.get(2),
-        structure: row.get(3),
+/// None if the field is not a string nor null, otherwise
+/// `Some(None)` if the field is missing and `Some(Some(None))` if it is null
+fn patched_field(patch: &Map<String, Value>, field: &str) -> Option<Option<Option<String>>> {
+    match patch.get(field) {
+        None => Some(None),
+        Some(&Value::Null) => Some(Some(None)),
+        Some(&Value::String(ref value)) => Some(Some(Some(value.clone()))),
+        Some(_) => None,
+    }
+}
+
+#[derive(FromForm)]
+struct DeleteOptions {
+    purge: bool,
+}
+
+#[delete("/sentences/<sentence_uuid>")]
+fn delete_sentence<'r>(
+    contributor: Contributor,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    delete_sentence_with_options(
+        contributor,
+        DeleteOptions { purge: false },
+        repository,
+        sentence_uuid,
+    )
+}
+
+/// Soft deletes a sentence: it is kept in database but hidden
+/// until it is restored or created again. With `purge=true` the
+/// sentence and its revisions are removed for good, this also
+/// works on an already soft deleted sentence, and is left to the admins.
+#[delete("/sentences/<sentence_uuid>?<options>")]
+fn delete_sentence_with_options<'r>(
+    contributor: Contributor,
+    options: DeleteOptions,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    if options.purge && (contributor.0).0.role < Role::Admin {
+        return Err(Error::Forbidden);
+    }
+
+    let real_uuid : Uuid = *sentence_uuid;
+
+    let revisions_removed = match repository.delete_sentence(&real_uuid, options.purge)? {
+        Some(revisions_removed) => revisions_removed,
+        None => return Err(Error::NotFound),
+    };
+
+    let report = DeletionReport {
+        id: real_uuid,
+        purged: options.purge,
+        revisions_removed: revisions_removed,
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(report).to_string()))
+        .finalize())
+}
+
+#[post("/sentences/<sentence_uuid>/restore")]
+fn restore_sentence<'r>(
+    _reviewer: Reviewer,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+
+    if !repository.restore_sentence(&real_uuid)? {
+        return Err(Error::NotFound);
+    }
+
+    Ok(Response::build()
+        .status(Status::NoContent)
+        .finalize())
+}
+
+/// Merges a near-duplicate sentence into another one of the same language
+/// variant: the target is kept and receives the structure of the merged
+/// sentence if it has none and the structure can be reprojected on its text,
+/// the merged sentence is then soft deleted.
+#[post("/sentences/<sentence_uuid>/merge-into/<target_uuid>")]
+fn merge_sentence<'r>(
+    reviewer: Reviewer,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+    target_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+    let real_target_uuid : Uuid = *target_uuid;
+    let author = reviewer.0.author();
+
+    if real_uuid == real_target_uuid {
+        return Err(Error::InvalidInput(
+            "a sentence cannot be merged into itself".to_string()
+        ));
     }
+
+    let structure_moved = repository.merge_sentence(
+        &real_uuid,
+        &real_target_uuid,
+        &author,
+        &mut |merged, target| {
+
+            if merged.iso639_3 != target.iso639_3 ||
+                merged.script != target.script ||
+                merged.region != target.region {
+                return Err(Error::InvalidInput(
+                    "both sentences must be of the same language variant".to_string()
+                ));
+            }
+
+            Ok(match (&merged.structure, &target.structure) {
+                (&Some(ref structure), &None) => {
+                    structure::reproject(structure, &merged.text, &target.text)
+                },
+                _ => None,
+            })
+        },
+    )?;
+
+    let report = SentenceMergeReport {
+        removed: real_uuid,
+        kept: real_target_uuid,
+        structure_moved: structure_moved,
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(report).to_string()))
+        .finalize())
 }
@@ -0,0 +1,1124 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
+
+use auth::Author;
+use error::Error;
+use migrations::{AppliedMigration, Migration, MigrationStore};
+use models::{
+    ApiKey,
+    ContributedEdit,
+    Contributions,
+    LanguageContributions,
+    NewReview,
+    Review,
+    Role,
+    Sentence,
+    Snapshot,
+    User,
+};
+use repository::{
+    ApiKeyRepository,
+    Creation,
+    LanguageDeletion,
+    LanguageMerge,
+    LanguageRepository,
+    LanguageUsage,
+    Modification,
+    ReviewRepository,
+    RevisionRecord,
+    SentenceQuery,
+    SentenceRepository,
+    Statuses,
+    UserRepository,
+};
+use structure;
+
+/// A stored sentence, `sentence.id` being always set.
+struct Row {
+    sentence: Sentence,
+    version: i32,
+    deleted: bool,
+}
+
+impl Row {
+
+    fn uuid(&self) -> Uuid {
+        self.sentence.id.expect("stored sentence without id")
+    }
+
+    /// Tells whether the sentence has the given content in the language variant of another one.
+    fn has_content(&self, content: &str, other: &Sentence) -> bool {
+        self.sentence.text == content &&
+            self.sentence.iso639_3 == other.iso639_3 &&
+            self.sentence.script == other.script &&
+            self.sentence.region == other.region
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.sentence.text.clone(),
+            iso639_3: Some(self.sentence.iso639_3.clone()),
+            structure: self.sentence.structure.clone(),
+            script: self.sentence.script.clone(),
+            region: self.sentence.region.clone(),
+        }
+    }
+
+    fn statuses(&self) -> Statuses {
+        Statuses {
+            sentence: self.sentence.review_status,
+            structure: self.sentence.structure_review_status,
+        }
+    }
+
+    fn set_statuses(&mut self, statuses: &Statuses) {
+        self.sentence.review_status = statuses.sentence;
+        self.sentence.structure_review_status = statuses.structure;
+    }
+
+    /// Gives the sentence a new state, and so a new version.
+    fn apply(&mut self, snapshot: &Snapshot) {
+        self.sentence.text = snapshot.text.clone();
+        self.sentence.iso639_3 = snapshot.iso639_3.clone().unwrap_or_default();
+        self.sentence.structure = snapshot.structure.clone();
+        self.sentence.script = snapshot.script.clone();
+        self.sentence.region = snapshot.region.clone();
+        self.version += 1;
+    }
+}
+
+/// Everything stored, the sentences being in the order they have been added.
+struct State {
+    languages: Vec<String>,
+    sentences: Vec<Row>,
+    revisions: Vec<(Uuid, RevisionRecord)>,
+    near_duplicates: Vec<(Uuid, i32)>,
+    /// the keys, with their hash
+    api_keys: Vec<(ApiKey, String)>,
+    users: Vec<User>,
+    /// the revisions made by users, as `(revision id, user id)`
+    revision_users: Vec<(i32, i32)>,
+    reviews: Vec<Review>,
+}
+
+impl State {
+
+    fn position(&self, sentence_uuid: &Uuid) -> Option<usize> {
+        self.sentences.iter().position(|row| row.sentence.id.as_ref() == Some(sentence_uuid))
+    }
+
+    fn visible_position(&self, sentence_uuid: &Uuid) -> Option<usize> {
+        match self.position(sentence_uuid) {
+            Some(index) if !self.sentences[index].deleted => Some(index),
+            _ => None,
+        }
+    }
+
+    fn check_language(&self, iso639_3: &str) -> Result<(), Error> {
+        if self.languages.iter().any(|language| language == iso639_3) {
+            Ok(())
+        } else {
+            Err(Error::InvalidInput(format!("unknown language {}", iso639_3)))
+        }
+    }
+
+    /// Records the revision of a sentence, from its previous state to its current one.
+    fn record(&mut self, index: usize, author: &Author, previous: Snapshot) {
+
+        let id = self.revisions.last().map_or(1, |&(_, ref revision)| revision.id + 1);
+        let sentence_uuid = self.sentences[index].uuid();
+        let revision = RevisionRecord {
+            id: id,
+            revised_at: now(),
+            author: author.name.clone(),
+            old: previous,
+            new: self.sentences[index].snapshot(),
+        };
+
+        self.revisions.push((sentence_uuid, revision));
+
+        if let Some(user_id) = author.user_id {
+            self.revision_users.push((id, user_id));
+            self.sentences[index].sentence.updated_by = Some(user_id);
+        }
+    }
+
+    /// Removes sentences for good, along with their revisions.
+    fn purge<F>(&mut self, predicate: F) -> usize
+        where F: Fn(&Row) -> bool
+    {
+        let purged: Vec<Uuid> = self.sentences.iter()
+            .filter(|row| predicate(row))
+            .map(|row| row.uuid())
+            .collect();
+
+        self.sentences.retain(|row| !purged.contains(&row.uuid()));
+        self.revisions.retain(|&(ref sentence_uuid, _)| !purged.contains(sentence_uuid));
+        self.near_duplicates.retain(|&(ref sentence_uuid, _)| !purged.contains(sentence_uuid));
+        self.reviews.retain(|review| !purged.contains(&review.sentence_id));
+
+        let revision_ids: Vec<i32> = self.revisions.iter().map(|&(_, ref revision)| revision.id).collect();
+        self.revision_users.retain(|&(revision_id, _)| revision_ids.contains(&revision_id));
+
+        purged.len()
+    }
+
+    /// Counts the visible sentences of a language.
+    fn usage(&self, iso639_3: &str) -> LanguageUsage {
+
+        let sentences = self.sentences.iter()
+            .filter(|row| !row.deleted && row.sentence.iso639_3 == iso639_3)
+            .count();
+
+        LanguageUsage {
+            iso639_3: iso639_3.to_string(),
+            sentences: sentences as i64,
+        }
+    }
+}
+
+/// Refuses a structure which is not well-formed XML, as the database does.
+fn check_structure(structure: &Option<String>) -> Result<(), Error> {
+    match *structure {
+        Some(ref structure) if !structure::is_well_formed(structure) => Err(Error::InvalidXml),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the current UTC date, e.g. `2018-04-01T10:00:00.000000Z`.
+fn now() -> String {
+
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = elapsed.as_secs();
+    let days = (seconds / 86400) as i64;
+
+    // civil date from the number of days since 1970-01-01
+    let shifted = days + 719468;
+    let era = shifted / 146097;
+    let day_of_era = shifted - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+        year,
+        month,
+        day,
+        seconds % 86400 / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        elapsed.subsec_nanos() / 1000,
+    )
+}
+
+/// Repository keeping the languages and sentences in memory,
+/// with the same rules as the databases. Its clones share the same state.
+#[derive(Clone)]
+pub struct MemoryRepository {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryRepository {
+
+    /// Creates an empty repository.
+    pub fn new() -> MemoryRepository {
+        MemoryRepository {
+            state: Arc::new(Mutex::new(State {
+                languages: Vec::new(),
+                sentences: Vec::new(),
+                revisions: Vec::new(),
+                near_duplicates: Vec::new(),
+                api_keys: Vec::new(),
+                users: Vec::new(),
+                revision_users: Vec::new(),
+                reviews: Vec::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<State> {
+        // a request panicking does not make the others fail in turn
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl SentenceRepository for MemoryRepository {
+
+    fn get_sentence(&self, sentence_uuid: &Uuid) -> Result<Option<(Sentence, i32)>, Error> {
+
+        let state = self.state();
+
+        Ok(state.visible_position(sentence_uuid)
+            .map(|index| (state.sentences[index].sentence.clone(), state.sentences[index].version)))
+    }
+
+    fn get_sentences(&self, query: &SentenceQuery) -> Result<Vec<Sentence>, Error> {
+
+        Ok(self.state().sentences
+            .iter()
+            .filter(|row| !row.deleted)
+            .map(|row| &row.sentence)
+            .filter(|sentence| match query.starting_after_id {
+                Some(ref uuid) => sentence.id.as_ref() >= Some(uuid),
+                None => true,
+            })
+            .filter(|sentence| match query.iso639_3 {
+                Some(ref iso639_3) => &sentence.iso639_3 == iso639_3,
+                None => true,
+            })
+            .filter(|sentence| query.script.is_none() || sentence.script == query.script)
+            .filter(|sentence| query.region.is_none() || sentence.region == query.region)
+            .take(query.limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn get_all_sentences(&self, include_deleted: bool) -> Result<Vec<Sentence>, Error> {
+
+        Ok(self.state().sentences
+            .iter()
+            .filter(|row| include_deleted || !row.deleted)
+            .map(|row| row.sentence.clone())
+            .collect())
+    }
+
+    fn create_sentence(&self, sentence: &Sentence, author: &Author) -> Result<Creation, Error> {
+
+        let mut state = self.state();
+
+        state.check_language(&sentence.iso639_3)?;
+        check_structure(&sentence.structure)?;
+
+        let uuid = sentence.id.unwrap_or_else(Uuid::new_v4);
+
+        let visible = state.sentences.iter()
+            .position(|row| !row.deleted && row.has_content(&sentence.text, sentence));
+
+        /* a deleted sentence with the same content is brought back
+           instead of being created again, if the given id is its own
+           and no visible sentence has taken its content since */
+        let deleted = state.sentences.iter().rposition(|row| {
+            row.deleted &&
+                row.has_content(&sentence.text, sentence) &&
+                sentence.id.map_or(true, |id| id == row.uuid())
+        });
+
+        if let (None, Some(index)) = (visible, deleted) {
+            let row = &mut state.sentences[index];
+            row.deleted = false;
+            row.sentence.structure = sentence.structure.clone();
+            if author.user_id.is_some() {
+                row.sentence.updated_by = author.user_id;
+            }
+            row.set_statuses(&Statuses::of_new(sentence));
+            row.version += 1;
+            return Ok(Creation::Restored(row.uuid()));
+        }
+
+        let conflicting = state.sentences.iter()
+            .position(|row| !row.deleted && row.uuid() == uuid)
+            .or(visible);
+
+        if let Some(index) = conflicting {
+            return Ok(Creation::Conflict(state.sentences[index].sentence.clone()));
+        }
+
+        // the uuid of a deleted sentence
+        if state.position(&uuid).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let mut created = sentence.clone();
+        created.id = Some(uuid);
+        created.created_by = author.user_id;
+        created.updated_by = author.user_id;
+        created.structure_review_status = Statuses::of_new(sentence).structure;
+        state.sentences.push(Row {
+            sentence: created,
+            version: 1,
+            deleted: false,
+        });
+
+        Ok(Creation::Created(uuid))
+    }
+
+    fn modify_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        author: &Author,
+        change: &mut FnMut(&Snapshot, i32) -> Result<Snapshot, Error>,
+    ) -> Result<Modification, Error> {
+
+        let mut state = self.state();
+
+        let index = state.visible_position(sentence_uuid).ok_or(Error::NotFound)?;
+        let previous = state.sentences[index].snapshot();
+        let version = state.sentences[index].version;
+
+        let next = change(&previous, version)?;
+
+        if next == previous {
+            return Ok(Modification::Unchanged(version));
+        }
+
+        state.check_language(&next.iso639_3.clone().unwrap_or_default())?;
+        check_structure(&next.structure)?;
+
+        let mut changed = state.sentences[index].sentence.clone();
+        changed.iso639_3 = next.iso639_3.clone().unwrap_or_default();
+        changed.script = next.script.clone();
+        changed.region = next.region.clone();
+
+        let conflicting = state.sentences.iter()
+            .find(|row| {
+                !row.deleted &&
+                    row.uuid() != *sentence_uuid &&
+                    row.has_content(&next.text, &changed)
+            })
+            .map(|row| row.sentence.clone());
+
+        if let Some(sentence) = conflicting {
+            return Ok(Modification::Conflict(sentence));
+        }
+
+        let statuses = state.sentences[index].statuses().changed(&previous, &next);
+
+        state.sentences[index].apply(&next);
+        state.sentences[index].set_statuses(&statuses);
+        state.record(index, author, previous);
+
+        Ok(Modification::Updated(state.sentences[index].version))
+    }
+
+    fn delete_sentence(&self, sentence_uuid: &Uuid, purge: bool) -> Result<Option<i64>, Error> {
+
+        let mut state = self.state();
+
+        if purge {
+            let revisions = state.revisions.iter()
+                .filter(|&&(ref revised, _)| revised == sentence_uuid)
+                .count();
+            let purged = state.purge(|row| row.uuid() == *sentence_uuid);
+            return Ok(if purged > 0 { Some(revisions as i64) } else { None });
+        }
+
+        match state.visible_position(sentence_uuid) {
+            Some(index) => {
+                state.sentences[index].deleted = true;
+                state.sentences[index].version += 1;
+                Ok(Some(0))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn restore_sentence(&self, sentence_uuid: &Uuid) -> Result<bool, Error> {
+
+        let mut state = self.state();
+
+        let index = match state.position(sentence_uuid) {
+            Some(index) if state.sentences[index].deleted => index,
+            _ => return Ok(false),
+        };
+
+        let taken = {
+            let restored = &state.sentences[index].sentence;
+            state.sentences.iter()
+                .any(|row| !row.deleted && row.has_content(&restored.text, restored))
+        };
+
+        if taken {
+            return Err(Error::AlreadyExists);
+        }
+
+        state.sentences[index].deleted = false;
+        state.sentences[index].version += 1;
+
+        Ok(true)
+    }
+
+    fn merge_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        target_uuid: &Uuid,
+        author: &Author,
+        merge: &mut FnMut(&Snapshot, &Snapshot) -> Result<Option<String>, Error>,
+    ) -> Result<bool, Error> {
+
+        let mut state = self.state();
+
+        let merged = state.visible_position(sentence_uuid);
+        let target = state.visible_position(target_uuid);
+
+        let (merged, target) = match (merged, target) {
+            (Some(merged), Some(target)) => (merged, target),
+            _ => return Err(Error::NotFound),
+        };
+
+        let previous = state.sentences[target].snapshot();
+        let moved_structure = merge(&state.sentences[merged].snapshot(), &previous)?;
+
+        if moved_structure.is_some() {
+            check_structure(&moved_structure)?;
+
+            let mut next = previous.clone();
+            next.structure = moved_structure.clone();
+            let statuses = state.sentences[target].statuses().changed(&previous, &next);
+
+            state.sentences[target].apply(&next);
+            state.sentences[target].set_statuses(&statuses);
+            state.record(target, author, previous);
+        }
+
+        state.sentences[merged].deleted = true;
+        state.sentences[merged].version += 1;
+
+        Ok(moved_structure.is_some())
+    }
+
+    fn get_revisions(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<RevisionRecord>>, Error> {
+
+        let state = self.state();
+
+        if state.position(sentence_uuid).is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(state.revisions.iter()
+            .filter(|&&(ref revised, _)| revised == sentence_uuid)
+            .map(|&(_, ref revision)| revision.clone())
+            .collect()))
+    }
+
+    fn get_revision(&self, sentence_uuid: &Uuid, revision_id: i32) -> Result<Option<Snapshot>, Error> {
+
+        Ok(self.state().revisions.iter()
+            .find(|&&(ref revised, ref revision)| revised == sentence_uuid && revision.id == revision_id)
+            .map(|&(_, ref revision)| revision.old.clone()))
+    }
+
+    fn get_training_corpus(
+        &self,
+        max_sentences: i64,
+        min_sentences: i64,
+    ) -> Result<Vec<(String, String)>, Error> {
+
+        let state = self.state();
+
+        let mut languages: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for row in state.sentences.iter().rev().filter(|row| !row.deleted) {
+            languages.entry(&row.sentence.iso639_3)
+                .or_insert_with(Vec::new)
+                .push(&row.sentence.text);
+        }
+
+        Ok(languages.into_iter()
+            .filter(|&(_, ref contents)| contents.len() as i64 >= min_sentences)
+            .flat_map(|(iso639_3, contents)| {
+                contents.into_iter()
+                    .take(max_sentences as usize)
+                    .map(move |content| (iso639_3.to_string(), content.to_string()))
+            })
+            .collect())
+    }
+
+    fn replace_near_duplicates(&self, groups: &[Vec<Uuid>]) -> Result<(), Error> {
+
+        let mut state = self.state();
+
+        state.near_duplicates = groups.iter()
+            .enumerate()
+            .flat_map(|(group_id, group)| {
+                group.iter().map(move |sentence_uuid| (*sentence_uuid, group_id as i32 + 1))
+            })
+            .collect();
+
+        Ok(())
+    }
+
+    fn get_near_duplicates(&self, iso639_3: &str) -> Result<Vec<(i32, Sentence)>, Error> {
+
+        let state = self.state();
+
+        let mut near_duplicates: Vec<(i32, usize)> = state.near_duplicates.iter()
+            .filter_map(|&(ref sentence_uuid, group_id)| {
+                state.visible_position(sentence_uuid).map(|index| (group_id, index))
+            })
+            .filter(|&(_, index)| state.sentences[index].sentence.iso639_3 == iso639_3)
+            .collect();
+
+        near_duplicates.sort();
+
+        Ok(near_duplicates.into_iter()
+            .map(|(group_id, index)| (group_id, state.sentences[index].sentence.clone()))
+            .collect())
+    }
+}
+
+impl LanguageRepository for MemoryRepository {
+
+    fn create_language(&self, iso639_3: &str) -> Result<(), Error> {
+
+        let mut state = self.state();
+
+        if state.languages.iter().any(|language| language == iso639_3) {
+            return Err(Error::AlreadyExists);
+        }
+
+        state.languages.push(iso639_3.to_string());
+        state.languages.sort();
+
+        Ok(())
+    }
+
+    fn get_languages(&self) -> Result<Vec<LanguageUsage>, Error> {
+
+        let state = self.state();
+
+        Ok(state.languages.iter()
+            .map(|language| state.usage(language))
+            .collect())
+    }
+
+    fn get_language(&self, iso639_3: &str) -> Result<Option<LanguageUsage>, Error> {
+
+        let state = self.state();
+
+        Ok(state.languages.iter()
+            .find(|language| *language == iso639_3)
+            .map(|language| state.usage(language)))
+    }
+
+    fn delete_language(&self, iso639_3: &str, cascade: bool) -> Result<LanguageDeletion, Error> {
+
+        let mut state = self.state();
+
+        state.check_language(iso639_3).map_err(|_| Error::NotFound)?;
+
+        let in_use = state.sentences.iter().any(|row| row.sentence.iso639_3 == iso639_3);
+        if in_use && !cascade {
+            return Ok(LanguageDeletion::InUse(state.usage(iso639_3)));
+        }
+
+        let removed = state.purge(|row| row.sentence.iso639_3 == iso639_3);
+        state.languages.retain(|language| language != iso639_3);
+
+        Ok(LanguageDeletion::Deleted(removed as u64))
+    }
+
+    fn merge_language(
+        &self,
+        iso639_3: &str,
+        target_iso639_3: &str,
+        author: &Author,
+    ) -> Result<LanguageMerge, Error> {
+
+        let mut state = self.state();
+
+        state.check_language(iso639_3).map_err(|_| Error::NotFound)?;
+        state.check_language(target_iso639_3).map_err(|_| Error::NotFound)?;
+
+        // the deleted sentences hold no content, they are moved as they are
+        let mut duplicates = Vec::new();
+        for source in state.sentences.iter().filter(|row| row.sentence.iso639_3 == iso639_3 && !row.deleted) {
+            let mut moved = source.sentence.clone();
+            moved.iso639_3 = target_iso639_3.to_string();
+            let target = state.sentences.iter()
+                .find(|row| !row.deleted && row.has_content(&source.sentence.text, &moved));
+            if let Some(target) = target {
+                duplicates.push((source.uuid(), target.uuid()));
+            }
+        }
+
+        let removed: Vec<Uuid> = duplicates.iter().map(|&(removed, _)| removed).collect();
+        state.purge(|row| removed.contains(&row.uuid()));
+
+        let moved: Vec<usize> = (0..state.sentences.len())
+            .filter(|&index| state.sentences[index].sentence.iso639_3 == iso639_3)
+            .collect();
+
+        // the sentences are to review again in their new language, drafts aside
+        for &index in moved.iter() {
+            let previous = state.sentences[index].snapshot();
+            let mut next = previous.clone();
+            next.iso639_3 = Some(target_iso639_3.to_string());
+            let statuses = state.sentences[index].statuses().changed(&previous, &next);
+            state.sentences[index].apply(&next);
+            state.sentences[index].set_statuses(&statuses);
+            state.record(index, author, previous);
+        }
+
+        state.languages.retain(|language| language != iso639_3);
+
+        Ok(LanguageMerge {
+            moved: moved.len() as u64,
+            duplicates: duplicates,
+        })
+    }
+    fn rename_language(&self, iso639_3: &str, new_iso639_3: &str, author: &Author) -> Result<u64, Error> {
+
+        let mut state = self.state();
+
+        state.check_language(iso639_3).map_err(|_| Error::NotFound)?;
+
+        if state.languages.iter().any(|language| language == new_iso639_3) {
+            return Err(Error::AlreadyExists);
+        }
+
+        let moved: Vec<usize> = (0..state.sentences.len())
+            .filter(|&index| state.sentences[index].sentence.iso639_3 == iso639_3)
+            .collect();
+
+        for &index in moved.iter() {
+            let previous = state.sentences[index].snapshot();
+            let mut next = previous.clone();
+            next.iso639_3 = Some(new_iso639_3.to_string());
+            state.sentences[index].apply(&next);
+            state.record(index, author, previous);
+        }
+
+        state.languages.retain(|language| language != iso639_3);
+        state.languages.push(new_iso639_3.to_string());
+        state.languages.sort();
+
+        Ok(moved.len() as u64)
+    }
+}
+
+impl ApiKeyRepository for MemoryRepository {
+
+    fn create_api_key(&self, name: &str, role: Role, key_hash: &str) -> Result<ApiKey, Error> {
+
+        let mut state = self.state();
+
+        if state.api_keys.iter().any(|&(_, ref hash)| hash == key_hash) {
+            return Err(Error::AlreadyExists);
+        }
+
+        // the key is given to the user of this name, created if needed
+        let existing = state.users.iter().find(|user| user.name == name).map(|user| user.id);
+        let user_id = match existing {
+            Some(user_id) => user_id,
+            None => {
+                let user = User {
+                    id: state.users.last().map_or(1, |user| user.id + 1),
+                    name: name.to_string(),
+                    created_at: now(),
+                };
+                let user_id = user.id;
+                state.users.push(user);
+                user_id
+            },
+        };
+
+        let api_key = ApiKey {
+            id: state.api_keys.last().map_or(1, |&(ref api_key, _)| api_key.id + 1),
+            user_id: user_id,
+            name: name.to_string(),
+            role: role,
+            created_at: now(),
+            key: None,
+        };
+
+        state.api_keys.push((api_key.clone(), key_hash.to_string()));
+
+        Ok(api_key)
+    }
+
+    fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+        Ok(self.state().api_keys.iter().map(|&(ref api_key, _)| api_key.clone()).collect())
+    }
+
+    fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, Error> {
+        Ok(self.state().api_keys
+            .iter()
+            .find(|&&(_, ref hash)| hash == key_hash)
+            .map(|&(ref api_key, _)| api_key.clone()))
+    }
+
+    fn revoke_api_key(&self, id: i32) -> Result<bool, Error> {
+
+        let mut state = self.state();
+
+        let count = state.api_keys.len();
+        state.api_keys.retain(|&(ref api_key, _)| api_key.id != id);
+
+        Ok(state.api_keys.len() < count)
+    }
+}
+
+impl UserRepository for MemoryRepository {
+
+    fn get_users(&self) -> Result<Vec<User>, Error> {
+        Ok(self.state().users.clone())
+    }
+
+    fn get_user(&self, user_id: i32) -> Result<Option<User>, Error> {
+        Ok(self.state().users.iter().find(|user| user.id == user_id).cloned())
+    }
+
+    fn get_contributions(&self, user_id: i32, limit: i64) -> Result<Option<Contributions>, Error> {
+
+        let state = self.state();
+
+        let user = match state.users.iter().find(|user| user.id == user_id) {
+            Some(user) => user.clone(),
+            None => return Ok(None),
+        };
+
+        let created: Vec<&Sentence> = state.sentences.iter()
+            .filter(|row| !row.deleted && row.sentence.created_by == Some(user_id))
+            .map(|row| &row.sentence)
+            .collect();
+
+        let revisions: Vec<(&Uuid, &RevisionRecord)> = state.revisions.iter()
+            .filter(|&&(_, ref revision)| {
+                state.revision_users.contains(&(revision.id, user_id))
+            })
+            .map(|&(ref sentence_uuid, ref revision)| (sentence_uuid, revision))
+            .collect();
+
+        let mut counts: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for sentence in created.iter() {
+            counts.entry(sentence.iso639_3.clone()).or_insert((0, 0)).0 += 1;
+        }
+        for &(_, revision) in revisions.iter() {
+            if let Some(ref iso639_3) = revision.new.iso639_3 {
+                counts.entry(iso639_3.clone()).or_insert((0, 0)).1 += 1;
+            }
+        }
+
+        Ok(Some(Contributions {
+            user: user,
+            languages: counts.into_iter()
+                .map(|(iso639_3, (sentences_created, edits))| {
+                    LanguageContributions {
+                        iso639_3: iso639_3,
+                        sentences_created: sentences_created,
+                        edits: edits,
+                    }
+                })
+                .collect(),
+            sentences: created.iter()
+                .rev()
+                .take(limit as usize)
+                .map(|&sentence| sentence.clone())
+                .collect(),
+            edits: revisions.iter()
+                .rev()
+                .take(limit as usize)
+                .map(|&(sentence_uuid, revision)| {
+                    ContributedEdit {
+                        revision_id: revision.id,
+                        sentence_id: *sentence_uuid,
+                        revised_at: revision.revised_at.clone(),
+                        iso639_3: revision.new.iso639_3.clone(),
+                    }
+                })
+                .collect(),
+        }))
+    }
+}
+
+impl ReviewRepository for MemoryRepository {
+
+    fn review_sentence(&self, sentence_uuid: &Uuid, review: &NewReview, author: &Author) -> Result<Review, Error> {
+
+        let mut state = self.state();
+
+        let index = state.visible_position(sentence_uuid).ok_or(Error::NotFound)?;
+        let statuses = state.sentences[index].statuses();
+        let reviewed = statuses.reviewed(review)?;
+
+        if reviewed != statuses {
+            state.sentences[index].set_statuses(&reviewed);
+            state.sentences[index].version += 1;
+        }
+
+        let recorded = Review {
+            id: state.reviews.last().map_or(1, |last| last.id + 1),
+            sentence_id: *sentence_uuid,
+            subject: review.subject,
+            status: review.status,
+            comment: review.comment.clone(),
+            reviewer_id: author.user_id,
+            reviewer: author.name.clone(),
+            reviewed_at: now(),
+        };
+
+        state.reviews.push(recorded.clone());
+
+        Ok(recorded)
+    }
+
+    fn get_reviews(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<Review>>, Error> {
+
+        let state = self.state();
+
+        if state.position(sentence_uuid).is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(state.reviews
+            .iter()
+            .filter(|review| review.sentence_id == *sentence_uuid)
+            .cloned()
+            .collect()))
+    }
+
+    fn submit_sentence(&self, sentence_uuid: &Uuid) -> Result<(), Error> {
+
+        let mut state = self.state();
+
+        let index = state.visible_position(sentence_uuid).ok_or(Error::NotFound)?;
+        let statuses = state.sentences[index].statuses();
+        let submitted = statuses.submitted();
+
+        if submitted != statuses {
+            state.sentences[index].set_statuses(&submitted);
+            state.sentences[index].version += 1;
+        }
+
+        Ok(())
+    }
+
+    fn get_review_queue(&self, iso639_3: Option<&str>, limit: i64) -> Result<Vec<Sentence>, Error> {
+
+        Ok(self.state().sentences
+            .iter()
+            .filter(|row| !row.deleted && row.statuses().is_pending())
+            .filter(|row| iso639_3.map_or(true, |iso639_3| row.sentence.iso639_3 == iso639_3))
+            .take(limit as usize)
+            .map(|row| row.sentence.clone())
+            .collect())
+    }
+}
+
+/// Nothing to migrate: the state is created with the current schema.
+impl MigrationStore for MemoryRepository {
+
+    fn migrations(&self) -> &'static [Migration] {
+        &[]
+    }
+
+    fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+        Ok(Vec::new())
+    }
+
+    fn apply(&self, _migration: &Migration) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn revert(&self, _migration: &Migration) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use uuid::Uuid;
+
+    use auth::Author;
+    use config::StorageConfig;
+    use error::Error;
+    use migrations;
+    use models::{NewReview, ReviewStatus, ReviewSubject, Sentence};
+    use repository::{Creation, Modification, Repository, Storage};
+
+    fn author() -> Author {
+        Author {
+            user_id: None,
+            name: None,
+        }
+    }
+
+    fn sentence(text: &str, iso639_3: &str) -> Sentence {
+        Sentence {
+            id: None,
+            text: text.to_string(),
+            iso639_3: iso639_3.to_string(),
+            structure: None,
+            script: None,
+            region: None,
+            created_by: None,
+            updated_by: None,
+            review_status: ReviewStatus::PendingReview,
+            structure_review_status: None,
+        }
+    }
+
+    /// Creates a sentence, returning its uuid.
+    fn create(repository: &Repository, text: &str, iso639_3: &str) -> Uuid {
+        match repository.create_sentence(&sentence(text, iso639_3), &author()).unwrap() {
+            Creation::Created(uuid) => uuid,
+            _ => panic!("the sentence {:?} has not been created", text),
+        }
+    }
+
+    fn storage() -> Storage {
+        Storage::open(&StorageConfig::Memory).unwrap()
+    }
+
+    #[test]
+    fn test_memory_storage_is_current_and_shared() {
+
+        let storage = storage();
+        migrations::check(&*storage.migration_store().unwrap()).unwrap();
+
+        storage.repository().unwrap().create_language("eng").unwrap();
+        let uuid = create(&*storage.repository().unwrap(), "Hello.", "eng");
+
+        let (found, version) = storage.repository().unwrap().get_sentence(&uuid).unwrap().unwrap();
+        assert_eq!(found.text, "Hello.");
+        assert_eq!(version, 1);
+    }
+
+    #[test]
+    fn test_create_sentence_with_taken_content_is_a_conflict() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        let uuid = create(&*repository, "Hello.", "eng");
+
+        match repository.create_sentence(&sentence("Hello.", "eng"), &author()).unwrap() {
+            Creation::Conflict(existing) => assert_eq!(existing.id, Some(uuid)),
+            _ => panic!("the content is taken"),
+        }
+    }
+
+    #[test]
+    fn test_create_sentence_in_unknown_language_fails() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+
+        assert!(repository.create_sentence(&sentence("Hello.", "eng"), &author()).is_err());
+    }
+
+    #[test]
+    fn test_create_existing_language_is_a_conflict() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+
+        match repository.create_language("eng") {
+            Err(Error::AlreadyExists) => {},
+            _ => panic!("the language exists"),
+        }
+    }
+
+    #[test]
+    fn test_modify_sentence_records_revision() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        let uuid = create(&*repository, "Hello.", "eng");
+
+        let modification = repository.modify_sentence(&uuid, &author(), &mut |current, _| {
+            let mut next = current.clone();
+            next.text = "Hello!".to_string();
+            Ok(next)
+        }).unwrap();
+
+        match modification {
+            Modification::Updated(version) => assert_eq!(version, 2),
+            _ => panic!("the sentence has changed"),
+        }
+        assert_eq!(repository.get_revisions(&uuid).unwrap().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_deleted_sentence_releases_its_content() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        let deleted = create(&*repository, "Hello.", "eng");
+        let kept = create(&*repository, "Hi.", "eng");
+
+        // a visible sentence cannot be restored
+        assert!(!repository.restore_sentence(&kept).unwrap());
+
+        repository.delete_sentence(&deleted, false).unwrap();
+        assert!(repository.get_sentence(&deleted).unwrap().is_none());
+
+        repository.modify_sentence(&kept, &author(), &mut |current, _| {
+            let mut next = current.clone();
+            next.text = "Hello.".to_string();
+            Ok(next)
+        }).unwrap();
+
+        match repository.restore_sentence(&deleted) {
+            Err(Error::AlreadyExists) => {},
+            _ => panic!("the content of the deleted sentence is taken"),
+        }
+    }
+
+    #[test]
+    fn test_create_sentence_restores_deleted_one() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        let uuid = create(&*repository, "Hello.", "eng");
+        repository.delete_sentence(&uuid, false).unwrap();
+        assert!(repository.get_sentence(&uuid).unwrap().is_none());
+
+        match repository.create_sentence(&sentence("Hello.", "eng"), &author()).unwrap() {
+            Creation::Restored(restored) => assert_eq!(restored, uuid),
+            _ => panic!("the deleted sentence has not been restored"),
+        }
+    }
+
+    #[test]
+    fn test_merge_language_moves_sentences_and_removes_duplicates() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        repository.create_language("enx").unwrap();
+        let kept = create(&*repository, "Hello.", "eng");
+        let duplicate = create(&*repository, "Hello.", "enx");
+        let moved = create(&*repository, "Hi.", "enx");
+
+        let merge = repository.merge_language("enx", "eng", &author()).unwrap();
+
+        assert_eq!(merge.moved, 1);
+        assert_eq!(merge.duplicates, vec![(duplicate, kept)]);
+        assert_eq!(repository.get_sentence(&moved).unwrap().unwrap().0.iso639_3, "eng");
+        assert!(repository.get_language("enx").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_merge_language_reviews_moved_sentences_again() {
+
+        let storage = storage();
+        let repository = storage.repository().unwrap();
+        repository.create_language("eng").unwrap();
+        repository.create_language("enx").unwrap();
+        let uuid = create(&*repository, "Hello.", "enx");
+
+        let review = NewReview {
+            subject: ReviewSubject::Sentence,
+            status: ReviewStatus::Approved,
+            comment: None,
+        };
+        repository.review_sentence(&uuid, &review, &author()).unwrap();
+
+        repository.merge_language("enx", "eng", &author()).unwrap();
+
+        let (sentence, _) = repository.get_sentence(&uuid).unwrap().unwrap();
+        assert_eq!(sentence.review_status, ReviewStatus::PendingReview);
+    }
+}
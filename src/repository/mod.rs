@@ -0,0 +1,646 @@
+//! Storage of the sentences, languages and users, kept apart from the handlers.
+//!
+//! The handlers go through the `Repository` trait, given to them by the
+//! `RequestRepository` guard from the configured `Storage`: PostgreSQL by
+//! default, a single SQLite file with `storage = "sqlite"`, or nothing but
+//! the memory of the process with `storage = "memory"`, lost when it stops.
+//!
+//! Every operation is atomic: the ones reading a sentence then changing it
+//! run in one transaction, calling back the handler to compute the change.
+
+mod memory;
+mod postgresql;
+mod sqlite;
+
+pub use self::memory::MemoryRepository;
+pub use self::postgresql::PostgresRepository;
+pub use self::sqlite::SqliteRepository;
+
+use rocket::http::Status;
+use rocket::request::{self, FromRequest};
+use rocket::{Request, State, Outcome};
+use rusqlite;
+
+use uuid::Uuid;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use auth::Author;
+use config::StorageConfig;
+use db;
+use error::Error;
+use migrations::MigrationStore;
+use models::{
+    ApiKey,
+    Contributions,
+    NewReview,
+    Review,
+    ReviewStatus,
+    ReviewSubject,
+    Role,
+    Sentence,
+    Snapshot,
+    User,
+};
+
+/// Which visible sentences to list, in the order they have been added.
+/// Every criterion is optional.
+pub struct SentenceQuery {
+    /// only the sentences with an uuid greater than or equal to this one
+    pub starting_after_id: Option<Uuid>,
+    pub iso639_3: Option<String>,
+    pub script: Option<String>,
+    pub region: Option<String>,
+    /// the greatest number of sentences to return
+    pub limit: i64,
+}
+
+/// A language and its number of visible sentences.
+pub struct LanguageUsage {
+    pub iso639_3: String,
+    pub sentences: i64,
+}
+
+/// Outcome of a sentence creation.
+pub enum Creation {
+    Created(Uuid),
+    /// a deleted sentence with the same content and language variant has been restored
+    Restored(Uuid),
+    /// the uuid, or the content in this language variant, is used by this sentence
+    Conflict(Sentence),
+}
+
+/// Outcome of a sentence modification.
+pub enum Modification {
+    /// the sentence has been changed, it is now at this version
+    Updated(i32),
+    /// the change leaves the sentence as it is, at this version
+    Unchanged(i32),
+    /// another sentence has the new content in the new language variant
+    Conflict(Sentence),
+}
+
+/// A recorded change of a sentence.
+#[derive(Clone)]
+pub struct RevisionRecord {
+    pub id: i32,
+    /// UTC date, e.g. `2018-04-01T10:00:00.000000Z`
+    pub revised_at: String,
+    pub author: Option<String>,
+    pub old: Snapshot,
+    pub new: Snapshot,
+}
+
+/// Outcome of a language deletion.
+pub enum LanguageDeletion {
+    /// the language still has sentences, deleted ones included
+    InUse(LanguageUsage),
+    /// the language has been deleted, with this number of sentences
+    Deleted(u64),
+}
+
+/// What a language merge has done.
+pub struct LanguageMerge {
+    pub moved: u64,
+    /// the sentences removed as duplicates, with the ones kept instead
+    pub duplicates: Vec<(Uuid, Uuid)>,
+}
+
+/// The review statuses of a sentence and of its structure.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Statuses {
+    pub sentence: ReviewStatus,
+    /// None if the sentence has no structure
+    pub structure: Option<ReviewStatus>,
+}
+
+impl Statuses {
+
+    /// Returns the statuses of a created sentence,
+    /// its structure having the status of the sentence.
+    pub fn of_new(sentence: &Sentence) -> Statuses {
+        Statuses {
+            sentence: sentence.review_status,
+            structure: sentence.structure.as_ref().map(|_| sentence.review_status),
+        }
+    }
+
+    /// Reads the stored statuses, the sentences stored without them being to review.
+    ///
+    /// Args:
+    ///
+    /// `has_structure` - true if the sentence has a structure
+    /// `status` - the stored status of the sentence
+    /// `structure_status` - the stored status of the structure
+    ///
+    /// Returns:
+    ///
+    /// the statuses, `Error::InvalidStoredValue` if one is unknown
+    pub fn from_stored(
+        has_structure: bool,
+        status: Option<String>,
+        structure_status: Option<String>,
+    ) -> Result<Statuses, Error> {
+
+        let parse = |status: Option<String>| -> Result<ReviewStatus, Error> {
+            match status {
+                Some(status) => status.parse().map_err(Error::InvalidStoredValue),
+                None => Ok(ReviewStatus::PendingReview),
+            }
+        };
+
+        Ok(Statuses {
+            sentence: parse(status)?,
+            structure: if has_structure { Some(parse(structure_status)?) } else { None },
+        })
+    }
+
+    /// Returns the statuses after a change of the sentence: a new text
+    /// or language variant is to be reviewed again, as is a new structure,
+    /// the drafts staying drafts.
+    ///
+    /// Args:
+    ///
+    /// `previous` - the sentence state before the change
+    /// `next` - the sentence state after the change
+    pub fn changed(&self, previous: &Snapshot, next: &Snapshot) -> Statuses {
+
+        let review_again = |status: ReviewStatus| {
+            if status == ReviewStatus::Draft {
+                status
+            } else {
+                ReviewStatus::PendingReview
+            }
+        };
+
+        let sentence = if previous.text != next.text || previous.language() != next.language() {
+            review_again(self.sentence)
+        } else {
+            self.sentence
+        };
+
+        let structure = match next.structure {
+            None => None,
+            Some(_) if previous.structure == next.structure => self.structure,
+            // an added structure is a draft along with its sentence
+            Some(_) => Some(review_again(self.structure.unwrap_or(self.sentence))),
+        };
+
+        Statuses {
+            sentence: sentence,
+            structure: structure,
+        }
+    }
+
+    /// Returns the statuses once a review is made.
+    ///
+    /// Returns:
+    ///
+    /// the statuses, `Error::InvalidInput` if the review
+    /// is about the structure of a sentence having none
+    pub fn reviewed(&self, review: &NewReview) -> Result<Statuses, Error> {
+        match review.subject {
+            ReviewSubject::Sentence => Ok(Statuses {
+                sentence: review.status,
+                structure: self.structure,
+            }),
+            ReviewSubject::Structure => match self.structure {
+                Some(_) => Ok(Statuses {
+                    sentence: self.sentence,
+                    structure: Some(review.status),
+                }),
+                None => Err(Error::InvalidInput("the sentence has no structure to review".to_string())),
+            },
+        }
+    }
+
+    /// Returns the statuses once the drafts are submitted for review.
+    pub fn submitted(&self) -> Statuses {
+
+        let submit = |status: ReviewStatus| {
+            if status == ReviewStatus::Draft {
+                ReviewStatus::PendingReview
+            } else {
+                status
+            }
+        };
+
+        Statuses {
+            sentence: submit(self.sentence),
+            structure: self.structure.map(submit),
+        }
+    }
+
+    /// Returns true if the sentence or its structure is pending review.
+    pub fn is_pending(&self) -> bool {
+        self.sentence == ReviewStatus::PendingReview ||
+            self.structure == Some(ReviewStatus::PendingReview)
+    }
+}
+
+pub trait SentenceRepository {
+
+    /// Returns a visible sentence and its version.
+    ///
+    /// Args:
+    ///
+    /// `sentence_uuid` - the sentence uuid
+    ///
+    /// Returns:
+    ///
+    /// the sentence and its version, None if it does not exist or is deleted
+    fn get_sentence(&self, sentence_uuid: &Uuid) -> Result<Option<(Sentence, i32)>, Error>;
+
+    /// Returns at most `query.limit` visible sentences matching the query.
+    ///
+    /// Args:
+    ///
+    /// `query` - the criteria of the sentences
+    fn get_sentences(&self, query: &SentenceQuery) -> Result<Vec<Sentence>, Error>;
+
+    /// Returns all the sentences, in the order they have been added.
+    ///
+    /// Args:
+    ///
+    /// `include_deleted` - true if the deleted sentences are returned too
+    fn get_all_sentences(&self, include_deleted: bool) -> Result<Vec<Sentence>, Error>;
+
+    /// Creates a sentence. Its language must exist, its uuid must be unused
+    /// and its content in its language variant unused by a visible sentence.
+    /// A deleted sentence with this content is restored instead, unless
+    /// another uuid is given.
+    ///
+    /// Args:
+    ///
+    /// `sentence` - the sentence, a new uuid is given to it if it has none
+    /// `author` - the author of the sentence
+    fn create_sentence(&self, sentence: &Sentence, author: &Author) -> Result<Creation, Error>;
+
+    /// Changes a visible sentence and records the revision.
+    ///
+    /// Args:
+    ///
+    /// `sentence_uuid` - the sentence uuid
+    /// `author` - the author of the change
+    /// `change` - computes the new state from the current state and version,
+    ///            its error cancels the modification
+    ///
+    /// Returns:
+    ///
+    /// what has been done, `Error::NotFound` if the sentence does not exist or is deleted
+    fn modify_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        author: &Author,
+        change: &mut FnMut(&Snapshot, i32) -> Result<Snapshot, Error>,
+    ) -> Result<Modification, Error>;
+
+    /// Soft deletes a sentence, or removes it with its revisions.
+    ///
+    /// Args:
+    ///
+    /// `sentence_uuid` - the sentence uuid
+    /// `purge` - true to remove the sentence for good, even if already deleted
+    ///
+    /// Returns:
+    ///
+    /// the number of removed revisions, None if there is no such sentence
+    fn delete_sentence(&self, sentence_uuid: &Uuid, purge: bool) -> Result<Option<i64>, Error>;
+
+    /// Makes a deleted sentence visible again, failing as a unique violation
+    /// if a visible sentence has taken its content in the meantime.
+    ///
+    /// Returns:
+    ///
+    /// false if the sentence does not exist or is not deleted
+    fn restore_sentence(&self, sentence_uuid: &Uuid) -> Result<bool, Error>;
+
+    /// Soft deletes a visible sentence in favour of another one,
+    /// which can receive a new structure.
+    ///
+    /// Args:
+    ///
+    /// `sentence_uuid` - the merged sentence uuid
+    /// `target_uuid` - the kept sentence uuid
+    /// `author` - the author of the structure change
+    /// `merge` - checks the merge from the states of the merged and kept sentences,
+    ///           and returns the new structure of the kept one, if any
+    ///
+    /// Returns:
+    ///
+    /// true if a structure has been given to the kept sentence,
+    /// `Error::NotFound` if one of the sentences does not exist or is deleted
+    fn merge_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        target_uuid: &Uuid,
+        author: &Author,
+        merge: &mut FnMut(&Snapshot, &Snapshot) -> Result<Option<String>, Error>,
+    ) -> Result<bool, Error>;
+
+    /// Returns the revisions of a sentence, deleted or not, oldest first.
+    ///
+    /// Returns:
+    ///
+    /// None if the sentence does not exist
+    fn get_revisions(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<RevisionRecord>>, Error>;
+
+    /// Returns the state of a sentence before one of its revisions.
+    ///
+    /// Returns:
+    ///
+    /// None if the sentence has no such revision
+    fn get_revision(&self, sentence_uuid: &Uuid, revision_id: i32) -> Result<Option<Snapshot>, Error>;
+
+    /// Returns the most recent visible sentences of each language
+    /// having enough of them, as `(iso639_3, content)`.
+    ///
+    /// Args:
+    ///
+    /// `max_sentences` - the number of sentences read per language
+    /// `min_sentences` - the number of sentences a language must have
+    fn get_training_corpus(
+        &self,
+        max_sentences: i64,
+        min_sentences: i64,
+    ) -> Result<Vec<(String, String)>, Error>;
+
+    /// Replaces all the stored near-duplicate groups.
+    ///
+    /// Args:
+    ///
+    /// `groups` - the groups, each one a list of sentence uuids
+    fn replace_near_duplicates(&self, groups: &[Vec<Uuid>]) -> Result<(), Error>;
+
+    /// Returns the visible near-duplicates of a language, as `(group, sentence)`,
+    /// by group then in the order the sentences have been added.
+    fn get_near_duplicates(&self, iso639_3: &str) -> Result<Vec<(i32, Sentence)>, Error>;
+}
+
+pub trait LanguageRepository {
+
+    /// Creates a language, `Error::AlreadyExists` if it already exists.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the language code
+    fn create_language(&self, iso639_3: &str) -> Result<(), Error>;
+
+    /// Returns all the languages, by code.
+    fn get_languages(&self) -> Result<Vec<LanguageUsage>, Error>;
+
+    /// Returns a language, None if it does not exist.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the language code
+    fn get_language(&self, iso639_3: &str) -> Result<Option<LanguageUsage>, Error>;
+
+    /// Deletes a language, `Error::NotFound` if it does not exist.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the language code
+    /// `cascade` - true to remove its sentences along with it
+    fn delete_language(&self, iso639_3: &str, cascade: bool) -> Result<LanguageDeletion, Error>;
+
+    /// Moves the sentences of a language to another one, then deletes it.
+    /// A visible sentence whose content is already used by a visible sentence
+    /// of the same variant of the target language is removed, the target one
+    /// being kept. The moved sentences are pending review again, unless drafts.
+    /// `Error::NotFound` if a language does not exist.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the merged language code
+    /// `target_iso639_3` - the kept language code
+    /// `author` - the author of the sentence revisions
+    fn merge_language(
+        &self,
+        iso639_3: &str,
+        target_iso639_3: &str,
+        author: &Author,
+    ) -> Result<LanguageMerge, Error>;
+
+    /// Gives a language another code, moving its sentences to it
+    /// and recording their revisions. Their review statuses are kept,
+    /// the language being the same under another code.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the current language code
+    /// `new_iso639_3` - the new language code, which must not be used yet
+    /// `author` - the author of the sentence revisions
+    ///
+    /// Returns:
+    ///
+    /// the number of sentences moved, `Error::NotFound` if the language
+    /// does not exist, a unique violation if the new code is used
+    fn rename_language(&self, iso639_3: &str, new_iso639_3: &str, author: &Author) -> Result<u64, Error>;
+}
+
+pub trait ApiKeyRepository {
+
+    /// Creates an API key.
+    ///
+    /// Args:
+    ///
+    /// `name` - the name of the user the key is given to, created if unknown
+    /// `role` - what the key allows
+    /// `key_hash` - the SHA-256 of the key, in hexadecimal
+    ///
+    /// Returns:
+    ///
+    /// the created key, without its secret
+    fn create_api_key(&self, name: &str, role: Role, key_hash: &str) -> Result<ApiKey, Error>;
+
+    /// Returns all the API keys, by id.
+    fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error>;
+
+    /// Returns the API key of a hash, None if there is none.
+    ///
+    /// Args:
+    ///
+    /// `key_hash` - the SHA-256 of the key, in hexadecimal
+    fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, Error>;
+
+    /// Removes an API key, so that it is refused from now on.
+    ///
+    /// Returns:
+    ///
+    /// false if there is no such key
+    fn revoke_api_key(&self, id: i32) -> Result<bool, Error>;
+}
+
+pub trait UserRepository {
+
+    /// Returns all the users, by id.
+    fn get_users(&self) -> Result<Vec<User>, Error>;
+
+    /// Returns a user, None if there is no such user.
+    ///
+    /// Args:
+    ///
+    /// `user_id` - the user id
+    fn get_user(&self, user_id: i32) -> Result<Option<User>, Error>;
+
+    /// Returns what a user has contributed: the number of visible sentences
+    /// added and of revisions made, per language, with the latest of them.
+    ///
+    /// Args:
+    ///
+    /// `user_id` - the user id
+    /// `limit` - the greatest number of sentences, and of revisions, listed
+    ///
+    /// Returns:
+    ///
+    /// the contributions, None if there is no such user
+    fn get_contributions(&self, user_id: i32, limit: i64) -> Result<Option<Contributions>, Error>;
+}
+
+pub trait ReviewRepository {
+
+    /// Reviews a visible sentence, or its structure, and records the review.
+    /// A change of status gives the sentence a new version.
+    ///
+    /// Args:
+    ///
+    /// `sentence_uuid` - the sentence uuid
+    /// `review` - the new status of the sentence or of its structure
+    /// `author` - the reviewer
+    ///
+    /// Returns:
+    ///
+    /// the recorded review, `Error::NotFound` if the sentence does not exist or is deleted,
+    /// `Error::InvalidInput` if the sentence has no structure to review
+    fn review_sentence(&self, sentence_uuid: &Uuid, review: &NewReview, author: &Author) -> Result<Review, Error>;
+
+    /// Returns the reviews of a sentence, deleted or not, oldest first.
+    ///
+    /// Returns:
+    ///
+    /// None if the sentence does not exist
+    fn get_reviews(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<Review>>, Error>;
+
+    /// Submits for review a visible sentence and its structure, if drafts.
+    /// A change of status gives the sentence a new version.
+    ///
+    /// Returns:
+    ///
+    /// `Error::NotFound` if the sentence does not exist or is deleted
+    fn submit_sentence(&self, sentence_uuid: &Uuid) -> Result<(), Error>;
+
+    /// Returns the visible sentences whose text or structure is pending review,
+    /// in the order they have been added.
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - only the sentences of this language, if any
+    /// `limit` - the greatest number of sentences to return
+    fn get_review_queue(&self, iso639_3: Option<&str>, limit: i64) -> Result<Vec<Sentence>, Error>;
+}
+
+/// Everything the handlers need from a storage.
+pub trait Repository:
+    SentenceRepository + LanguageRepository + ApiKeyRepository + UserRepository + ReviewRepository {}
+
+impl<T> Repository for T
+    where T: SentenceRepository + LanguageRepository + ApiKeyRepository + UserRepository + ReviewRepository {}
+
+/// The storage selected at startup.
+#[derive(Clone)]
+pub enum Storage {
+    Postgres(db::Pool),
+    Sqlite(Arc<Mutex<rusqlite::Connection>>),
+    Memory(MemoryRepository),
+}
+
+impl Storage {
+
+    /// Opens the configured storage, its schema is left to the migrations.
+    ///
+    /// Args:
+    ///
+    /// `config` - the storage configuration
+    ///
+    /// Returns:
+    ///
+    /// the storage, or why the database cannot be reached or opened
+    pub fn open(config: &StorageConfig) -> Result<Storage, String> {
+        match *config {
+            StorageConfig::Postgres(ref database) => {
+                Ok(Storage::Postgres(db::init_pool(database)?))
+            },
+            StorageConfig::Sqlite(ref path) => {
+                Ok(Storage::Sqlite(Arc::new(Mutex::new(sqlite::open(path)?))))
+            },
+            StorageConfig::Memory => Ok(Storage::Memory(MemoryRepository::new())),
+        }
+    }
+
+    /// Returns a repository on a connection of the storage.
+    ///
+    /// Returns:
+    ///
+    /// the repository, `Error::Unavailable` if no connection can be had
+    pub fn repository<'a>(&'a self) -> Result<Box<Repository + 'a>, Error> {
+        match *self {
+            Storage::Postgres(ref pool) => {
+                let connection = pool.get().map_err(|_| Error::Unavailable)?;
+                Ok(Box::new(PostgresRepository::new(connection)))
+            },
+            Storage::Sqlite(ref connection) => {
+                // a SQLite file has one writer at a time anyway;
+                // a request that panicked rolled its transaction back on drop,
+                // so the connection stays usable after the mutex is poisoned
+                let connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+                Ok(Box::new(SqliteRepository::new(connection)))
+            },
+            Storage::Memory(ref repository) => Ok(Box::new(repository.clone())),
+        }
+    }
+
+    /// Returns the migrations of the storage, on one of its connections.
+    ///
+    /// Returns:
+    ///
+    /// the migration store, `Error::Unavailable` if no connection can be had
+    pub fn migration_store<'a>(&'a self) -> Result<Box<MigrationStore + 'a>, Error> {
+        match *self {
+            Storage::Postgres(ref pool) => {
+                let connection = pool.get().map_err(|_| Error::Unavailable)?;
+                Ok(Box::new(PostgresRepository::new(connection)))
+            },
+            Storage::Sqlite(ref connection) => {
+                let connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+                Ok(Box::new(SqliteRepository::new(connection)))
+            },
+            Storage::Memory(ref repository) => Ok(Box::new(repository.clone())),
+        }
+    }
+}
+
+/// Repository request guard, on a connection of the managed `Storage`.
+pub struct RequestRepository<'r>(Box<Repository + 'r>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for RequestRepository<'r> {
+
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<RequestRepository<'r>, ()> {
+
+        let storage = request.guard::<State<Storage>>()?;
+        match storage.inner().repository() {
+            Ok(repository) => Outcome::Success(RequestRepository(repository)),
+            Err(_) => Outcome::Failure((Status::ServiceUnavailable, ())),
+        }
+    }
+}
+
+impl<'r> Deref for RequestRepository<'r> {
+    type Target = Repository + 'r;
+
+    fn deref(&self) -> &(Repository + 'r) {
+        &*self.0
+    }
+}
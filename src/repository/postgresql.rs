@@ -0,0 +1,1872 @@
+use postgres::GenericConnection;
+use postgres::error::UNIQUE_VIOLATION;
+use postgres::rows::Row;
+use r2d2::PooledConnection;
+use r2d2_postgres::PostgresConnectionManager;
+
+use uuid::Uuid;
+
+use auth::Author;
+use error::Error;
+use language_tag::LanguageTag;
+use migrations::{self, AppliedMigration, Migration, MigrationStore};
+use models::{
+    ApiKey,
+    ContributedEdit,
+    Contributions,
+    LanguageContributions,
+    NewReview,
+    Review,
+    Role,
+    Sentence,
+    Snapshot,
+    User,
+};
+use repository::{
+    ApiKeyRepository,
+    Creation,
+    LanguageDeletion,
+    LanguageMerge,
+    LanguageRepository,
+    LanguageUsage,
+    Modification,
+    ReviewRepository,
+    RevisionRecord,
+    SentenceQuery,
+    SentenceRepository,
+    Statuses,
+    UserRepository,
+};
+
+/// Repository on a pooled PostgreSQL connection.
+pub struct PostgresRepository {
+    connection: PooledConnection<PostgresConnectionManager>,
+}
+
+impl PostgresRepository {
+
+    /// Creates a repository using the given connection.
+    ///
+    /// Args:
+    ///
+    /// `connection` - database connection handler
+    pub fn new(connection: PooledConnection<PostgresConnectionManager>) -> PostgresRepository {
+        PostgresRepository {
+            connection: connection,
+        }
+    }
+}
+
+/// The columns of a sentence read by `sentence_from_row`,
+/// from `sentence` and the tables of `SENTENCE_JOINS`.
+const SENTENCE_COLUMNS: &str = r#"
+    sentence.id,
+    sentence.content,
+    language.iso639_3,
+    sentence.structure::text,
+    sentence.script,
+    sentence.region,
+    sentence_attribution.created_by,
+    sentence_attribution.updated_by,
+    sentence_status.status,
+    sentence_status.structure_status
+"#;
+
+/// The tables joined to `sentence` for the `SENTENCE_COLUMNS`.
+const SENTENCE_JOINS: &str = r#"
+    JOIN language ON (sentence.language_id = language.id)
+    LEFT JOIN sentence_attribution ON (sentence_attribution.sentence_id = sentence.id)
+    LEFT JOIN sentence_status ON (sentence_status.sentence_id = sentence.id)
+"#;
+
+/// Reads a sentence from the `SENTENCE_COLUMNS`, starting at the given column.
+fn sentence_from_row(row: &Row, first: usize) -> Result<Sentence, Error> {
+    let structure: Option<String> = row.get(first + 3);
+    let statuses = Statuses::from_stored(
+        structure.is_some(),
+        row.get(first + 8),
+        row.get(first + 9),
+    )?;
+    Ok(Sentence {
+        id: row.get(first),
+        text: row.get(first + 1),
+        iso639_3: row.get(first + 2),
+        structure: structure,
+        script: row.get(first + 4),
+        region: row.get(first + 5),
+        created_by: row.get(first + 6),
+        updated_by: row.get(first + 7),
+        review_status: statuses.sentence,
+        structure_review_status: statuses.structure,
+    })
+}
+
+/// Reads a sentence state from the `content, iso639_3, structure, script, region` columns,
+/// starting at the given one.
+fn snapshot_from_row(row: &Row, first: usize) -> Snapshot {
+    Snapshot {
+        text: row.get(first),
+        iso639_3: row.get(first + 1),
+        structure: row.get(first + 2),
+        script: row.get(first + 3),
+        region: row.get(first + 4),
+    }
+}
+
+/// Returns the first sentence selected by a query on an uuid, a content and a language variant,
+/// its columns being the ones read by `sentence_from_row`.
+fn query_sentence(
+    connection: &GenericConnection,
+    query: &str,
+    sentence_uuid: &Uuid,
+    content: &str,
+    language: &LanguageTag,
+) -> Result<Option<Sentence>, Error> {
+
+    let rows = connection.query(
+        query,
+        &[
+            &sentence_uuid,
+            &content,
+            &language.iso639_3,
+            &language.script,
+            &language.region,
+        ],
+    )?;
+
+    rows.iter().next().map(|row| sentence_from_row(&row, 0)).transpose()
+}
+
+/// Returns the sentence, not deleted, having the given uuid,
+/// or the given content in the given language variant.
+fn get_sentence_by_uuid_or_content(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+    content: &str,
+    language: &LanguageTag,
+) -> Result<Option<Sentence>, Error> {
+
+    query_sentence(
+        connection,
+        &format!(
+            r#"
+                SELECT {}
+                FROM sentence {}
+                WHERE
+                    sentence.deleted_at IS NULL AND
+                    (
+                        sentence.id = $1 OR
+                        (
+                            sentence.content = $2 AND
+                            language.iso639_3 = $3 AND
+                            sentence.script IS NOT DISTINCT FROM $4 AND
+                            sentence.region IS NOT DISTINCT FROM $5
+                        )
+                    )
+            "#,
+            SENTENCE_COLUMNS,
+            SENTENCE_JOINS,
+        ),
+        sentence_uuid,
+        content,
+        language,
+    )
+}
+
+/// Returns the sentence, not deleted, other than the given one,
+/// having the given content in the given language variant.
+fn get_other_sentence_by_content(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+    content: &str,
+    language: &LanguageTag,
+) -> Result<Option<Sentence>, Error> {
+
+    query_sentence(
+        connection,
+        &format!(
+            r#"
+                SELECT {}
+                FROM sentence {}
+                WHERE
+                    sentence.deleted_at IS NULL AND
+                    sentence.id != $1 AND
+                    sentence.content = $2 AND
+                    language.iso639_3 = $3 AND
+                    sentence.script IS NOT DISTINCT FROM $4 AND
+                    sentence.region IS NOT DISTINCT FROM $5
+            "#,
+            SENTENCE_COLUMNS,
+            SENTENCE_JOINS,
+        ),
+        sentence_uuid,
+        content,
+        language,
+    )
+}
+
+/// Returns the current state and version of a sentence and locks its row
+/// until the end of the transaction, so the revision recorded
+/// afterwards holds the actual previous values.
+///
+/// Args:
+///
+/// `connection` - the transaction in which the sentence is modified
+/// `sentence_uuid` - the sentence uuid
+///
+/// Returns:
+///
+/// the sentence state and version, None if the sentence does not exist or is deleted
+fn lock_snapshot(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+) -> Result<Option<(Snapshot, i32)>, Error> {
+
+    let rows = connection.query(
+        r#"
+            SELECT
+                sentence.content,
+                language.iso639_3,
+                sentence.structure::text,
+                sentence.script,
+                sentence.region,
+                sentence.version
+            FROM sentence
+            LEFT JOIN language ON (sentence.language_id = language.id)
+            WHERE
+                sentence.id = $1 AND
+                sentence.deleted_at IS NULL
+            FOR UPDATE OF sentence
+        "#,
+        &[&sentence_uuid],
+    )?;
+
+    Ok(rows.iter()
+        .next()
+        .map(|row| (snapshot_from_row(&row, 0), row.get(5))))
+}
+
+/// Records a revision of a sentence, from its previous state
+/// to the state currently stored in the database.
+///
+/// Args:
+///
+/// `connection` - the transaction in which the sentence has been modified
+/// `sentence_uuid` - the sentence uuid
+/// `author` - the author of the change
+/// `previous` - the sentence state before the change
+fn record(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+    author: &Author,
+    previous: &Snapshot,
+) -> Result<(), Error> {
+
+    let rows = connection.query(
+        r#"
+            INSERT INTO sentence_revision(
+                sentence_id,
+                author,
+                old_content,
+                old_iso639_3,
+                old_structure,
+                old_script,
+                old_region,
+                new_content,
+                new_iso639_3,
+                new_structure,
+                new_script,
+                new_region
+            )
+            SELECT
+                sentence.id,
+                $2,
+                $3,
+                $4,
+                $5::TEXT::XML,
+                $6,
+                $7,
+                sentence.content,
+                language.iso639_3,
+                sentence.structure,
+                sentence.script,
+                sentence.region
+            FROM sentence
+            LEFT JOIN language ON (sentence.language_id = language.id)
+            WHERE sentence.id = $1
+            RETURNING id
+        "#,
+        &[
+            &sentence_uuid,
+            &author.name,
+            &previous.text,
+            &previous.iso639_3,
+            &previous.structure,
+            &previous.script,
+            &previous.region,
+        ],
+    )?;
+
+    let user_id = match author.user_id {
+        Some(user_id) => user_id,
+        None => return Ok(()),
+    };
+
+    let revision_id: i32 = rows.get(0).get(0);
+
+    connection.execute(
+        "INSERT INTO revision_attribution(revision_id, user_id) VALUES ($1, $2)",
+        &[
+            &revision_id,
+            &user_id,
+        ],
+    )?;
+
+    attribute_update(connection, sentence_uuid, user_id)
+}
+
+/// Records a user as the last one having changed a sentence.
+///
+/// Args:
+///
+/// `connection` - the transaction in which the sentence has been changed
+/// `sentence_uuid` - the sentence uuid
+/// `user_id` - the user id
+fn attribute_update(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+    user_id: i32,
+) -> Result<(), Error> {
+
+    connection.execute(
+        r#"
+            INSERT INTO sentence_attribution(sentence_id, updated_by)
+            VALUES ($1, $2)
+            ON CONFLICT (sentence_id) DO UPDATE SET updated_by = EXCLUDED.updated_by
+        "#,
+        &[
+            &sentence_uuid,
+            &user_id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Returns the review statuses of a sentence.
+///
+/// Args:
+///
+/// `connection` - the transaction in which the sentence is locked
+/// `sentence_uuid` - the sentence uuid
+/// `has_structure` - true if the sentence has a structure
+fn get_statuses(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+    has_structure: bool,
+) -> Result<Statuses, Error> {
+
+    let rows = connection.query(
+        "SELECT status, structure_status FROM sentence_status WHERE sentence_id = $1",
+        &[&sentence_uuid],
+    )?;
+
+    match rows.iter().next() {
+        Some(row) => Statuses::from_stored(has_structure, row.get(0), row.get(1)),
+        None => Statuses::from_stored(has_structure, None, None),
+    }
+}
+
+/// Stores the review statuses of a sentence.
+///
+/// Args:
+///
+/// `connection` - the transaction in which the sentence is changed
+/// `sentence_uuid` - the sentence uuid
+/// `statuses` - the new statuses
+fn store_statuses(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+    statuses: &Statuses,
+) -> Result<(), Error> {
+
+    connection.execute(
+        r#"
+            INSERT INTO sentence_status(sentence_id, status, structure_status)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (sentence_id) DO UPDATE SET
+                status = EXCLUDED.status,
+                structure_status = EXCLUDED.structure_status
+        "#,
+        &[
+            &sentence_uuid,
+            &statuses.sentence.as_str(),
+            &statuses.structure.map(|status| status.as_str()),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Gives a sentence a new version, and so a new entity tag, when its statuses change,
+/// as they are sent along with it.
+///
+/// Args:
+///
+/// `connection` - the transaction in which the statuses are changed
+/// `sentence_uuid` - the sentence uuid
+fn touch(
+    connection: &GenericConnection,
+    sentence_uuid: &Uuid,
+) -> Result<(), Error> {
+
+    connection.execute(
+        "UPDATE sentence SET version = version + 1 WHERE id = $1",
+        &[&sentence_uuid],
+    )?;
+
+    Ok(())
+}
+
+impl SentenceRepository for PostgresRepository {
+
+    fn get_sentence(&self, sentence_uuid: &Uuid) -> Result<Option<(Sentence, i32)>, Error> {
+
+        let rows = self.connection.query(
+            &format!(
+                r#"
+                    SELECT {}, sentence.version
+                    FROM sentence {}
+                    WHERE
+                        sentence.id = $1 AND
+                        sentence.deleted_at IS NULL
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[&sentence_uuid],
+        )?;
+
+        rows.iter()
+            .next()
+            .map(|row| -> Result<_, Error> { Ok((sentence_from_row(&row, 0)?, row.get(10))) })
+            .transpose()
+    }
+
+    fn get_sentences(&self, query: &SentenceQuery) -> Result<Vec<Sentence>, Error> {
+
+        let rows = self.connection.query(
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        ($1::UUID IS NULL OR sentence.id >= $1) AND
+                        ($2::TEXT IS NULL OR language.iso639_3 = $2) AND
+                        ($3::TEXT IS NULL OR sentence.script = $3) AND
+                        ($4::TEXT IS NULL OR sentence.region = $4) AND
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        added_at,
+                        sentence.id
+                    LIMIT $5
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[
+                &query.starting_after_id,
+                &query.iso639_3,
+                &query.script,
+                &query.region,
+                &query.limit,
+            ],
+        )?;
+
+        rows.iter().map(|row| sentence_from_row(&row, 0)).collect()
+    }
+
+    fn get_all_sentences(&self, include_deleted: bool) -> Result<Vec<Sentence>, Error> {
+
+        let rows = self.connection.query(
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        $1 OR
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        added_at,
+                        sentence.id
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[&include_deleted],
+        )?;
+
+        rows.iter().map(|row| sentence_from_row(&row, 0)).collect()
+    }
+
+    fn create_sentence(&self, sentence: &Sentence, author: &Author) -> Result<Creation, Error> {
+
+        let uuid = sentence.id.unwrap_or_else(Uuid::new_v4);
+
+        let statuses = Statuses::of_new(sentence);
+
+        /* a deleted sentence with the same content is brought back
+           instead of being created again, if the given id is its own
+           and no visible sentence has taken its content since */
+        let rows = self.connection.query(
+            r#"
+                UPDATE sentence
+                SET
+                    deleted_at = NULL,
+                    structure = $4::TEXT::XML
+                WHERE id = (
+                    SELECT deleted.id
+                    FROM sentence AS deleted
+                    WHERE
+                        deleted.content = $2 AND
+                        deleted.language_id = (SELECT id FROM language WHERE iso639_3 = $3) AND
+                        deleted.script IS NOT DISTINCT FROM $5 AND
+                        deleted.region IS NOT DISTINCT FROM $6 AND
+                        deleted.deleted_at IS NOT NULL AND
+                        ($1::UUID IS NULL OR deleted.id = $1) AND
+                        NOT EXISTS (
+                            SELECT 1
+                            FROM sentence AS visible
+                            WHERE
+                                visible.content = deleted.content AND
+                                visible.language_id = deleted.language_id AND
+                                visible.script IS NOT DISTINCT FROM deleted.script AND
+                                visible.region IS NOT DISTINCT FROM deleted.region AND
+                                visible.deleted_at IS NULL
+                        )
+                    ORDER BY deleted.deleted_at DESC
+                    LIMIT 1
+                )
+                RETURNING id
+            "#,
+            &[
+                &sentence.id,
+                &sentence.text,
+                &sentence.iso639_3,
+                &sentence.structure,
+                &sentence.script,
+                &sentence.region,
+            ],
+        )?;
+
+        if let Some(row) = rows.iter().next() {
+
+            let restored: Uuid = row.get(0);
+
+            if let Some(user_id) = author.user_id {
+                attribute_update(&*self.connection, &restored, user_id)?;
+            }
+
+            store_statuses(&*self.connection, &restored, &statuses)?;
+
+            return Ok(Creation::Restored(restored));
+        }
+
+        // the sentence, its attribution and its statuses are inserted by the same statement
+        let result = self.connection.query(
+            r#"
+            WITH created AS (
+                INSERT INTO sentence(
+                    id,
+                    content,
+                    language_id,
+                    structure,
+                    script,
+                    region
+                ) VALUES (
+                    $1,
+                    $2,
+                    -- the language id is found using coalesce()
+                    -- in order to force a relation error
+                    -- if no language is found
+                    -- (it prevents NULL to be inserted as the sentence language) */
+                    COALESCE((SELECT id FROM language WHERE iso639_3 = $3), 0),
+                    $4::TEXT::XML,
+                    $5,
+                    $6
+                )
+                RETURNING id
+            ), attributed AS (
+                INSERT INTO sentence_attribution(sentence_id, created_by, updated_by)
+                SELECT id, $7::INTEGER, $7::INTEGER
+                FROM created
+            )
+            INSERT INTO sentence_status(sentence_id, status, structure_status)
+            SELECT id, $8::TEXT, $9::TEXT
+            FROM created
+            RETURNING sentence_id
+            "#,
+            &[
+                &uuid,
+                &sentence.text,
+                &sentence.iso639_3,
+                &sentence.structure,
+                &sentence.script,
+                &sentence.region,
+                &author.user_id,
+                &statuses.sentence.as_str(),
+                &statuses.structure.map(|status| status.as_str()),
+            ],
+        );
+
+        let error = match result {
+            Ok(rows) => return Ok(Creation::Created(rows.get(0).get(0))),
+            Err(error) => error,
+        };
+
+        // an unknown language is reported as a foreign key violation
+        if error.code() != Some(&UNIQUE_VIOLATION) {
+            return Err(Error::from(error));
+        }
+
+        let language = LanguageTag {
+            iso639_3: sentence.iso639_3.clone(),
+            script: sentence.script.clone(),
+            region: sentence.region.clone(),
+        };
+
+        // the uuid may be the one of a deleted sentence, which is not returned
+        match get_sentence_by_uuid_or_content(&*self.connection, &uuid, &sentence.text, &language)? {
+            Some(conflicting) => Ok(Creation::Conflict(conflicting)),
+            None => Err(Error::from(error)),
+        }
+    }
+
+    fn modify_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        author: &Author,
+        change: &mut FnMut(&Snapshot, i32) -> Result<Snapshot, Error>,
+    ) -> Result<Modification, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let (previous, version) = match lock_snapshot(&transaction, sentence_uuid)? {
+            Some(locked) => locked,
+            None => return Err(Error::NotFound),
+        };
+
+        let next = change(&previous, version)?;
+
+        if next == previous {
+            return Ok(Modification::Unchanged(version));
+        }
+
+        /* we add ::TEXT::XML because Postgresql query parameters need explicit cast:
+           https://github.com/sfackler/rust-postgres/issues/309#issuecomment-351063887 */
+        let result = transaction.query(
+            r#"
+                UPDATE sentence
+                SET
+                    content = $1,
+                    -- same trick as on creation: force a relation error
+                    -- if the language does not exist
+                    language_id = COALESCE((SELECT id FROM language WHERE iso639_3 = $2), 0),
+                    structure = $3::TEXT::XML,
+                    script = $4,
+                    region = $5
+                WHERE id = $6
+                RETURNING version
+            "#,
+            &[
+                &next.text,
+                &next.iso639_3,
+                &next.structure,
+                &next.script,
+                &next.region,
+                &sentence_uuid,
+            ],
+        );
+
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(error) => {
+
+                if error.code() == Some(&UNIQUE_VIOLATION) {
+
+                    /* the failed transaction must be rolled back
+                       before looking for the conflicting sentence */
+                    drop(transaction);
+
+                    let conflicting = get_other_sentence_by_content(
+                        &*self.connection,
+                        sentence_uuid,
+                        &next.text,
+                        &next.language(),
+                    )?;
+
+                    if let Some(sentence) = conflicting {
+                        return Ok(Modification::Conflict(sentence));
+                    }
+                }
+
+                return Err(Error::from(error));
+            }
+        };
+
+        let version: i32 = rows.get(0).get(0);
+
+        let statuses = get_statuses(&transaction, sentence_uuid, previous.structure.is_some())?;
+        store_statuses(&transaction, sentence_uuid, &statuses.changed(&previous, &next))?;
+
+        record(&transaction, sentence_uuid, author, &previous)?;
+        transaction.commit()?;
+
+        Ok(Modification::Updated(version))
+    }
+
+    fn delete_sentence(&self, sentence_uuid: &Uuid, purge: bool) -> Result<Option<i64>, Error> {
+
+        /* revisions are removed by the ON DELETE CASCADE of their foreign key,
+           the data-modifying CTE still sees them as it shares the statement snapshot */
+        let rows = if purge {
+            self.connection.query(
+                r#"
+                    WITH purged AS (
+                        DELETE FROM sentence
+                        WHERE id = $1
+                        RETURNING id
+                    )
+                    SELECT
+                        (
+                            SELECT COUNT(*)
+                            FROM sentence_revision
+                            WHERE sentence_id = purged.id
+                        )
+                    FROM purged
+                "#,
+                &[&sentence_uuid],
+            )?
+        } else {
+            self.connection.query(
+                r#"
+                    UPDATE sentence
+                    SET deleted_at = CURRENT_TIMESTAMP
+                    WHERE
+                        id = $1 AND
+                        deleted_at IS NULL
+                    RETURNING 0::BIGINT
+                "#,
+                &[&sentence_uuid],
+            )?
+        };
+
+        Ok(rows.iter().next().map(|row| row.get(0)))
+    }
+
+    fn restore_sentence(&self, sentence_uuid: &Uuid) -> Result<bool, Error> {
+
+        let restored = self.connection.execute(
+            r#"
+                UPDATE sentence
+                SET deleted_at = NULL
+                WHERE
+                    id = $1 AND
+                    deleted_at IS NOT NULL
+            "#,
+            &[&sentence_uuid],
+        )?;
+
+        Ok(restored > 0)
+    }
+
+    fn merge_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        target_uuid: &Uuid,
+        author: &Author,
+        merge: &mut FnMut(&Snapshot, &Snapshot) -> Result<Option<String>, Error>,
+    ) -> Result<bool, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let merged = lock_snapshot(&transaction, sentence_uuid)?;
+        let target = lock_snapshot(&transaction, target_uuid)?;
+
+        let (merged, target) = match (merged, target) {
+            (Some((merged, _)), Some((target, _))) => (merged, target),
+            _ => return Err(Error::NotFound),
+        };
+
+        let moved_structure = merge(&merged, &target)?;
+
+        if let Some(ref structure) = moved_structure {
+
+            transaction.execute(
+                r#"
+                    UPDATE sentence
+                    SET structure = $1::TEXT::XML
+                    WHERE id = $2
+                "#,
+                &[
+                    &structure,
+                    &target_uuid,
+                ],
+            )?;
+
+            let next = Snapshot {
+                structure: Some(structure.clone()),
+                ..target.clone()
+            };
+
+            let statuses = get_statuses(&transaction, target_uuid, target.structure.is_some())?;
+            store_statuses(&transaction, target_uuid, &statuses.changed(&target, &next))?;
+
+            record(&transaction, target_uuid, author, &target)?;
+        }
+
+        transaction.execute(
+            r#"
+                UPDATE sentence
+                SET deleted_at = CURRENT_TIMESTAMP
+                WHERE id = $1
+            "#,
+            &[&sentence_uuid],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(moved_structure.is_some())
+    }
+
+    fn get_revisions(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<RevisionRecord>>, Error> {
+
+        let exists = self.connection.query(
+            "SELECT 1 FROM sentence WHERE id = $1",
+            &[&sentence_uuid],
+        )?;
+
+        if exists.is_empty() {
+            return Ok(None);
+        }
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    id,
+                    to_char(
+                        revised_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    ),
+                    author,
+                    old_content,
+                    old_iso639_3,
+                    old_structure::text,
+                    old_script,
+                    old_region,
+                    new_content,
+                    new_iso639_3,
+                    new_structure::text,
+                    new_script,
+                    new_region
+                FROM sentence_revision
+                WHERE sentence_id = $1
+                ORDER BY id
+            "#,
+            &[&sentence_uuid],
+        )?;
+
+        Ok(Some(rows.iter()
+            .map(|row| RevisionRecord {
+                id: row.get(0),
+                revised_at: row.get(1),
+                author: row.get(2),
+                old: snapshot_from_row(&row, 3),
+                new: snapshot_from_row(&row, 8),
+            })
+            .collect()))
+    }
+
+    fn get_revision(&self, sentence_uuid: &Uuid, revision_id: i32) -> Result<Option<Snapshot>, Error> {
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    old_content,
+                    old_iso639_3,
+                    old_structure::text,
+                    old_script,
+                    old_region
+                FROM sentence_revision
+                WHERE id = $1 AND sentence_id = $2
+            "#,
+            &[
+                &revision_id,
+                &sentence_uuid,
+            ],
+        )?;
+
+        Ok(rows.iter().next().map(|row| snapshot_from_row(&row, 0)))
+    }
+
+    fn get_training_corpus(
+        &self,
+        max_sentences: i64,
+        min_sentences: i64,
+    ) -> Result<Vec<(String, String)>, Error> {
+
+        let rows = self.connection.query(
+            r#"
+                SELECT iso639_3, content
+                FROM (
+                    SELECT
+                        language.iso639_3,
+                        sentence.content,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY sentence.language_id
+                            ORDER BY sentence.added_at DESC
+                        ) AS rank,
+                        COUNT(*) OVER (PARTITION BY sentence.language_id) AS sentences
+                    FROM sentence
+                    JOIN language ON (sentence.language_id = language.id)
+                    WHERE sentence.deleted_at IS NULL
+                ) AS corpus
+                WHERE
+                    rank <= $1 AND
+                    sentences >= $2
+            "#,
+            &[
+                &max_sentences,
+                &min_sentences,
+            ],
+        )?;
+
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    fn replace_near_duplicates(&self, groups: &[Vec<Uuid>]) -> Result<(), Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        transaction.execute("DELETE FROM near_duplicate", &[])?;
+
+        {
+            let statement = transaction.prepare(
+                "INSERT INTO near_duplicate(sentence_id, group_id) VALUES ($1, $2)"
+            )?;
+
+            for (group_id, group) in groups.iter().enumerate() {
+                for sentence_uuid in group.iter() {
+                    statement.execute(&[
+                        sentence_uuid,
+                        &(group_id as i32 + 1),
+                    ])?;
+                }
+            }
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn get_near_duplicates(&self, iso639_3: &str) -> Result<Vec<(i32, Sentence)>, Error> {
+
+        let rows = self.connection.query(
+            &format!(
+                r#"
+                    SELECT {}, near_duplicate.group_id
+                    FROM sentence {}
+                    JOIN near_duplicate ON (near_duplicate.sentence_id = sentence.id)
+                    WHERE
+                        language.iso639_3 = $1 AND
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        near_duplicate.group_id,
+                        sentence.added_at,
+                        sentence.id
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[&iso639_3],
+        )?;
+
+        rows.iter().map(|row| -> Result<_, Error> { Ok((row.get(10), sentence_from_row(&row, 0)?)) }).collect()
+    }
+}
+
+impl LanguageRepository for PostgresRepository {
+
+    fn create_language(&self, iso639_3: &str) -> Result<(), Error> {
+
+        // an existing language is reported as a unique violation
+        self.connection.execute(
+            r#"
+            INSERT INTO language(iso639_3)
+            VALUES ($1)
+            "#,
+            &[&iso639_3],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_languages(&self) -> Result<Vec<LanguageUsage>, Error> {
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    language.iso639_3,
+                    COUNT(sentence.id)
+                FROM language
+                LEFT JOIN sentence ON (
+                    sentence.language_id = language.id AND
+                    sentence.deleted_at IS NULL
+                )
+                GROUP BY language.id
+                ORDER BY language.iso639_3
+            "#,
+            &[],
+        )?;
+
+        Ok(rows.iter().map(|row| language_from_row(&row)).collect())
+    }
+
+    fn get_language(&self, iso639_3: &str) -> Result<Option<LanguageUsage>, Error> {
+        get_language(&*self.connection, iso639_3)
+    }
+
+    fn delete_language(&self, iso639_3: &str, cascade: bool) -> Result<LanguageDeletion, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let rows = transaction.query(
+            r#"
+                SELECT
+                    id,
+                    (SELECT COUNT(*) FROM sentence WHERE language_id = language.id)
+                FROM language
+                WHERE iso639_3 = $1
+                FOR UPDATE
+            "#,
+            &[&iso639_3],
+        )?;
+
+        if rows.is_empty() {
+            return Err(Error::NotFound);
+        }
+
+        let language_id: i32 = rows.get(0).get(0);
+        let sentences: i64 = rows.get(0).get(1);
+
+        if sentences > 0 && !cascade {
+            return match get_language(&transaction, iso639_3)? {
+                Some(usage) => Ok(LanguageDeletion::InUse(usage)),
+                None => Err(Error::NotFound),
+            };
+        }
+
+        let sentences_removed = transaction.execute(
+            "DELETE FROM sentence WHERE language_id = $1",
+            &[&language_id],
+        )?;
+
+        transaction.execute(
+            "DELETE FROM language WHERE id = $1",
+            &[&language_id],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(LanguageDeletion::Deleted(sentences_removed))
+    }
+
+    fn merge_language(
+        &self,
+        iso639_3: &str,
+        target_iso639_3: &str,
+        author: &Author,
+    ) -> Result<LanguageMerge, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let rows = transaction.query(
+            r#"
+                SELECT
+                    (SELECT id FROM language WHERE iso639_3 = $1 FOR UPDATE),
+                    (SELECT id FROM language WHERE iso639_3 = $2 FOR UPDATE)
+            "#,
+            &[
+                &iso639_3,
+                &target_iso639_3,
+            ],
+        )?;
+
+        let source_id: Option<i32> = rows.get(0).get(0);
+        let target_id: Option<i32> = rows.get(0).get(1);
+
+        let (source_id, target_id) = match (source_id, target_id) {
+            (Some(source_id), Some(target_id)) => (source_id, target_id),
+            _ => return Err(Error::NotFound),
+        };
+
+        // the deleted sentences hold no content, they are moved as they are
+        let rows = transaction.query(
+            r#"
+                DELETE FROM sentence AS source
+                USING sentence AS target
+                WHERE
+                    source.language_id = $1 AND
+                    target.language_id = $2 AND
+                    source.content = target.content AND
+                    source.script IS NOT DISTINCT FROM target.script AND
+                    source.region IS NOT DISTINCT FROM target.region AND
+                    source.deleted_at IS NULL AND
+                    target.deleted_at IS NULL
+                RETURNING
+                    source.id,
+                    target.id
+            "#,
+            &[
+                &source_id,
+                &target_id,
+            ],
+        )?;
+
+        let duplicates: Vec<(Uuid, Uuid)> = rows.iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect();
+
+        // the sentences are to review again in their new language, drafts aside
+        transaction.execute(
+            r#"
+                UPDATE sentence_status
+                SET status = 'pending_review'
+                WHERE
+                    status <> 'draft' AND
+                    sentence_id IN (SELECT id FROM sentence WHERE language_id = $1)
+            "#,
+            &[&source_id],
+        )?;
+
+        let moved = move_sentences(&transaction, source_id, target_id, iso639_3, target_iso639_3, author)?;
+
+        transaction.commit()?;
+
+        Ok(LanguageMerge {
+            moved: moved,
+            duplicates: duplicates,
+        })
+    }
+
+    fn rename_language(&self, iso639_3: &str, new_iso639_3: &str, author: &Author) -> Result<u64, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let rows = transaction.query(
+            "SELECT id FROM language WHERE iso639_3 = $1 FOR UPDATE",
+            &[&iso639_3],
+        )?;
+
+        let source_id: i32 = match rows.iter().next() {
+            Some(row) => row.get(0),
+            None => return Err(Error::NotFound),
+        };
+
+        // an existing language is reported as a unique violation
+        let rows = transaction.query(
+            "INSERT INTO language(iso639_3) VALUES ($1) RETURNING id",
+            &[&new_iso639_3],
+        )?;
+        let target_id: i32 = rows.get(0).get(0);
+
+        let moved = move_sentences(&transaction, source_id, target_id, iso639_3, new_iso639_3, author)?;
+
+        transaction.commit()?;
+
+        Ok(moved)
+    }
+}
+
+/// Moves all the sentences of a language to another one, recording the revisions,
+/// then deletes it. The sentences must not collide with the ones of the other language.
+///
+/// Returns:
+///
+/// the number of sentences moved, deleted ones included
+fn move_sentences(
+    connection: &GenericConnection,
+    source_id: i32,
+    target_id: i32,
+    iso639_3: &str,
+    target_iso639_3: &str,
+    author: &Author,
+) -> Result<u64, Error> {
+
+    let revisions = connection.query(
+        r#"
+            INSERT INTO sentence_revision(
+                sentence_id,
+                author,
+                old_content,
+                old_iso639_3,
+                old_structure,
+                old_script,
+                old_region,
+                new_content,
+                new_iso639_3,
+                new_structure,
+                new_script,
+                new_region
+            )
+            SELECT
+                id,
+                $2,
+                content,
+                $3,
+                structure,
+                script,
+                region,
+                content,
+                $4,
+                structure,
+                script,
+                region
+            FROM sentence
+            WHERE language_id = $1
+            RETURNING id
+        "#,
+        &[
+            &source_id,
+            &author.name,
+            &iso639_3,
+            &target_iso639_3,
+        ],
+    )?;
+
+    if let Some(user_id) = author.user_id {
+
+        let statement = connection.prepare(
+            "INSERT INTO revision_attribution(revision_id, user_id) VALUES ($1, $2)"
+        )?;
+
+        for row in revisions.iter() {
+            let revision_id: i32 = row.get(0);
+            statement.execute(&[&revision_id, &user_id])?;
+        }
+
+        connection.execute(
+            r#"
+                INSERT INTO sentence_attribution(sentence_id, updated_by)
+                SELECT id, $2
+                FROM sentence
+                WHERE language_id = $1
+                ON CONFLICT (sentence_id) DO UPDATE SET updated_by = EXCLUDED.updated_by
+            "#,
+            &[
+                &source_id,
+                &user_id,
+            ],
+        )?;
+    }
+
+    let moved = connection.execute(
+        "UPDATE sentence SET language_id = $2 WHERE language_id = $1",
+        &[
+            &source_id,
+            &target_id,
+        ],
+    )?;
+
+    connection.execute(
+        "DELETE FROM language WHERE id = $1",
+        &[&source_id],
+    )?;
+
+    Ok(moved)
+}
+
+/// Returns a language and its number of visible sentences, None if it does not exist.
+fn get_language(connection: &GenericConnection, iso639_3: &str) -> Result<Option<LanguageUsage>, Error> {
+
+    let rows = connection.query(
+        r#"
+            SELECT
+                language.iso639_3,
+                COUNT(sentence.id)
+            FROM language
+            LEFT JOIN sentence ON (
+                sentence.language_id = language.id AND
+                sentence.deleted_at IS NULL
+            )
+            WHERE language.iso639_3 = $1
+            GROUP BY language.id
+        "#,
+        &[&iso639_3],
+    )?;
+
+    Ok(rows.iter().next().map(|row| language_from_row(&row)))
+}
+
+/// Reads a language from the `iso639_3, sentences count` columns.
+fn language_from_row(row: &Row) -> LanguageUsage {
+    LanguageUsage {
+        iso639_3: row.get(0),
+        sentences: row.get(1),
+    }
+}
+
+impl ApiKeyRepository for PostgresRepository {
+
+    fn create_api_key(&self, name: &str, role: Role, key_hash: &str) -> Result<ApiKey, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        // the key is given to the user of this name, created if needed
+        transaction.execute(
+            "INSERT INTO user_account(name) VALUES ($1) ON CONFLICT (name) DO NOTHING",
+            &[&name],
+        )?;
+
+        let rows = transaction.query(
+            r#"
+                INSERT INTO api_key(user_id, role, key_hash)
+                SELECT id, $2, $3
+                FROM user_account
+                WHERE name = $1
+                RETURNING id
+            "#,
+            &[
+                &name,
+                &role.as_str(),
+                &key_hash,
+            ],
+        )?;
+
+        let id: i32 = rows.get(0).get(0);
+
+        let rows = transaction.query(
+            r#"
+                SELECT
+                    api_key.id,
+                    api_key.user_id,
+                    user_account.name,
+                    api_key.role,
+                    to_char(
+                        api_key.created_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+                FROM api_key
+                JOIN user_account ON (api_key.user_id = user_account.id)
+                WHERE api_key.id = $1
+            "#,
+            &[&id],
+        )?;
+
+        let api_key = api_key_from_row(&rows.get(0))?;
+
+        transaction.commit()?;
+
+        Ok(api_key)
+    }
+
+    fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    api_key.id,
+                    api_key.user_id,
+                    user_account.name,
+                    api_key.role,
+                    to_char(
+                        api_key.created_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+                FROM api_key
+                JOIN user_account ON (api_key.user_id = user_account.id)
+                ORDER BY api_key.id
+            "#,
+            &[],
+        )?;
+
+        rows.iter().map(|row| api_key_from_row(&row)).collect()
+    }
+
+    fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, Error> {
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    api_key.id,
+                    api_key.user_id,
+                    user_account.name,
+                    api_key.role,
+                    to_char(
+                        api_key.created_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+                FROM api_key
+                JOIN user_account ON (api_key.user_id = user_account.id)
+                WHERE api_key.key_hash = $1
+            "#,
+            &[&key_hash],
+        )?;
+
+        rows.iter().next().map(|row| api_key_from_row(&row)).transpose()
+    }
+
+    fn revoke_api_key(&self, id: i32) -> Result<bool, Error> {
+
+        let revoked = self.connection.execute(
+            "DELETE FROM api_key WHERE id = $1",
+            &[&id],
+        )?;
+
+        Ok(revoked > 0)
+    }
+}
+
+/// Reads an API key from the `id, user_id, name, role, created_at` columns.
+fn api_key_from_row(row: &Row) -> Result<ApiKey, Error> {
+    let role: String = row.get(3);
+    Ok(ApiKey {
+        id: row.get(0),
+        user_id: row.get(1),
+        name: row.get(2),
+        role: role.parse().map_err(Error::InvalidStoredValue)?,
+        created_at: row.get(4),
+        key: None,
+    })
+}
+
+impl UserRepository for PostgresRepository {
+
+    fn get_users(&self) -> Result<Vec<User>, Error> {
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    id,
+                    name,
+                    to_char(
+                        created_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+                FROM user_account
+                ORDER BY id
+            "#,
+            &[],
+        )?;
+
+        Ok(rows.iter().map(|row| user_from_row(&row)).collect())
+    }
+
+    fn get_user(&self, user_id: i32) -> Result<Option<User>, Error> {
+        get_user(&*self.connection, user_id)
+    }
+
+    fn get_contributions(&self, user_id: i32, limit: i64) -> Result<Option<Contributions>, Error> {
+
+        let user = match get_user(&*self.connection, user_id)? {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    iso639_3,
+                    SUM(sentences_created)::BIGINT,
+                    SUM(edits)::BIGINT
+                FROM (
+                    SELECT
+                        language.iso639_3,
+                        COUNT(*) AS sentences_created,
+                        0 AS edits
+                    FROM sentence_attribution
+                    JOIN sentence ON (sentence_attribution.sentence_id = sentence.id)
+                    JOIN language ON (sentence.language_id = language.id)
+                    WHERE
+                        sentence_attribution.created_by = $1 AND
+                        sentence.deleted_at IS NULL
+                    GROUP BY language.iso639_3
+                    UNION ALL
+                    SELECT
+                        sentence_revision.new_iso639_3,
+                        0,
+                        COUNT(*)
+                    FROM revision_attribution
+                    JOIN sentence_revision ON (revision_attribution.revision_id = sentence_revision.id)
+                    WHERE
+                        revision_attribution.user_id = $1 AND
+                        sentence_revision.new_iso639_3 IS NOT NULL
+                    GROUP BY sentence_revision.new_iso639_3
+                ) AS contributions
+                GROUP BY iso639_3
+                ORDER BY iso639_3
+            "#,
+            &[&user_id],
+        )?;
+
+        let languages = rows.iter()
+            .map(|row| {
+                LanguageContributions {
+                    iso639_3: row.get(0),
+                    sentences_created: row.get(1),
+                    edits: row.get(2),
+                }
+            })
+            .collect();
+
+        let rows = self.connection.query(
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        sentence_attribution.created_by = $1 AND
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        sentence.added_at DESC,
+                        sentence.id DESC
+                    LIMIT $2
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[
+                &user_id,
+                &limit,
+            ],
+        )?;
+
+        let sentences = rows.iter().map(|row| sentence_from_row(&row, 0)).collect::<Result<_, _>>()?;
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    sentence_revision.id,
+                    sentence_revision.sentence_id,
+                    to_char(
+                        sentence_revision.revised_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    ),
+                    sentence_revision.new_iso639_3
+                FROM revision_attribution
+                JOIN sentence_revision ON (revision_attribution.revision_id = sentence_revision.id)
+                WHERE revision_attribution.user_id = $1
+                ORDER BY sentence_revision.id DESC
+                LIMIT $2
+            "#,
+            &[
+                &user_id,
+                &limit,
+            ],
+        )?;
+
+        let edits = rows.iter()
+            .map(|row| {
+                ContributedEdit {
+                    revision_id: row.get(0),
+                    sentence_id: row.get(1),
+                    revised_at: row.get(2),
+                    iso639_3: row.get(3),
+                }
+            })
+            .collect();
+
+        Ok(Some(Contributions {
+            user: user,
+            languages: languages,
+            sentences: sentences,
+            edits: edits,
+        }))
+    }
+}
+
+/// Returns a user, None if there is no such user.
+fn get_user(connection: &GenericConnection, user_id: i32) -> Result<Option<User>, Error> {
+
+    let rows = connection.query(
+        r#"
+            SELECT
+                id,
+                name,
+                to_char(
+                    created_at AT TIME ZONE 'UTC',
+                    'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                )
+            FROM user_account
+            WHERE id = $1
+        "#,
+        &[&user_id],
+    )?;
+
+    Ok(rows.iter().next().map(|row| user_from_row(&row)))
+}
+
+/// Reads a user from the `id, name, created_at` columns.
+fn user_from_row(row: &Row) -> User {
+    User {
+        id: row.get(0),
+        name: row.get(1),
+        created_at: row.get(2),
+    }
+}
+
+impl ReviewRepository for PostgresRepository {
+
+    fn review_sentence(&self, sentence_uuid: &Uuid, review: &NewReview, author: &Author) -> Result<Review, Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let (snapshot, _) = match lock_snapshot(&transaction, sentence_uuid)? {
+            Some(locked) => locked,
+            None => return Err(Error::NotFound),
+        };
+
+        let statuses = get_statuses(&transaction, sentence_uuid, snapshot.structure.is_some())?;
+        let reviewed = statuses.reviewed(review)?;
+
+        if reviewed != statuses {
+            store_statuses(&transaction, sentence_uuid, &reviewed)?;
+            touch(&transaction, sentence_uuid)?;
+        }
+
+        let rows = transaction.query(
+            r#"
+                INSERT INTO sentence_review(
+                    sentence_id,
+                    reviewer_id,
+                    subject,
+                    status,
+                    comment
+                ) VALUES ($1, $2, $3, $4, $5)
+                RETURNING
+                    id,
+                    to_char(
+                        reviewed_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+            "#,
+            &[
+                &sentence_uuid,
+                &author.user_id,
+                &review.subject.as_str(),
+                &review.status.as_str(),
+                &review.comment,
+            ],
+        )?;
+
+        let row = rows.get(0);
+        let recorded = Review {
+            id: row.get(0),
+            sentence_id: *sentence_uuid,
+            subject: review.subject,
+            status: review.status,
+            comment: review.comment.clone(),
+            reviewer_id: author.user_id,
+            reviewer: author.name.clone(),
+            reviewed_at: row.get(1),
+        };
+
+        transaction.commit()?;
+
+        Ok(recorded)
+    }
+
+    fn get_reviews(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<Review>>, Error> {
+
+        let exists = self.connection.query(
+            "SELECT 1 FROM sentence WHERE id = $1",
+            &[&sentence_uuid],
+        )?;
+
+        if exists.is_empty() {
+            return Ok(None);
+        }
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    sentence_review.id,
+                    sentence_review.sentence_id,
+                    sentence_review.subject,
+                    sentence_review.status,
+                    sentence_review.comment,
+                    sentence_review.reviewer_id,
+                    user_account.name,
+                    to_char(
+                        sentence_review.reviewed_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+                FROM sentence_review
+                LEFT JOIN user_account ON (user_account.id = sentence_review.reviewer_id)
+                WHERE sentence_review.sentence_id = $1
+                ORDER BY sentence_review.id
+            "#,
+            &[&sentence_uuid],
+        )?;
+
+        Ok(Some(rows.iter().map(|row| review_from_row(&row)).collect::<Result<_, _>>()?))
+    }
+
+    fn submit_sentence(&self, sentence_uuid: &Uuid) -> Result<(), Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        let (snapshot, _) = match lock_snapshot(&transaction, sentence_uuid)? {
+            Some(locked) => locked,
+            None => return Err(Error::NotFound),
+        };
+
+        let statuses = get_statuses(&transaction, sentence_uuid, snapshot.structure.is_some())?;
+        let submitted = statuses.submitted();
+
+        if submitted != statuses {
+            store_statuses(&transaction, sentence_uuid, &submitted)?;
+            touch(&transaction, sentence_uuid)?;
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn get_review_queue(&self, iso639_3: Option<&str>, limit: i64) -> Result<Vec<Sentence>, Error> {
+
+        // the sentences without statuses are pending review, as read by sentence_from_row()
+        let rows = self.connection.query(
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        ($1::TEXT IS NULL OR language.iso639_3 = $1) AND
+                        sentence.deleted_at IS NULL AND
+                        (
+                            COALESCE(sentence_status.status, 'pending_review') = 'pending_review' OR
+                            (
+                                sentence.structure IS NOT NULL AND
+                                COALESCE(sentence_status.structure_status, 'pending_review') = 'pending_review'
+                            )
+                        )
+                    ORDER BY
+                        added_at,
+                        sentence.id
+                    LIMIT $2
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[
+                &iso639_3,
+                &limit,
+            ],
+        )?;
+
+        rows.iter().map(|row| sentence_from_row(&row, 0)).collect()
+    }
+}
+
+/// Reads a review from the `id, sentence_id, subject, status, comment,
+/// reviewer_id, reviewer name, reviewed_at` columns.
+fn review_from_row(row: &Row) -> Result<Review, Error> {
+    let subject: String = row.get(2);
+    let status: String = row.get(3);
+    Ok(Review {
+        id: row.get(0),
+        sentence_id: row.get(1),
+        subject: subject.parse().map_err(Error::InvalidStoredValue)?,
+        status: status.parse().map_err(Error::InvalidStoredValue)?,
+        comment: row.get(4),
+        reviewer_id: row.get(5),
+        reviewer: row.get(6),
+        reviewed_at: row.get(7),
+    })
+}
+
+impl MigrationStore for PostgresRepository {
+
+    fn migrations(&self) -> &'static [Migration] {
+        migrations::POSTGRES
+    }
+
+    fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+
+        self.connection.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            &[],
+        )?;
+
+        let rows = self.connection.query(
+            r#"
+                SELECT
+                    version,
+                    name,
+                    checksum,
+                    to_char(
+                        applied_at AT TIME ZONE 'UTC',
+                        'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'
+                    )
+                FROM schema_migrations
+                ORDER BY version
+            "#,
+            &[],
+        )?;
+
+        Ok(rows.iter()
+            .map(|row| {
+                AppliedMigration {
+                    version: row.get(0),
+                    name: row.get(1),
+                    checksum: row.get(2),
+                    applied_at: row.get(3),
+                }
+            })
+            .collect())
+    }
+
+    fn apply(&self, migration: &Migration) -> Result<(), Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        transaction.batch_execute(migration.up)?;
+        transaction.execute(
+            "INSERT INTO schema_migrations(version, name, checksum) VALUES ($1, $2, $3)",
+            &[
+                &migration.version,
+                &migration.name,
+                &migration.checksum(),
+            ],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn revert(&self, migration: &Migration) -> Result<(), Error> {
+
+        let transaction = self.connection.transaction()?;
+
+        transaction.batch_execute(migration.down)?;
+        transaction.execute(
+            "DELETE FROM schema_migrations WHERE version = $1",
+            &[&migration.version],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1977 @@
+//! Repository on a single SQLite file, for running the service without a
+//! database server. The schema mirrors the PostgreSQL one: uuids and dates
+//! are stored as text, and the structures as text validated before being
+//! written, as SQLite has no XML type.
+
+use rusqlite::{Connection, Row};
+use rusqlite::types::ToSql;
+
+use uuid::Uuid;
+use std::collections::BTreeMap;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+use auth::Author;
+use error::Error;
+use language_tag::LanguageTag;
+use migrations::{self, AppliedMigration, Migration, MigrationStore};
+use models::{
+    ApiKey,
+    ContributedEdit,
+    Contributions,
+    LanguageContributions,
+    NewReview,
+    Review,
+    Role,
+    Sentence,
+    Snapshot,
+    User,
+};
+use repository::{
+    ApiKeyRepository,
+    Creation,
+    LanguageDeletion,
+    LanguageMerge,
+    LanguageRepository,
+    LanguageUsage,
+    Modification,
+    ReviewRepository,
+    RevisionRecord,
+    SentenceQuery,
+    SentenceRepository,
+    Statuses,
+    UserRepository,
+};
+use structure;
+
+/// Extended result codes of the primary key and unique constraint violations.
+const UNIQUE_VIOLATIONS: [i32; 2] = [1555, 2067];
+
+/// Repository on the SQLite connection, held for the time of a request.
+pub struct SqliteRepository<'a> {
+    connection: MutexGuard<'a, Connection>,
+}
+
+impl<'a> SqliteRepository<'a> {
+
+    /// Creates a repository using the given connection.
+    ///
+    /// Args:
+    ///
+    /// `connection` - the locked database connection
+    pub fn new(connection: MutexGuard<'a, Connection>) -> SqliteRepository<'a> {
+        SqliteRepository {
+            connection: connection,
+        }
+    }
+
+    /// Runs queries in a transaction, committed if they succeed
+    /// and rolled back otherwise.
+    ///
+    /// Args:
+    ///
+    /// `queries` - the queries, given the connection
+    fn in_transaction<T, F>(&self, queries: F) -> Result<T, Error>
+        where F: FnOnce(&Connection) -> Result<T, Error>
+    {
+        // the write lock is taken at once, so the rows read
+        // cannot change before they are updated
+        self.connection.execute_batch("BEGIN IMMEDIATE")?;
+
+        let result = queries(&*self.connection);
+
+        match result {
+            Ok(_) => self.connection.execute_batch("COMMIT")?,
+            Err(_) => {
+                let _ = self.connection.execute_batch("ROLLBACK");
+            },
+        }
+
+        result
+    }
+}
+
+/// Opens the database file, creating it if needed.
+/// Its tables are created by the migrations.
+///
+/// Args:
+///
+/// `path` - the database file
+///
+/// Returns:
+///
+/// the connection, or why the file cannot be opened
+pub fn open(path: &str) -> Result<Connection, String> {
+
+    let connection = Connection::open(path)
+        .map_err(|error| format!("can't open SQLite database {}: {}", path, error))?;
+
+    // the tests, or another tool, may write to the file at the same time
+    connection.busy_timeout(Duration::from_secs(5))
+        .map_err(|error| format!("can't set SQLite busy timeout: {}", error))?;
+
+    // the foreign keys are only enforced once asked for, on each connection
+    connection.execute_batch(
+        r#"
+        PRAGMA foreign_keys = ON;
+        PRAGMA journal_mode = WAL;
+        "#,
+    )
+    .map_err(|error| format!("can't configure SQLite database: {}", error))?;
+
+    Ok(connection)
+}
+
+/// Tells whether a query failed on a primary key or unique constraint.
+fn is_unique_violation(error: &::rusqlite::Error) -> bool {
+    match *error {
+        ::rusqlite::Error::SqliteFailure(ref error, _) => {
+            UNIQUE_VIOLATIONS.contains(&error.extended_code)
+        },
+        _ => false,
+    }
+}
+
+/// Refuses a structure which is not well-formed XML, as the `XML` type of PostgreSQL does.
+fn check_structure(structure: &Option<String>) -> Result<(), Error> {
+    match *structure {
+        Some(ref structure) if !structure::is_well_formed(structure) => Err(Error::InvalidXml),
+        _ => Ok(()),
+    }
+}
+
+/// Runs a query and reads all its rows.
+///
+/// Args:
+///
+/// `connection` - database connection handler
+/// `query` - the query
+/// `params` - its parameters
+/// `read` - reads a row
+fn query_all<T, F>(
+    connection: &Connection,
+    query: &str,
+    params: &[&ToSql],
+    read: F,
+) -> Result<Vec<T>, Error>
+    where F: FnMut(&Row) -> T
+{
+    let mut statement = connection.prepare(query)?;
+    let rows = statement.query_map(params, read)?;
+
+    let mut values = Vec::new();
+    for value in rows {
+        values.push(value?);
+    }
+
+    Ok(values)
+}
+
+/// Runs a query and reads all its rows, with a reader that can fail on a stored value.
+///
+/// Args:
+///
+/// `connection` - database connection handler
+/// `query` - the query
+/// `params` - its parameters
+/// `read` - reads a row
+fn try_query_all<T, F>(
+    connection: &Connection,
+    query: &str,
+    params: &[&ToSql],
+    read: F,
+) -> Result<Vec<T>, Error>
+    where F: FnMut(&Row) -> Result<T, Error>
+{
+    query_all(connection, query, params, read)?.into_iter().collect()
+}
+
+/// Reads an uuid stored as text.
+fn uuid_from_row(row: &Row, index: i32) -> Result<Uuid, Error> {
+    let uuid: String = row.get(index);
+    Uuid::parse_str(&uuid).map_err(|_| {
+        Error::InvalidStoredValue(format!("stored sentence id {} is not an uuid", uuid))
+    })
+}
+
+/// The columns of a sentence read by `sentence_from_row`,
+/// from `sentence` and the tables of `SENTENCE_JOINS`.
+const SENTENCE_COLUMNS: &str = r#"
+    sentence.id,
+    sentence.content,
+    language.iso639_3,
+    sentence.structure,
+    sentence.script,
+    sentence.region,
+    sentence_attribution.created_by,
+    sentence_attribution.updated_by,
+    sentence_status.status,
+    sentence_status.structure_status
+"#;
+
+/// The tables joined to `sentence` for the `SENTENCE_COLUMNS`.
+const SENTENCE_JOINS: &str = r#"
+    JOIN language ON (sentence.language_id = language.id)
+    LEFT JOIN sentence_attribution ON (sentence_attribution.sentence_id = sentence.id)
+    LEFT JOIN sentence_status ON (sentence_status.sentence_id = sentence.id)
+"#;
+
+/// Reads a sentence from the `SENTENCE_COLUMNS`, starting at the given column.
+fn sentence_from_row(row: &Row, first: i32) -> Result<Sentence, Error> {
+    let structure: Option<String> = row.get(first + 3);
+    let statuses = Statuses::from_stored(
+        structure.is_some(),
+        row.get(first + 8),
+        row.get(first + 9),
+    )?;
+    Ok(Sentence {
+        id: Some(uuid_from_row(row, first)?),
+        text: row.get(first + 1),
+        iso639_3: row.get(first + 2),
+        structure: structure,
+        script: row.get(first + 4),
+        region: row.get(first + 5),
+        created_by: row.get(first + 6),
+        updated_by: row.get(first + 7),
+        review_status: statuses.sentence,
+        structure_review_status: statuses.structure,
+    })
+}
+
+/// Reads a sentence state from the `content, iso639_3, structure, script, region` columns,
+/// starting at the given one.
+fn snapshot_from_row(row: &Row, first: i32) -> Snapshot {
+    Snapshot {
+        text: row.get(first),
+        iso639_3: row.get(first + 1),
+        structure: row.get(first + 2),
+        script: row.get(first + 3),
+        region: row.get(first + 4),
+    }
+}
+
+/// Returns the first sentence selected by a query on an uuid, a content and a language variant,
+/// its columns being the ones read by `sentence_from_row`.
+fn query_sentence(
+    connection: &Connection,
+    query: &str,
+    sentence_uuid: &Uuid,
+    content: &str,
+    language: &LanguageTag,
+) -> Result<Option<Sentence>, Error> {
+
+    let sentences = try_query_all(
+        connection,
+        query,
+        &[
+            &sentence_uuid.to_string(),
+            &content,
+            &language.iso639_3,
+            &language.script,
+            &language.region,
+        ],
+        |row| sentence_from_row(row, 0),
+    )?;
+
+    Ok(sentences.into_iter().next())
+}
+
+/// Returns the sentence, not deleted, having the given uuid,
+/// or the given content in the given language variant.
+fn get_sentence_by_uuid_or_content(
+    connection: &Connection,
+    sentence_uuid: &Uuid,
+    content: &str,
+    language: &LanguageTag,
+) -> Result<Option<Sentence>, Error> {
+
+    query_sentence(
+        connection,
+        &format!(
+            r#"
+                SELECT {}
+                FROM sentence {}
+                WHERE
+                    sentence.deleted_at IS NULL AND
+                    (
+                        sentence.id = ?1 OR
+                        (
+                            sentence.content = ?2 AND
+                            language.iso639_3 = ?3 AND
+                            sentence.script IS ?4 AND
+                            sentence.region IS ?5
+                        )
+                    )
+            "#,
+            SENTENCE_COLUMNS,
+            SENTENCE_JOINS,
+        ),
+        sentence_uuid,
+        content,
+        language,
+    )
+}
+
+/// Returns the sentence, not deleted, other than the given one,
+/// having the given content in the given language variant.
+fn get_other_sentence_by_content(
+    connection: &Connection,
+    sentence_uuid: &Uuid,
+    content: &str,
+    language: &LanguageTag,
+) -> Result<Option<Sentence>, Error> {
+
+    query_sentence(
+        connection,
+        &format!(
+            r#"
+                SELECT {}
+                FROM sentence {}
+                WHERE
+                    sentence.deleted_at IS NULL AND
+                    sentence.id != ?1 AND
+                    sentence.content = ?2 AND
+                    language.iso639_3 = ?3 AND
+                    sentence.script IS ?4 AND
+                    sentence.region IS ?5
+            "#,
+            SENTENCE_COLUMNS,
+            SENTENCE_JOINS,
+        ),
+        sentence_uuid,
+        content,
+        language,
+    )
+}
+
+/// Returns the current state and version of a visible sentence.
+fn get_snapshot(
+    connection: &Connection,
+    sentence_uuid: &Uuid,
+) -> Result<Option<(Snapshot, i32)>, Error> {
+
+    let snapshots = query_all(
+        connection,
+        r#"
+            SELECT
+                sentence.content,
+                language.iso639_3,
+                sentence.structure,
+                sentence.script,
+                sentence.region,
+                sentence.version
+            FROM sentence
+            LEFT JOIN language ON (sentence.language_id = language.id)
+            WHERE
+                sentence.id = ?1 AND
+                sentence.deleted_at IS NULL
+        "#,
+        &[&sentence_uuid.to_string()],
+        |row| (snapshot_from_row(row, 0), row.get(5)),
+    )?;
+
+    Ok(snapshots.into_iter().next())
+}
+
+/// Records a revision of a sentence, from its previous state
+/// to the state currently stored in the database.
+///
+/// Args:
+///
+/// `connection` - the connection, in the transaction modifying the sentence
+/// `sentence_uuid` - the sentence uuid
+/// `author` - the author of the change
+/// `previous` - the sentence state before the change
+fn record(
+    connection: &Connection,
+    sentence_uuid: &Uuid,
+    author: &Author,
+    previous: &Snapshot,
+) -> Result<(), Error> {
+
+    connection.execute(
+        r#"
+            INSERT INTO sentence_revision(
+                sentence_id,
+                author,
+                old_content,
+                old_iso639_3,
+                old_structure,
+                old_script,
+                old_region,
+                new_content,
+                new_iso639_3,
+                new_structure,
+                new_script,
+                new_region
+            )
+            SELECT
+                sentence.id,
+                ?2,
+                ?3,
+                ?4,
+                ?5,
+                ?6,
+                ?7,
+                sentence.content,
+                language.iso639_3,
+                sentence.structure,
+                sentence.script,
+                sentence.region
+            FROM sentence
+            LEFT JOIN language ON (sentence.language_id = language.id)
+            WHERE sentence.id = ?1
+        "#,
+        &[
+            &sentence_uuid.to_string(),
+            &author.name,
+            &previous.text,
+            &previous.iso639_3,
+            &previous.structure,
+            &previous.script,
+            &previous.region,
+        ],
+    )?;
+
+    let user_id = match author.user_id {
+        Some(user_id) => user_id,
+        None => return Ok(()),
+    };
+
+    connection.execute(
+        "INSERT INTO revision_attribution(revision_id, user_id) VALUES (?1, ?2)",
+        &[
+            &connection.last_insert_rowid(),
+            &user_id,
+        ],
+    )?;
+
+    attribute_update(connection, sentence_uuid, user_id)
+}
+
+/// Records a user as the last one having changed a sentence.
+///
+/// Args:
+///
+/// `connection` - the connection, in the transaction changing the sentence
+/// `sentence_uuid` - the sentence uuid
+/// `user_id` - the user id
+fn attribute_update(connection: &Connection, sentence_uuid: &Uuid, user_id: i32) -> Result<(), Error> {
+
+    // no upsert before SQLite 3.24
+    connection.execute(
+        "INSERT OR IGNORE INTO sentence_attribution(sentence_id) VALUES (?1)",
+        &[&sentence_uuid.to_string()],
+    )?;
+
+    connection.execute(
+        "UPDATE sentence_attribution SET updated_by = ?2 WHERE sentence_id = ?1",
+        &[
+            &sentence_uuid.to_string(),
+            &user_id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Returns the review statuses of a sentence.
+///
+/// Args:
+///
+/// `connection` - the connection, in the transaction changing the sentence
+/// `sentence_uuid` - the sentence uuid
+/// `has_structure` - true if the sentence has a structure
+fn get_statuses(connection: &Connection, sentence_uuid: &Uuid, has_structure: bool) -> Result<Statuses, Error> {
+
+    let stored: Vec<(Option<String>, Option<String>)> = query_all(
+        connection,
+        "SELECT status, structure_status FROM sentence_status WHERE sentence_id = ?1",
+        &[&sentence_uuid.to_string()],
+        |row| (row.get(0), row.get(1)),
+    )?;
+
+    match stored.into_iter().next() {
+        Some((status, structure_status)) => Statuses::from_stored(has_structure, status, structure_status),
+        None => Statuses::from_stored(has_structure, None, None),
+    }
+}
+
+/// Stores the review statuses of a sentence.
+///
+/// Args:
+///
+/// `connection` - the connection, in the transaction changing the sentence
+/// `sentence_uuid` - the sentence uuid
+/// `statuses` - the new statuses
+fn store_statuses(connection: &Connection, sentence_uuid: &Uuid, statuses: &Statuses) -> Result<(), Error> {
+
+    // nothing references the statuses, they can be replaced as a whole
+    connection.execute(
+        r#"
+            INSERT OR REPLACE INTO sentence_status(sentence_id, status, structure_status)
+            VALUES (?1, ?2, ?3)
+        "#,
+        &[
+            &sentence_uuid.to_string(),
+            &statuses.sentence.as_str(),
+            &statuses.structure.map(|status| status.as_str()),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Gives a sentence a new version, and so a new entity tag, when its statuses change,
+/// as they are sent along with it.
+///
+/// Args:
+///
+/// `connection` - the connection, in the transaction changing the statuses
+/// `sentence_uuid` - the sentence uuid
+fn touch(connection: &Connection, sentence_uuid: &Uuid) -> Result<(), Error> {
+
+    connection.execute(
+        "UPDATE sentence SET version = version + 1 WHERE id = ?1",
+        &[&sentence_uuid.to_string()],
+    )?;
+
+    Ok(())
+}
+
+impl<'a> SentenceRepository for SqliteRepository<'a> {
+
+    fn get_sentence(&self, sentence_uuid: &Uuid) -> Result<Option<(Sentence, i32)>, Error> {
+
+        let sentences = try_query_all(
+            &self.connection,
+            &format!(
+                r#"
+                    SELECT {}, sentence.version
+                    FROM sentence {}
+                    WHERE
+                        sentence.id = ?1 AND
+                        sentence.deleted_at IS NULL
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[&sentence_uuid.to_string()],
+            |row| Ok((sentence_from_row(row, 0)?, row.get(10))),
+        )?;
+
+        Ok(sentences.into_iter().next())
+    }
+
+    fn get_sentences(&self, query: &SentenceQuery) -> Result<Vec<Sentence>, Error> {
+
+        // the uuids are stored in their lowercase hyphenated form,
+        // so they compare as text the same way as uuids
+        try_query_all(
+            &self.connection,
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        (?1 IS NULL OR sentence.id >= ?1) AND
+                        (?2 IS NULL OR language.iso639_3 = ?2) AND
+                        (?3 IS NULL OR sentence.script = ?3) AND
+                        (?4 IS NULL OR sentence.region = ?4) AND
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        sentence.added_at,
+                        sentence.rowid
+                    LIMIT ?5
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[
+                &query.starting_after_id.map(|uuid| uuid.to_string()),
+                &query.iso639_3,
+                &query.script,
+                &query.region,
+                &query.limit,
+            ],
+            |row| sentence_from_row(row, 0),
+        )
+    }
+
+    fn get_all_sentences(&self, include_deleted: bool) -> Result<Vec<Sentence>, Error> {
+
+        try_query_all(
+            &self.connection,
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        ?1 OR
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        sentence.added_at,
+                        sentence.rowid
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[&include_deleted],
+            |row| sentence_from_row(row, 0),
+        )
+    }
+
+    fn create_sentence(&self, sentence: &Sentence, author: &Author) -> Result<Creation, Error> {
+
+        check_structure(&sentence.structure)?;
+
+        let statuses = Statuses::of_new(sentence);
+        let uuid = sentence.id.unwrap_or_else(Uuid::new_v4);
+        let given_uuid = sentence.id.map(|uuid| uuid.to_string());
+
+        self.in_transaction(|connection| {
+
+            /* a deleted sentence with the same content is brought back
+               instead of being created again, if the given id is its own
+               and no visible sentence has taken its content since */
+            let deleted = try_query_all(
+                connection,
+                r#"
+                    SELECT id
+                    FROM sentence
+                    WHERE
+                        content = ?2 AND
+                        language_id = (SELECT id FROM language WHERE iso639_3 = ?3) AND
+                        script IS ?4 AND
+                        region IS ?5 AND
+                        deleted_at IS NOT NULL AND
+                        (?1 IS NULL OR id = ?1) AND
+                        NOT EXISTS (
+                            SELECT 1
+                            FROM sentence AS visible
+                            WHERE
+                                visible.content = sentence.content AND
+                                visible.language_id = sentence.language_id AND
+                                visible.script IS sentence.script AND
+                                visible.region IS sentence.region AND
+                                visible.deleted_at IS NULL
+                        )
+                    ORDER BY deleted_at DESC
+                    LIMIT 1
+                "#,
+                &[
+                    &given_uuid,
+                    &sentence.text,
+                    &sentence.iso639_3,
+                    &sentence.script,
+                    &sentence.region,
+                ],
+                |row| uuid_from_row(row, 0),
+            )?;
+
+            if let Some(restored) = deleted.into_iter().next() {
+
+                connection.execute(
+                    r#"
+                        UPDATE sentence
+                        SET
+                            deleted_at = NULL,
+                            structure = ?2
+                        WHERE id = ?1
+                    "#,
+                    &[
+                        &restored.to_string(),
+                        &sentence.structure,
+                    ],
+                )?;
+
+                if let Some(user_id) = author.user_id {
+                    attribute_update(connection, &restored, user_id)?;
+                }
+
+                store_statuses(connection, &restored, &statuses)?;
+
+                return Ok(Creation::Restored(restored));
+            }
+
+            // as with PostgreSQL, an unknown language
+            // is reported as a foreign key violation
+            let result = connection.execute(
+                r#"
+                INSERT INTO sentence(
+                    id,
+                    content,
+                    language_id,
+                    structure,
+                    script,
+                    region
+                ) VALUES (
+                    ?1,
+                    ?2,
+                    COALESCE((SELECT id FROM language WHERE iso639_3 = ?3), 0),
+                    ?4,
+                    ?5,
+                    ?6
+                )
+                "#,
+                &[
+                    &uuid.to_string(),
+                    &sentence.text,
+                    &sentence.iso639_3,
+                    &sentence.structure,
+                    &sentence.script,
+                    &sentence.region,
+                ],
+            );
+
+            let error = match result {
+                Ok(_) => {
+                    connection.execute(
+                        r#"
+                            INSERT INTO sentence_attribution(sentence_id, created_by, updated_by)
+                            VALUES (?1, ?2, ?2)
+                        "#,
+                        &[
+                            &uuid.to_string(),
+                            &author.user_id,
+                        ],
+                    )?;
+                    store_statuses(connection, &uuid, &statuses)?;
+                    return Ok(Creation::Created(uuid));
+                },
+                Err(error) => error,
+            };
+
+            if !is_unique_violation(&error) {
+                return Err(Error::from(error));
+            }
+
+            let language = LanguageTag {
+                iso639_3: sentence.iso639_3.clone(),
+                script: sentence.script.clone(),
+                region: sentence.region.clone(),
+            };
+
+            // the uuid may be the one of a deleted sentence, which is not returned
+            match get_sentence_by_uuid_or_content(connection, &uuid, &sentence.text, &language)? {
+                Some(conflicting) => Ok(Creation::Conflict(conflicting)),
+                None => Err(Error::from(error)),
+            }
+        })
+    }
+
+    fn modify_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        author: &Author,
+        change: &mut FnMut(&Snapshot, i32) -> Result<Snapshot, Error>,
+    ) -> Result<Modification, Error> {
+
+        self.in_transaction(|connection| {
+
+            let (previous, version) = match get_snapshot(connection, sentence_uuid)? {
+                Some(current) => current,
+                None => return Err(Error::NotFound),
+            };
+
+            let next = change(&previous, version)?;
+
+            if next == previous {
+                return Ok(Modification::Unchanged(version));
+            }
+
+            check_structure(&next.structure)?;
+
+            let result = connection.execute(
+                r#"
+                    UPDATE sentence
+                    SET
+                        content = ?1,
+                        -- same trick as on creation: force a relation error
+                        -- if the language does not exist
+                        language_id = COALESCE((SELECT id FROM language WHERE iso639_3 = ?2), 0),
+                        structure = ?3,
+                        script = ?4,
+                        region = ?5
+                    WHERE id = ?6
+                "#,
+                &[
+                    &next.text,
+                    &next.iso639_3,
+                    &next.structure,
+                    &next.script,
+                    &next.region,
+                    &sentence_uuid.to_string(),
+                ],
+            );
+
+            if let Err(error) = result {
+
+                if is_unique_violation(&error) {
+
+                    let conflicting = get_other_sentence_by_content(
+                        connection,
+                        sentence_uuid,
+                        &next.text,
+                        &next.language(),
+                    )?;
+
+                    if let Some(sentence) = conflicting {
+                        return Ok(Modification::Conflict(sentence));
+                    }
+                }
+
+                return Err(Error::from(error));
+            }
+
+            let statuses = get_statuses(connection, sentence_uuid, previous.structure.is_some())?;
+            store_statuses(connection, sentence_uuid, &statuses.changed(&previous, &next))?;
+
+            record(connection, sentence_uuid, author, &previous)?;
+
+            let version: i32 = connection.query_row(
+                "SELECT version FROM sentence WHERE id = ?1",
+                &[&sentence_uuid.to_string()],
+                |row| row.get(0),
+            )?;
+
+            Ok(Modification::Updated(version))
+        })
+    }
+
+    fn delete_sentence(&self, sentence_uuid: &Uuid, purge: bool) -> Result<Option<i64>, Error> {
+
+        let id = sentence_uuid.to_string();
+
+        if !purge {
+
+            let deleted = self.connection.execute(
+                r#"
+                    UPDATE sentence
+                    SET deleted_at = STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now')
+                    WHERE
+                        id = ?1 AND
+                        deleted_at IS NULL
+                "#,
+                &[&id],
+            )?;
+
+            return Ok(if deleted > 0 { Some(0) } else { None });
+        }
+
+        self.in_transaction(|connection| {
+
+            let revisions: i64 = connection.query_row(
+                "SELECT COUNT(*) FROM sentence_revision WHERE sentence_id = ?1",
+                &[&id],
+                |row| row.get(0),
+            )?;
+
+            // the revisions are removed by the ON DELETE CASCADE of their foreign key
+            let purged = connection.execute(
+                "DELETE FROM sentence WHERE id = ?1",
+                &[&id],
+            )?;
+
+            Ok(if purged > 0 { Some(revisions) } else { None })
+        })
+    }
+
+    fn restore_sentence(&self, sentence_uuid: &Uuid) -> Result<bool, Error> {
+
+        let restored = self.connection.execute(
+            r#"
+                UPDATE sentence
+                SET deleted_at = NULL
+                WHERE
+                    id = ?1 AND
+                    deleted_at IS NOT NULL
+            "#,
+            &[&sentence_uuid.to_string()],
+        )?;
+
+        Ok(restored > 0)
+    }
+
+    fn merge_sentence(
+        &self,
+        sentence_uuid: &Uuid,
+        target_uuid: &Uuid,
+        author: &Author,
+        merge: &mut FnMut(&Snapshot, &Snapshot) -> Result<Option<String>, Error>,
+    ) -> Result<bool, Error> {
+
+        self.in_transaction(|connection| {
+
+            let merged = get_snapshot(connection, sentence_uuid)?;
+            let target = get_snapshot(connection, target_uuid)?;
+
+            let (merged, target) = match (merged, target) {
+                (Some((merged, _)), Some((target, _))) => (merged, target),
+                _ => return Err(Error::NotFound),
+            };
+
+            let moved_structure = merge(&merged, &target)?;
+
+            if moved_structure.is_some() {
+
+                check_structure(&moved_structure)?;
+
+                connection.execute(
+                    r#"
+                        UPDATE sentence
+                        SET structure = ?1
+                        WHERE id = ?2
+                    "#,
+                    &[
+                        &moved_structure,
+                        &target_uuid.to_string(),
+                    ],
+                )?;
+
+                let next = Snapshot {
+                    structure: moved_structure.clone(),
+                    ..target.clone()
+                };
+
+                let statuses = get_statuses(connection, target_uuid, target.structure.is_some())?;
+                store_statuses(connection, target_uuid, &statuses.changed(&target, &next))?;
+
+                record(connection, target_uuid, author, &target)?;
+            }
+
+            connection.execute(
+                r#"
+                    UPDATE sentence
+                    SET deleted_at = STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now')
+                    WHERE id = ?1
+                "#,
+                &[&sentence_uuid.to_string()],
+            )?;
+
+            Ok(moved_structure.is_some())
+        })
+    }
+
+    fn get_revisions(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<RevisionRecord>>, Error> {
+
+        let id = sentence_uuid.to_string();
+
+        let exists = query_all(
+            &self.connection,
+            "SELECT 1 FROM sentence WHERE id = ?1",
+            &[&id],
+            |_| (),
+        )?;
+
+        if exists.is_empty() {
+            return Ok(None);
+        }
+
+        let revisions = query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    id,
+                    revised_at,
+                    author,
+                    old_content,
+                    old_iso639_3,
+                    old_structure,
+                    old_script,
+                    old_region,
+                    new_content,
+                    new_iso639_3,
+                    new_structure,
+                    new_script,
+                    new_region
+                FROM sentence_revision
+                WHERE sentence_id = ?1
+                ORDER BY id
+            "#,
+            &[&id],
+            |row| RevisionRecord {
+                id: row.get(0),
+                revised_at: row.get(1),
+                author: row.get(2),
+                old: snapshot_from_row(row, 3),
+                new: snapshot_from_row(row, 8),
+            },
+        )?;
+
+        Ok(Some(revisions))
+    }
+
+    fn get_revision(&self, sentence_uuid: &Uuid, revision_id: i32) -> Result<Option<Snapshot>, Error> {
+
+        let snapshots = query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    old_content,
+                    old_iso639_3,
+                    old_structure,
+                    old_script,
+                    old_region
+                FROM sentence_revision
+                WHERE id = ?1 AND sentence_id = ?2
+            "#,
+            &[
+                &revision_id,
+                &sentence_uuid.to_string(),
+            ],
+            |row| snapshot_from_row(row, 0),
+        )?;
+
+        Ok(snapshots.into_iter().next())
+    }
+
+    fn get_training_corpus(
+        &self,
+        max_sentences: i64,
+        min_sentences: i64,
+    ) -> Result<Vec<(String, String)>, Error> {
+
+        // counted here rather than with window functions,
+        // which older SQLite versions lack
+        let sentences = query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    language.iso639_3,
+                    sentence.content
+                FROM sentence
+                JOIN language ON (sentence.language_id = language.id)
+                WHERE sentence.deleted_at IS NULL
+                ORDER BY
+                    sentence.added_at DESC,
+                    sentence.rowid DESC
+            "#,
+            &[],
+            |row| (row.get(0), row.get(1)),
+        )?;
+
+        let mut languages: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (iso639_3, content) in sentences {
+            languages.entry(iso639_3).or_insert_with(Vec::new).push(content);
+        }
+
+        Ok(languages.into_iter()
+            .filter(|&(_, ref contents)| contents.len() as i64 >= min_sentences)
+            .flat_map(|(iso639_3, contents)| {
+                contents.into_iter()
+                    .take(max_sentences as usize)
+                    .map(move |content| (iso639_3.clone(), content))
+            })
+            .collect())
+    }
+
+    fn replace_near_duplicates(&self, groups: &[Vec<Uuid>]) -> Result<(), Error> {
+
+        self.in_transaction(|connection| {
+
+            connection.execute("DELETE FROM near_duplicate", &[])?;
+
+            let mut statement = connection.prepare(
+                "INSERT INTO near_duplicate(sentence_id, group_id) VALUES (?1, ?2)"
+            )?;
+
+            for (group_id, group) in groups.iter().enumerate() {
+                for sentence_uuid in group.iter() {
+                    statement.execute(&[
+                        &sentence_uuid.to_string(),
+                        &(group_id as i32 + 1),
+                    ])?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn get_near_duplicates(&self, iso639_3: &str) -> Result<Vec<(i32, Sentence)>, Error> {
+
+        try_query_all(
+            &self.connection,
+            &format!(
+                r#"
+                    SELECT {}, near_duplicate.group_id
+                    FROM sentence {}
+                    JOIN near_duplicate ON (near_duplicate.sentence_id = sentence.id)
+                    WHERE
+                        language.iso639_3 = ?1 AND
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        near_duplicate.group_id,
+                        sentence.added_at,
+                        sentence.rowid
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[&iso639_3],
+            |row| Ok((row.get(10), sentence_from_row(row, 0)?)),
+        )
+    }
+}
+
+impl<'a> LanguageRepository for SqliteRepository<'a> {
+
+    fn create_language(&self, iso639_3: &str) -> Result<(), Error> {
+
+        // an existing language is reported as a unique violation
+        self.connection.execute(
+            r#"
+            INSERT INTO language(iso639_3)
+            VALUES (?1)
+            "#,
+            &[&iso639_3],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_languages(&self) -> Result<Vec<LanguageUsage>, Error> {
+
+        query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    language.iso639_3,
+                    COUNT(sentence.id)
+                FROM language
+                LEFT JOIN sentence ON (
+                    sentence.language_id = language.id AND
+                    sentence.deleted_at IS NULL
+                )
+                GROUP BY language.id
+                ORDER BY language.iso639_3
+            "#,
+            &[],
+            |row| language_from_row(row),
+        )
+    }
+
+    fn get_language(&self, iso639_3: &str) -> Result<Option<LanguageUsage>, Error> {
+        get_language(&self.connection, iso639_3)
+    }
+
+    fn delete_language(&self, iso639_3: &str, cascade: bool) -> Result<LanguageDeletion, Error> {
+
+        self.in_transaction(|connection| {
+
+            let languages = query_all(
+                connection,
+                r#"
+                    SELECT
+                        id,
+                        (SELECT COUNT(*) FROM sentence WHERE language_id = language.id)
+                    FROM language
+                    WHERE iso639_3 = ?1
+                "#,
+                &[&iso639_3],
+                |row| (row.get(0), row.get(1)),
+            )?;
+
+            let (language_id, sentences): (i64, i64) = match languages.into_iter().next() {
+                Some(language) => language,
+                None => return Err(Error::NotFound),
+            };
+
+            if sentences > 0 && !cascade {
+                return match get_language(connection, iso639_3)? {
+                    Some(usage) => Ok(LanguageDeletion::InUse(usage)),
+                    None => Err(Error::NotFound),
+                };
+            }
+
+            let sentences_removed = connection.execute(
+                "DELETE FROM sentence WHERE language_id = ?1",
+                &[&language_id],
+            )?;
+
+            connection.execute(
+                "DELETE FROM language WHERE id = ?1",
+                &[&language_id],
+            )?;
+
+            Ok(LanguageDeletion::Deleted(sentences_removed as u64))
+        })
+    }
+
+    fn merge_language(
+        &self,
+        iso639_3: &str,
+        target_iso639_3: &str,
+        author: &Author,
+    ) -> Result<LanguageMerge, Error> {
+
+        self.in_transaction(|connection| {
+
+            let ids = query_all(
+                connection,
+                r#"
+                    SELECT
+                        (SELECT id FROM language WHERE iso639_3 = ?1),
+                        (SELECT id FROM language WHERE iso639_3 = ?2)
+                "#,
+                &[
+                    &iso639_3,
+                    &target_iso639_3,
+                ],
+                |row| (row.get(0), row.get(1)),
+            )?;
+
+            let (source_id, target_id): (i64, i64) = match ids.into_iter().next() {
+                Some((Some(source_id), Some(target_id))) => (source_id, target_id),
+                _ => return Err(Error::NotFound),
+            };
+
+            // the deleted sentences hold no content, they are moved as they are
+            let duplicates = try_query_all(
+                connection,
+                r#"
+                    SELECT
+                        source.id,
+                        target.id
+                    FROM sentence AS source
+                    JOIN sentence AS target ON (
+                        target.language_id = ?2 AND
+                        source.content = target.content AND
+                        source.script IS target.script AND
+                        source.region IS target.region AND
+                        target.deleted_at IS NULL
+                    )
+                    WHERE
+                        source.language_id = ?1 AND
+                        source.deleted_at IS NULL
+                "#,
+                &[
+                    &source_id,
+                    &target_id,
+                ],
+                |row| Ok((uuid_from_row(row, 0)?, uuid_from_row(row, 1)?)),
+            )?;
+
+            for &(ref removed, _) in duplicates.iter() {
+                connection.execute(
+                    "DELETE FROM sentence WHERE id = ?1",
+                    &[&removed.to_string()],
+                )?;
+            }
+
+            // the sentences are to review again in their new language, drafts aside
+            connection.execute(
+                r#"
+                    UPDATE sentence_status
+                    SET status = 'pending_review'
+                    WHERE
+                        status <> 'draft' AND
+                        sentence_id IN (SELECT id FROM sentence WHERE language_id = ?1)
+                "#,
+                &[&source_id],
+            )?;
+
+            let moved = move_sentences(connection, source_id, target_id, iso639_3, target_iso639_3, author)?;
+
+            Ok(LanguageMerge {
+                moved: moved,
+                duplicates: duplicates,
+            })
+        })
+    }
+
+    fn rename_language(&self, iso639_3: &str, new_iso639_3: &str, author: &Author) -> Result<u64, Error> {
+
+        self.in_transaction(|connection| {
+
+            let ids: Vec<i64> = query_all(
+                connection,
+                "SELECT id FROM language WHERE iso639_3 = ?1",
+                &[&iso639_3],
+                |row| row.get(0),
+            )?;
+
+            let source_id = match ids.into_iter().next() {
+                Some(source_id) => source_id,
+                None => return Err(Error::NotFound),
+            };
+
+            // an existing language is reported as a unique violation
+            connection.execute(
+                "INSERT INTO language(iso639_3) VALUES (?1)",
+                &[&new_iso639_3],
+            )?;
+            let target_id = connection.last_insert_rowid();
+
+            move_sentences(connection, source_id, target_id, iso639_3, new_iso639_3, author)
+        })
+    }
+}
+
+/// Moves all the sentences of a language to another one, recording the revisions,
+/// then deletes it. The sentences must not collide with the ones of the other language.
+///
+/// Returns:
+///
+/// the number of sentences moved, deleted ones included
+fn move_sentences(
+    connection: &Connection,
+    source_id: i64,
+    target_id: i64,
+    iso639_3: &str,
+    target_iso639_3: &str,
+    author: &Author,
+) -> Result<u64, Error> {
+
+    let last_revision_id: i64 = connection.query_row(
+        "SELECT COALESCE(MAX(id), 0) FROM sentence_revision",
+        &[],
+        |row| row.get(0),
+    )?;
+
+    connection.execute(
+        r#"
+            INSERT INTO sentence_revision(
+                sentence_id,
+                author,
+                old_content,
+                old_iso639_3,
+                old_structure,
+                old_script,
+                old_region,
+                new_content,
+                new_iso639_3,
+                new_structure,
+                new_script,
+                new_region
+            )
+            SELECT
+                id,
+                ?2,
+                content,
+                ?3,
+                structure,
+                script,
+                region,
+                content,
+                ?4,
+                structure,
+                script,
+                region
+            FROM sentence
+            WHERE language_id = ?1
+        "#,
+        &[
+            &source_id,
+            &author.name,
+            &iso639_3,
+            &target_iso639_3,
+        ],
+    )?;
+
+    if let Some(user_id) = author.user_id {
+
+        // the revisions just recorded are the ones after the last one
+        connection.execute(
+            r#"
+                INSERT INTO revision_attribution(revision_id, user_id)
+                SELECT id, ?2
+                FROM sentence_revision
+                WHERE id > ?1
+            "#,
+            &[
+                &last_revision_id,
+                &user_id,
+            ],
+        )?;
+
+        connection.execute(
+            r#"
+                INSERT OR IGNORE INTO sentence_attribution(sentence_id)
+                SELECT id
+                FROM sentence
+                WHERE language_id = ?1
+            "#,
+            &[&source_id],
+        )?;
+
+        connection.execute(
+            r#"
+                UPDATE sentence_attribution
+                SET updated_by = ?2
+                WHERE sentence_id IN (SELECT id FROM sentence WHERE language_id = ?1)
+            "#,
+            &[
+                &source_id,
+                &user_id,
+            ],
+        )?;
+    }
+
+    let moved = connection.execute(
+        "UPDATE sentence SET language_id = ?2 WHERE language_id = ?1",
+        &[
+            &source_id,
+            &target_id,
+        ],
+    )?;
+
+    connection.execute(
+        "DELETE FROM language WHERE id = ?1",
+        &[&source_id],
+    )?;
+
+    Ok(moved as u64)
+}
+
+/// Returns a language and its number of visible sentences, None if it does not exist.
+fn get_language(connection: &Connection, iso639_3: &str) -> Result<Option<LanguageUsage>, Error> {
+
+    let languages = query_all(
+        connection,
+        r#"
+            SELECT
+                language.iso639_3,
+                COUNT(sentence.id)
+            FROM language
+            LEFT JOIN sentence ON (
+                sentence.language_id = language.id AND
+                sentence.deleted_at IS NULL
+            )
+            WHERE language.iso639_3 = ?1
+            GROUP BY language.id
+        "#,
+        &[&iso639_3],
+        |row| language_from_row(row),
+    )?;
+
+    Ok(languages.into_iter().next())
+}
+
+/// Reads a language from the `iso639_3, sentences count` columns.
+fn language_from_row(row: &Row) -> LanguageUsage {
+    LanguageUsage {
+        iso639_3: row.get(0),
+        sentences: row.get(1),
+    }
+}
+
+impl<'a> ApiKeyRepository for SqliteRepository<'a> {
+
+    fn create_api_key(&self, name: &str, role: Role, key_hash: &str) -> Result<ApiKey, Error> {
+
+        self.in_transaction(|connection| {
+
+            // the key is given to the user of this name, created if needed
+            connection.execute(
+                "INSERT OR IGNORE INTO user_account(name) VALUES (?1)",
+                &[&name],
+            )?;
+
+            connection.execute(
+                r#"
+                INSERT INTO api_key(user_id, role, key_hash)
+                SELECT id, ?2, ?3
+                FROM user_account
+                WHERE name = ?1
+                "#,
+                &[
+                    &name,
+                    &role.as_str(),
+                    &key_hash,
+                ],
+            )?;
+
+            let api_keys = try_query_all(
+                connection,
+                r#"
+                    SELECT
+                        api_key.id,
+                        api_key.user_id,
+                        user_account.name,
+                        api_key.role,
+                        api_key.created_at
+                    FROM api_key
+                    JOIN user_account ON (api_key.user_id = user_account.id)
+                    WHERE api_key.id = ?1
+                "#,
+                &[&connection.last_insert_rowid()],
+                |row| api_key_from_row(row),
+            )?;
+
+            api_keys.into_iter().next().ok_or(Error::NotFound)
+        })
+    }
+
+    fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+
+        try_query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    api_key.id,
+                    api_key.user_id,
+                    user_account.name,
+                    api_key.role,
+                    api_key.created_at
+                FROM api_key
+                JOIN user_account ON (api_key.user_id = user_account.id)
+                ORDER BY api_key.id
+            "#,
+            &[],
+            |row| api_key_from_row(row),
+        )
+    }
+
+    fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, Error> {
+
+        let api_keys = try_query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    api_key.id,
+                    api_key.user_id,
+                    user_account.name,
+                    api_key.role,
+                    api_key.created_at
+                FROM api_key
+                JOIN user_account ON (api_key.user_id = user_account.id)
+                WHERE api_key.key_hash = ?1
+            "#,
+            &[&key_hash],
+            |row| api_key_from_row(row),
+        )?;
+
+        Ok(api_keys.into_iter().next())
+    }
+
+    fn revoke_api_key(&self, id: i32) -> Result<bool, Error> {
+
+        let revoked = self.connection.execute(
+            "DELETE FROM api_key WHERE id = ?1",
+            &[&id],
+        )?;
+
+        Ok(revoked > 0)
+    }
+}
+
+/// Reads an API key from the `id, user_id, name, role, created_at` columns.
+fn api_key_from_row(row: &Row) -> Result<ApiKey, Error> {
+    let role: String = row.get(3);
+    Ok(ApiKey {
+        id: row.get(0),
+        user_id: row.get(1),
+        name: row.get(2),
+        role: role.parse().map_err(Error::InvalidStoredValue)?,
+        created_at: row.get(4),
+        key: None,
+    })
+}
+
+impl<'a> UserRepository for SqliteRepository<'a> {
+
+    fn get_users(&self) -> Result<Vec<User>, Error> {
+
+        query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    id,
+                    name,
+                    created_at
+                FROM user_account
+                ORDER BY id
+            "#,
+            &[],
+            |row| user_from_row(row),
+        )
+    }
+
+    fn get_user(&self, user_id: i32) -> Result<Option<User>, Error> {
+        get_user(&self.connection, user_id)
+    }
+
+    fn get_contributions(&self, user_id: i32, limit: i64) -> Result<Option<Contributions>, Error> {
+
+        let user = match get_user(&self.connection, user_id)? {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        let languages = query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    iso639_3,
+                    SUM(sentences_created),
+                    SUM(edits)
+                FROM (
+                    SELECT
+                        language.iso639_3 AS iso639_3,
+                        COUNT(*) AS sentences_created,
+                        0 AS edits
+                    FROM sentence_attribution
+                    JOIN sentence ON (sentence_attribution.sentence_id = sentence.id)
+                    JOIN language ON (sentence.language_id = language.id)
+                    WHERE
+                        sentence_attribution.created_by = ?1 AND
+                        sentence.deleted_at IS NULL
+                    GROUP BY language.iso639_3
+                    UNION ALL
+                    SELECT
+                        sentence_revision.new_iso639_3,
+                        0,
+                        COUNT(*)
+                    FROM revision_attribution
+                    JOIN sentence_revision ON (revision_attribution.revision_id = sentence_revision.id)
+                    WHERE
+                        revision_attribution.user_id = ?1 AND
+                        sentence_revision.new_iso639_3 IS NOT NULL
+                    GROUP BY sentence_revision.new_iso639_3
+                )
+                GROUP BY iso639_3
+                ORDER BY iso639_3
+            "#,
+            &[&user_id],
+            |row| {
+                LanguageContributions {
+                    iso639_3: row.get(0),
+                    sentences_created: row.get(1),
+                    edits: row.get(2),
+                }
+            },
+        )?;
+
+        let sentences = try_query_all(
+            &self.connection,
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        sentence_attribution.created_by = ?1 AND
+                        sentence.deleted_at IS NULL
+                    ORDER BY
+                        sentence.added_at DESC,
+                        sentence.rowid DESC
+                    LIMIT ?2
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[
+                &user_id,
+                &limit,
+            ],
+            |row| sentence_from_row(row, 0),
+        )?;
+
+        let edits = try_query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    sentence_revision.id,
+                    sentence_revision.sentence_id,
+                    sentence_revision.revised_at,
+                    sentence_revision.new_iso639_3
+                FROM revision_attribution
+                JOIN sentence_revision ON (revision_attribution.revision_id = sentence_revision.id)
+                WHERE revision_attribution.user_id = ?1
+                ORDER BY sentence_revision.id DESC
+                LIMIT ?2
+            "#,
+            &[
+                &user_id,
+                &limit,
+            ],
+            |row| {
+                Ok(ContributedEdit {
+                    revision_id: row.get(0),
+                    sentence_id: uuid_from_row(row, 1)?,
+                    revised_at: row.get(2),
+                    iso639_3: row.get(3),
+                })
+            },
+        )?;
+
+        Ok(Some(Contributions {
+            user: user,
+            languages: languages,
+            sentences: sentences,
+            edits: edits,
+        }))
+    }
+}
+
+/// Returns a user, None if there is no such user.
+fn get_user(connection: &Connection, user_id: i32) -> Result<Option<User>, Error> {
+
+    let users = query_all(
+        connection,
+        r#"
+            SELECT
+                id,
+                name,
+                created_at
+            FROM user_account
+            WHERE id = ?1
+        "#,
+        &[&user_id],
+        |row| user_from_row(row),
+    )?;
+
+    Ok(users.into_iter().next())
+}
+
+/// Reads a user from the `id, name, created_at` columns.
+fn user_from_row(row: &Row) -> User {
+    User {
+        id: row.get(0),
+        name: row.get(1),
+        created_at: row.get(2),
+    }
+}
+
+impl<'a> ReviewRepository for SqliteRepository<'a> {
+
+    fn review_sentence(&self, sentence_uuid: &Uuid, review: &NewReview, author: &Author) -> Result<Review, Error> {
+
+        self.in_transaction(|connection| {
+
+            let (snapshot, _) = match get_snapshot(connection, sentence_uuid)? {
+                Some(current) => current,
+                None => return Err(Error::NotFound),
+            };
+
+            let statuses = get_statuses(connection, sentence_uuid, snapshot.structure.is_some())?;
+            let reviewed = statuses.reviewed(review)?;
+
+            if reviewed != statuses {
+                store_statuses(connection, sentence_uuid, &reviewed)?;
+                touch(connection, sentence_uuid)?;
+            }
+
+            connection.execute(
+                r#"
+                    INSERT INTO sentence_review(
+                        sentence_id,
+                        reviewer_id,
+                        subject,
+                        status,
+                        comment
+                    ) VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+                &[
+                    &sentence_uuid.to_string(),
+                    &author.user_id,
+                    &review.subject.as_str(),
+                    &review.status.as_str(),
+                    &review.comment,
+                ],
+            )?;
+
+            let id = connection.last_insert_rowid();
+            let reviewed_at: String = connection.query_row(
+                "SELECT reviewed_at FROM sentence_review WHERE id = ?1",
+                &[&id],
+                |row| row.get(0),
+            )?;
+
+            Ok(Review {
+                id: id as i32,
+                sentence_id: *sentence_uuid,
+                subject: review.subject,
+                status: review.status,
+                comment: review.comment.clone(),
+                reviewer_id: author.user_id,
+                reviewer: author.name.clone(),
+                reviewed_at: reviewed_at,
+            })
+        })
+    }
+
+    fn get_reviews(&self, sentence_uuid: &Uuid) -> Result<Option<Vec<Review>>, Error> {
+
+        let id = sentence_uuid.to_string();
+
+        let exists = query_all(
+            &self.connection,
+            "SELECT 1 FROM sentence WHERE id = ?1",
+            &[&id],
+            |_| (),
+        )?;
+
+        if exists.is_empty() {
+            return Ok(None);
+        }
+
+        let reviews = try_query_all(
+            &self.connection,
+            r#"
+                SELECT
+                    sentence_review.id,
+                    sentence_review.sentence_id,
+                    sentence_review.subject,
+                    sentence_review.status,
+                    sentence_review.comment,
+                    sentence_review.reviewer_id,
+                    user_account.name,
+                    sentence_review.reviewed_at
+                FROM sentence_review
+                LEFT JOIN user_account ON (user_account.id = sentence_review.reviewer_id)
+                WHERE sentence_review.sentence_id = ?1
+                ORDER BY sentence_review.id
+            "#,
+            &[&id],
+            |row| review_from_row(row),
+        )?;
+
+        Ok(Some(reviews))
+    }
+
+    fn submit_sentence(&self, sentence_uuid: &Uuid) -> Result<(), Error> {
+
+        self.in_transaction(|connection| {
+
+            let (snapshot, _) = match get_snapshot(connection, sentence_uuid)? {
+                Some(current) => current,
+                None => return Err(Error::NotFound),
+            };
+
+            let statuses = get_statuses(connection, sentence_uuid, snapshot.structure.is_some())?;
+            let submitted = statuses.submitted();
+
+            if submitted != statuses {
+                store_statuses(connection, sentence_uuid, &submitted)?;
+                touch(connection, sentence_uuid)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn get_review_queue(&self, iso639_3: Option<&str>, limit: i64) -> Result<Vec<Sentence>, Error> {
+
+        // the sentences without statuses are pending review, as read by sentence_from_row()
+        try_query_all(
+            &self.connection,
+            &format!(
+                r#"
+                    SELECT {}
+                    FROM sentence {}
+                    WHERE
+                        (?1 IS NULL OR language.iso639_3 = ?1) AND
+                        sentence.deleted_at IS NULL AND
+                        (
+                            COALESCE(sentence_status.status, 'pending_review') = 'pending_review' OR
+                            (
+                                sentence.structure IS NOT NULL AND
+                                COALESCE(sentence_status.structure_status, 'pending_review') = 'pending_review'
+                            )
+                        )
+                    ORDER BY
+                        sentence.added_at,
+                        sentence.rowid
+                    LIMIT ?2
+                "#,
+                SENTENCE_COLUMNS,
+                SENTENCE_JOINS,
+            ),
+            &[
+                &iso639_3,
+                &limit,
+            ],
+            |row| sentence_from_row(row, 0),
+        )
+    }
+}
+
+/// Reads a review from the `id, sentence_id, subject, status, comment,
+/// reviewer_id, reviewer name, reviewed_at` columns.
+fn review_from_row(row: &Row) -> Result<Review, Error> {
+    let subject: String = row.get(2);
+    let status: String = row.get(3);
+    Ok(Review {
+        id: row.get(0),
+        sentence_id: uuid_from_row(row, 1)?,
+        subject: subject.parse().map_err(Error::InvalidStoredValue)?,
+        status: status.parse().map_err(Error::InvalidStoredValue)?,
+        comment: row.get(4),
+        reviewer_id: row.get(5),
+        reviewer: row.get(6),
+        reviewed_at: row.get(7),
+    })
+}
+
+impl<'a> MigrationStore for SqliteRepository<'a> {
+
+    fn migrations(&self) -> &'static [Migration] {
+        migrations::SQLITE
+    }
+
+    fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, Error> {
+
+        self.connection.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%dT%H:%M:%fZ', 'now'))
+            );
+            "#,
+        )?;
+
+        query_all(
+            &*self.connection,
+            r#"
+                SELECT
+                    version,
+                    name,
+                    checksum,
+                    applied_at
+                FROM schema_migrations
+                ORDER BY version
+            "#,
+            &[],
+            |row| {
+                AppliedMigration {
+                    version: row.get(0),
+                    name: row.get(1),
+                    checksum: row.get(2),
+                    applied_at: row.get(3),
+                }
+            },
+        )
+    }
+
+    fn apply(&self, migration: &Migration) -> Result<(), Error> {
+
+        self.in_transaction(|connection| {
+            connection.execute_batch(migration.up)?;
+            connection.execute(
+                "INSERT INTO schema_migrations(version, name, checksum) VALUES (?1, ?2, ?3)",
+                &[
+                    &migration.version,
+                    &migration.name,
+                    &migration.checksum(),
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn revert(&self, migration: &Migration) -> Result<(), Error> {
+
+        self.in_transaction(|connection| {
+            connection.execute_batch(migration.down)?;
+            connection.execute(
+                "DELETE FROM schema_migrations WHERE version = ?1",
+                &[&migration.version],
+            )?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,148 @@
+use rocket::{Response, State};
+use rocket::http::{
+    Status,
+    ContentType,
+};
+use rocket_contrib::{Json, UUID};
+
+use uuid::Uuid;
+use std::io::Cursor;
+
+use auth::{Contributor, Reader, Reviewer};
+use config::Pagination;
+use error::Error;
+use iso639::Registry;
+use languages;
+use models::{NewReview, ReviewStatus};
+use repository::RequestRepository;
+use sentences::page_size;
+
+#[derive(FromForm)]
+struct QueueFilters {
+    lang: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Reviews a sentence, or its structure, giving it a new status
+/// and recording the comment of the reviewer. A rejection must be explained.
+#[post("/sentences/<sentence_uuid>/reviews", format="application/json", data="<review>")]
+fn review_sentence<'r>(
+    reviewer: Reviewer,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+    review: Json<NewReview>,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+    let mut review = review.into_inner();
+
+    // a blank comment is no comment
+    review.comment = review.comment
+        .map(|comment| comment.trim().to_string())
+        .and_then(|comment| if comment.is_empty() { None } else { Some(comment) });
+
+    match review.status {
+        ReviewStatus::Draft => {
+            return Err(Error::InvalidInput(
+                "a review cannot turn a sentence back into a draft".to_string()
+            ));
+        },
+        ReviewStatus::Rejected if review.comment.is_none() => {
+            return Err(Error::InvalidInput(
+                "a rejection needs a comment".to_string()
+            ));
+        },
+        _ => {},
+    }
+
+    let review = repository.review_sentence(&real_uuid, &review, &reviewer.0.author())?;
+
+    Ok(Response::build()
+        .status(Status::Created)
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(review).to_string()))
+        .finalize())
+}
+
+/// Lists the reviews of a sentence, deleted or not, oldest first.
+#[get("/sentences/<sentence_uuid>/reviews")]
+fn get_sentence_reviews<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+
+    let reviews = match repository.get_reviews(&real_uuid)? {
+        Some(reviews) => reviews,
+        None => return Err(Error::NotFound),
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(reviews).to_string()))
+        .finalize())
+}
+
+/// Submits a draft sentence, and its structure, for review.
+#[post("/sentences/<sentence_uuid>/submit")]
+fn submit_sentence<'r>(
+    _contributor: Contributor,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+
+    repository.submit_sentence(&real_uuid)?;
+
+    Ok(Response::build()
+        .status(Status::NoContent)
+        .finalize())
+}
+
+#[get("/review/queue")]
+fn get_review_queue<'r>(
+    reviewer: Reviewer,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    pagination: State<Pagination>,
+) -> Result<Response<'r>, Error> {
+
+    get_review_queue_with_filters(
+        reviewer,
+        QueueFilters {
+            lang: None,
+            limit: None,
+        },
+        repository,
+        registry,
+        pagination,
+    )
+}
+
+/// Lists the sentences whose text or structure is pending review,
+/// the ones added first coming first.
+#[get("/review/queue?<filters>")]
+fn get_review_queue_with_filters<'r>(
+    _reviewer: Reviewer,
+    filters: QueueFilters,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    pagination: State<Pagination>,
+) -> Result<Response<'r>, Error> {
+
+    let limit = page_size(filters.limit, &pagination)?;
+    let iso639_3 = filters.lang.map(|lang| languages::iso639_3(&registry, lang));
+
+    let sentences = repository.get_review_queue(
+        iso639_3.as_ref().map(|iso639_3| iso639_3.as_str()),
+        limit,
+    )?;
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(sentences).to_string()))
+        .finalize())
+}
@@ -0,0 +1,91 @@
+use rocket::Response;
+use rocket::http::{
+    Status,
+    ContentType,
+};
+use rocket_contrib::UUID;
+
+use uuid::Uuid;
+use std::io::Cursor;
+
+use auth::{Reader, Reviewer};
+use diff;
+use error::Error;
+use models::Revision;
+use one_sentence;
+use repository::RequestRepository;
+
+#[get("/sentences/<sentence_uuid>/history")]
+fn get_sentence_history<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+
+    let records = match repository.get_revisions(&real_uuid)? {
+        Some(records) => records,
+        None => return Err(Error::NotFound),
+    };
+
+    let revisions: Vec<Revision> = records.into_iter()
+        .map(|record| {
+            let structure_diff = if record.old.structure != record.new.structure {
+                Some(diff::render(&diff::diff_chars(
+                    record.old.structure.as_ref().map_or("", |structure| structure.as_str()),
+                    record.new.structure.as_ref().map_or("", |structure| structure.as_str()),
+                )))
+            } else {
+                None
+            };
+
+            Revision {
+                id: record.id,
+                revised_at: record.revised_at,
+                author: record.author,
+                text_diff: diff::render(&diff::diff_chars(&record.old.text, &record.new.text)),
+                structure_diff: structure_diff,
+                old: record.old,
+                new: record.new,
+            }
+        })
+        .collect();
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(revisions).to_string()))
+        .finalize())
+}
+
+/// Puts the sentence back in the state it had before the given revision,
+/// undoing that revision and all the ones made after it.
+#[post("/sentences/<sentence_uuid>/revert/<revision_id>")]
+fn revert_sentence<'r>(
+    reviewer: Reviewer,
+    repository: RequestRepository,
+    sentence_uuid: UUID,
+    revision_id: i32,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid : Uuid = *sentence_uuid;
+    let author = reviewer.0.author();
+
+    let restored = match repository.get_revision(&real_uuid, revision_id)? {
+        Some(snapshot) => snapshot,
+        None => return Err(Error::NotFound),
+    };
+
+    // a language removed since is reported as a foreign key violation
+    let modification = repository.modify_sentence(&real_uuid, &author, &mut |_, _| {
+        Ok(restored.clone())
+    })?;
+
+    if let Err(conflict) = one_sentence::modified_version(modification) {
+        return Ok(conflict);
+    }
+
+    Ok(Response::build()
+        .status(Status::NoContent)
+        .finalize())
+}
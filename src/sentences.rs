@@ -1,271 +1,172 @@
-use rocket::Response;
+use rocket::{Response, State};
 use rocket_contrib::Json;
 use rocket::http::{
     Status,
     ContentType,
 };
-use postgres::error::{
-    UNIQUE_VIOLATION,
-    FOREIGN_KEY_VIOLATION,
-};
-use xml::reader::EventReader;
-use xml::reader::XmlEvent::{Characters, Whitespace};
 
 use uuid::Uuid;
 use std::io::Cursor;
+use std::sync::Arc;
+
+use auth::{Contributor, Reader};
+use canonical::Canonicalizer;
+use config::{Features, Pagination};
+use error::Error;
+use iso639::Registry;
+use language_detection;
+use language_tag;
+use models::Sentence;
+use repository::{
+    Creation,
+    RequestRepository,
+    SentenceQuery,
+};
+use sentence_aligner_core::language_detection::Verdict;
+use validation;
 
-use db;
-
-#[derive(Deserialize, Serialize)]
-pub struct Sentence {
-    pub id: Option<Uuid>,
-    pub text: String,
-    pub iso639_3: String,
-    pub structure: Option<String>,
+#[derive(FromForm)]
+struct SentenceFilters {
+    starting_after_id: Option<String>,
+    script: Option<String>,
+    region: Option<String>,
+    limit: Option<i64>,
 }
 
-#[derive(FromForm)]
-struct LastUuid {
-    starting_after_id: String,
+/// Returns the number of sentences of a listing page.
+///
+/// Args:
+///
+/// `limit` - the number asked by the client, if any
+/// `pagination` - the configured page sizes
+///
+/// Returns:
+///
+/// the page size, `Error::InvalidInput` if the limit is out of bounds
+pub fn page_size(limit: Option<i64>, pagination: &Pagination) -> Result<i64, Error> {
+    match limit {
+        None => Ok(pagination.default_page_size),
+        Some(limit) if limit >= 1 && limit <= pagination.max_page_size => Ok(limit),
+        Some(limit) => Err(Error::InvalidInput(format!(
+            "limit {} is not between 1 and {}",
+            limit,
+            pagination.max_page_size,
+        ))),
+    }
 }
 
 #[post("/sentences", format="application/json", data="<sentence>")]
 fn create_sentence<'r>(
-    connection: db::DbConnection,
+    contributor: Contributor,
+    repository: RequestRepository,
+    registry: State<Registry>,
+    canonicalizer: State<Canonicalizer>,
+    features: State<Features>,
+    language_models: State<Arc<language_detection::Models>>,
     sentence: Json<Sentence>
-) -> Response<'r> {
-
-    if let Some(ref content) = sentence.structure {
+) -> Result<Response<'r>, Error> {
 
-        let parser = EventReader::from_str(&content);
-        let mut structure = String::new();
+    let sentence = validation::prepare_sentence(sentence.into_inner(), &registry, &canonicalizer)?;
 
-        for word in parser {
-            match word {
-                Ok(Characters(value)) | Ok(Whitespace(value)) => {
-                    structure += &value;
-                },
-                _ => {}
-            }
-        }
-
-        if sentence.text != structure {
-            return Response::build()
-                .status(Status::BadRequest)
-                .finalize();
-        }
-    }
-
-    let uuid = sentence.id.or_else(|| Some(Uuid::new_v4())).unwrap();
-
-    let result = connection.query(
-        r#"
-        INSERT INTO sentence(
-            id,
-            content,
-            language_id,
-            structure
-        ) VALUES (
-            $1,
-            $2,
-            -- the language id is found using coalesce()
-            -- in order to force a relation error
-            -- if no language is found
-            -- (it prevents NULL to be inserted as the sentence language) */
-            COALESCE((SELECT id FROM language WHERE iso639_3 = $3), 0),
-            $4::TEXT::XML
-        )
-        RETURNING id
-        "#,
-        &[
-            &uuid,
-            &sentence.text,
-            &sentence.iso639_3,
-            &sentence.structure,
-        ],
-    );
-
-    let rows = match result {
-        Ok(rows) => rows,
-        Err(ref e) => {
-
-            let error = e.code();
-            if error == Some(&UNIQUE_VIOLATION) {
-
-                let sentence = get_sentence_by_uuid_or_content_and_language(
-                    &connection,
-                    &uuid,
-                    &sentence.text,
-                    &sentence.iso639_3,
-                );
-
-                return Response::build()
-                    .status(Status::Conflict)
-                    .header(ContentType::JSON)
-                    .sized_body(Cursor::new(json!(sentence).to_string()))
-                    .finalize();
-            }
-            if error == Some(&FOREIGN_KEY_VIOLATION) {
-                return Response::build()
-                    .status(Status::BadRequest)
-                    .finalize();
-            }
+    /* a sentence very likely written in another language is refused,
+       a less certain disagreement is only reported */
+    let verdict = if features.language_detection {
+        language_models.detector().check(&sentence.text, &sentence.iso639_3)
+    } else {
+        Verdict::Agrees
+    };
 
-            panic!(format!("{}", e));
-        }
+    let warning = match verdict {
+        Verdict::Agrees => None,
+        Verdict::Disagrees(candidate) => Some(candidate),
+        Verdict::StronglyDisagrees(candidate) => return Err(Error::LanguageMismatch(candidate)),
     };
 
-    let sentence_uuid: Uuid = rows
-        .iter()
-        .next() // there's only 1 result
-        .expect("0 results, expected one...")
-        .get(0)
-    ;
+    let sentence_uuid = match repository.create_sentence(&sentence, &contributor.0.author())? {
+        Creation::Created(sentence_uuid) | Creation::Restored(sentence_uuid) => sentence_uuid,
+        Creation::Conflict(sentence) => {
+            return Ok(Response::build()
+                .status(Status::Conflict)
+                .header(ContentType::JSON)
+                .sized_body(Cursor::new(json!(sentence).to_string()))
+                .finalize());
+        },
+    };
 
-    Response::build()
+    let mut response = Response::build()
         .status(Status::Created)
         .raw_header("Location", format!("/sentences/{}", sentence_uuid))
-        .finalize()
+        .finalize();
+
+    if let Some(candidate) = warning {
+        response.set_raw_header(
+            language_detection::WARNING_HEADER,
+            format!("{}; score={:.3}", candidate.iso639_3, candidate.score),
+        );
+    }
+
+    Ok(response)
 }
 
 
 #[get("/sentences")]
 fn get_all_sentences<'r>(
-    connection: db::DbConnection,
-) -> Response<'r> {
-
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                content,
-                language.iso639_3,
-                structure::text
-            FROM sentence
-            JOIN language ON (sentence.language_id = language.id)
-            ORDER BY
-                added_at,
-                sentence.id
-            LIMIT 100
-        "#,
-        &[],
-    );
-
-    let rows = result.expect("problem while getting sentence");
-
-    let sentences: Vec<Sentence> = rows.iter()
-        .map(|row| {
-            Sentence {
-                id: row.get(0),
-                text: row.get(1),
-                iso639_3: row.get(2),
-                structure: row.get(3),
-            }
-        })
-        .collect();
-
-    Response::build()
-        .header(ContentType::JSON)
-        .sized_body(Cursor::new(json!(sentences).to_string()))
-        .finalize()
+    reader: Reader,
+    repository: RequestRepository,
+    pagination: State<Pagination>,
+) -> Result<Response<'r>, Error> {
+
+    get_all_sentences_with_last_uuid(
+        reader,
+        SentenceFilters {
+            starting_after_id: None,
+            script: None,
+            region: None,
+            limit: None,
+        },
+        repository,
+        pagination,
+    )
 }
 
-#[get("/sentences?<last_uuid>")]
+#[get("/sentences?<filters>")]
 fn get_all_sentences_with_last_uuid<'r>(
-    last_uuid: LastUuid,
-    connection: db::DbConnection,
-) -> Response<'r> {
-
-    let real_uuid: Uuid = Uuid::parse_str(&last_uuid.starting_after_id).unwrap();
+    _reader: Reader,
+    filters: SentenceFilters,
+    repository: RequestRepository,
+    pagination: State<Pagination>,
+) -> Result<Response<'r>, Error> {
+
+    let real_uuid: Option<Uuid> = match filters.starting_after_id {
+        Some(uuid) => match Uuid::parse_str(&uuid) {
+            Ok(uuid) => Some(uuid),
+            Err(_) => {
+                return Err(Error::InvalidInput(
+                    format!("starting_after_id {} is not a UUID", uuid)
+                ));
+            }
+        },
+        None => None,
+    };
 
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                content,
-                language.iso639_3,
-                structure::text
-            FROM sentence
-            JOIN language ON (sentence.language_id = language.id)
-            WHERE sentence.id >= $1
-            ORDER BY
-                added_at,
-                sentence.id
-            LIMIT 100
-        "#,
-        &[&real_uuid],
-    );
+    let limit = page_size(filters.limit, &pagination)?;
 
-    let rows = result.expect("problem while getting sentence");
+    /* unknown codes are kept as is, they then match no sentence */
+    let script = filters.script
+        .map(|script| language_tag::normalise_script(&script).unwrap_or(script));
+    let region = filters.region
+        .map(|region| language_tag::normalise_region(&region).unwrap_or(region));
 
-    let sentences: Vec<Sentence> = rows.iter()
-        .map(|row| {
-            Sentence {
-                id: row.get(0),
-                text: row.get(1),
-                iso639_3: row.get(2),
-                structure: row.get(3),
-            }
-        })
-        .collect();
+    let sentences = repository.get_sentences(&SentenceQuery {
+        starting_after_id: real_uuid,
+        iso639_3: None,
+        script: script,
+        region: region,
+        limit: limit,
+    })?;
 
-    Response::build()
+    Ok(Response::build()
         .header(ContentType::JSON)
         .sized_body(Cursor::new(json!(sentences).to_string()))
-        .finalize()
-}
-
-/// Return a sentence by its UUID or its content and language.
-///
-/// Args:
-///
-/// `connection` - database connection handler
-/// `uuid` - the sentence uuid
-/// `content` - the sentence content
-/// `iso639_3` - the sentence language
-///
-/// Returns:
-///
-/// a sentence object
-fn get_sentence_by_uuid_or_content_and_language(
-    connection: &db::DbConnection,
-    uuid: &Uuid,
-    content: &str,
-    iso639_3: &str,
-) -> Sentence {
-
-    let result = connection.query(
-        r#"
-            SELECT
-                sentence.id,
-                content,
-                language.iso639_3,
-                structure::text
-            FROM sentence
-            JOIN language ON (sentence.language_id = language.id)
-            WHERE
-            sentence.id = $1 OR
-            (sentence.content = $2 AND language.iso639_3 = $3)
-        "#,
-        &[
-            &uuid,
-            &content,
-            &iso639_3,
-        ],
-    );
-
-    let rows = result.expect("problem while getting sentence");
-
-    let row = rows
-        .iter()
-        .next() // there's only 1 result
-        .expect("0 results, expected one...")
-    ;
-
-    Sentence {
-        id: row.get(0),
-        text: row.get(1),
-        iso639_3: row.get(2),
-        structure: row.get(3),
-    }
+        .finalize())
 }
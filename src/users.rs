@@ -0,0 +1,63 @@
+use rocket::{Response, State};
+use rocket::http::ContentType;
+
+use std::io::Cursor;
+
+use auth::{Admin, Reader};
+use config::Pagination;
+use error::Error;
+use repository::RequestRepository;
+
+/// Lists the users, the ones the API keys are given to.
+#[get("/users")]
+fn get_users<'r>(
+    _admin: Admin,
+    repository: RequestRepository,
+) -> Result<Response<'r>, Error> {
+
+    let users = repository.get_users()?;
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(users).to_string()))
+        .finalize())
+}
+
+#[get("/users/<user_id>")]
+fn get_user<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    user_id: i32,
+) -> Result<Response<'r>, Error> {
+
+    let user = match repository.get_user(user_id)? {
+        Some(user) => user,
+        None => return Err(Error::NotFound),
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(user).to_string()))
+        .finalize())
+}
+
+/// Returns the number of sentences added and edits made by a user in
+/// each language, with a page of the latest sentences and of the latest edits.
+#[get("/users/<user_id>/contributions")]
+fn get_user_contributions<'r>(
+    _reader: Reader,
+    repository: RequestRepository,
+    pagination: State<Pagination>,
+    user_id: i32,
+) -> Result<Response<'r>, Error> {
+
+    let contributions = match repository.get_contributions(user_id, pagination.default_page_size)? {
+        Some(contributions) => contributions,
+        None => return Err(Error::NotFound),
+    };
+
+    Ok(Response::build()
+        .header(ContentType::JSON)
+        .sized_body(Cursor::new(json!(contributions).to_string()))
+        .finalize())
+}
@@ -1,17 +1,33 @@
 extern crate postgres;
+extern crate rusqlite;
 extern crate uuid;
 
 use std::env;
+use std::time::Duration;
 
-use self::postgres::{
-    Connection,
-    TlsMode,
-};
+use self::postgres::TlsMode;
 use self::postgres::params::{
     ConnectParams,
     Host,
 };
 
+/// The API keys of the tests, as `(role, SHA-256 of the key)`,
+/// the keys being in `utils/tests_commons.rs`. Each one is given
+/// to a user named after its role.
+const API_KEYS: [(&str, &str); 4] = [
+    ("reader", "c84e0916ac2bc43a1821afb14a4daac8ecc1d16aa4f6bbb47e998f557074058b"),
+    ("contributor", "6ae7ea5aa1fe3ff8d1eb0b0db659fd5d319b09af0f3b9f77c2146738ff9a6e6c"),
+    ("reviewer", "b1083db26dc4f9653c453db4c3541378476d1315856eb49e50ced0dea34b7562"),
+    ("admin", "944650a7cd0f9e14d5c4fb15edbffb7fa45fb9ed36a4fa9be3d7e5476ae51bd9"),
+];
+
+/// Connection to the database of the tested server,
+/// selected by `STORAGE` and `SQLITE_PATH` as the server does.
+pub enum Connection {
+    Postgres(postgres::Connection),
+    Sqlite(rusqlite::Connection),
+}
+
 pub trait DatabaseHandler {
 
     fn connect_and_clean() -> Self;
@@ -22,6 +38,8 @@ pub trait DatabaseHandler {
 
     fn insert_sentence_with_uuid(&self, uuid: &uuid::Uuid, content: &str, iso639_3: &str);
 
+    fn set_sentence_structure(&self, uuid: &uuid::Uuid, structure: &str);
+
     fn assert_language_exists(&self, iso639_3: &str);
 
     fn assert_sentence_structure_equals(&self, uuid: &uuid::Uuid, structure: &str);
@@ -31,12 +49,17 @@ pub trait DatabaseHandler {
     fn assert_sentence_language_equals(&self, uuid: &uuid::Uuid, iso639_3: &str);
 
     fn assert_sentence_structure_is_null(&self, uuid: &uuid::Uuid);
+
+    fn assert_sentence_is_deleted(&self, uuid: &uuid::Uuid, deleted: bool);
+
+    fn assert_sentence_does_not_exist(&self, uuid: &uuid::Uuid);
 }
 
-impl DatabaseHandler for Connection {
+impl DatabaseHandler for postgres::Connection {
 
-    /// Creates a new connection instance and clean the whole database content
-    fn connect_and_clean() -> Connection {
+    /// Creates a new connection instance and clean the whole database content,
+    /// only the API keys of the tests being left
+    fn connect_and_clean() -> postgres::Connection {
 
         let builder = ConnectParams::builder()
             .user(
@@ -46,13 +69,25 @@ impl DatabaseHandler for Connection {
             .database(&env::var("DB_NAME").expect("missing DB_NAME"))
             .build(Host::Tcp(env::var("DB_HOST").expect("missing DB_HOST")));
 
-        let connection = Connection::connect(
+        let connection = postgres::Connection::connect(
             builder,
             TlsMode::None,
         ).unwrap();
 
-        connection.execute("TRUNCATE TABLE sentence;", &[]).unwrap();
+        connection.execute("TRUNCATE TABLE sentence CASCADE;", &[]).unwrap();
         connection.execute("TRUNCATE TABLE language CASCADE;", &[]).unwrap();
+        connection.execute("TRUNCATE TABLE user_account CASCADE;", &[]).unwrap();
+
+        for &(role, key_hash) in API_KEYS.iter() {
+            connection.execute(
+                "INSERT INTO user_account(name) VALUES ($1)",
+                &[&role],
+            ).unwrap();
+            connection.execute(
+                "INSERT INTO api_key(user_id, role, key_hash) SELECT id, $1, $2 FROM user_account WHERE name = $1",
+                &[&role, &key_hash],
+            ).unwrap();
+        }
 
         connection
     }
@@ -156,6 +191,32 @@ impl DatabaseHandler for Connection {
         );
     }
 
+    /// Sets the structure of the sentence with the given id
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the uuid of the sentence to update
+    /// `structure` - the XML structure to set
+    fn set_sentence_structure(
+        &self,
+        uuid: &uuid::Uuid,
+        structure: &str,
+    ) {
+
+        let _ = self.execute(
+            r#"
+            UPDATE sentence
+            SET structure = $1::TEXT::XML
+            WHERE id = $2
+            "#,
+            &[
+                &structure,
+                &uuid,
+            ]
+        )
+        .expect("problem while setting sentence structure");
+    }
+
     /// Assertion to check if a given language exists from its iso639_3 name
     ///
     /// Args:
@@ -335,4 +396,493 @@ impl DatabaseHandler for Connection {
             None,
         );
     }
+
+    /// Assertion that checks if a sentence is soft deleted or not
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the UUID of the sentence to check
+    /// `deleted` - true if the sentence must be deleted
+    fn assert_sentence_is_deleted(
+        &self,
+        uuid: &uuid::Uuid,
+        deleted: bool,
+    ) {
+
+        let result = self.query(
+            r#"
+                SELECT deleted_at IS NOT NULL
+                FROM sentence
+                WHERE id = $1
+            "#,
+            &[&uuid]
+        );
+
+        let rows = result.expect("problem while getting sentence");
+
+        let row = rows
+            .iter()
+            .next() // there's only 1 result
+            .expect("0 results, expected one...")
+        ;
+
+        let is_deleted: bool = row.get(0);
+
+        assert_eq!(
+            is_deleted,
+            deleted,
+            "the sentence deletion state is not the expected one.",
+        );
+    }
+
+    /// Assertion that checks that a sentence is not in database anymore
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the UUID of the sentence to check
+    fn assert_sentence_does_not_exist(
+        &self,
+        uuid: &uuid::Uuid,
+    ) {
+
+        let rows = self.query(
+            r#"
+                SELECT 1
+                FROM sentence
+                WHERE id = $1
+            "#,
+            &[&uuid]
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(rows.len(), 0, "The sentence still exists.");
+    }
+}
+
+impl DatabaseHandler for Connection {
+
+    /// Connects to the database of the configured storage and cleans its whole content
+    fn connect_and_clean() -> Connection {
+
+        let storage = env::var("STORAGE").unwrap_or_else(|_| "postgres".to_string());
+
+        let connection = match storage.as_str() {
+            "sqlite" => Connection::Sqlite(DatabaseHandler::connect_and_clean()),
+            _ => Connection::Postgres(DatabaseHandler::connect_and_clean()),
+        };
+
+        // the server keeps the language detector trained from the sentences
+        // of the previous tests, it fails if the detection is turned off
+        let _ = ::tests_commons::client().refresh_language_detection();
+
+        connection
+    }
+
+    fn insert_language(&self, iso639_3: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.insert_language(iso639_3),
+            Connection::Sqlite(ref connection) => connection.insert_language(iso639_3),
+        }
+    }
+
+    fn insert_sentence(&self, content: &str, iso639_3: &str) -> uuid::Uuid {
+        match *self {
+            Connection::Postgres(ref connection) => connection.insert_sentence(content, iso639_3),
+            Connection::Sqlite(ref connection) => connection.insert_sentence(content, iso639_3),
+        }
+    }
+
+    fn insert_sentence_with_uuid(&self, uuid: &uuid::Uuid, content: &str, iso639_3: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.insert_sentence_with_uuid(uuid, content, iso639_3),
+            Connection::Sqlite(ref connection) => connection.insert_sentence_with_uuid(uuid, content, iso639_3),
+        }
+    }
+
+    fn set_sentence_structure(&self, uuid: &uuid::Uuid, structure: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.set_sentence_structure(uuid, structure),
+            Connection::Sqlite(ref connection) => connection.set_sentence_structure(uuid, structure),
+        }
+    }
+
+    fn assert_language_exists(&self, iso639_3: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_language_exists(iso639_3),
+            Connection::Sqlite(ref connection) => connection.assert_language_exists(iso639_3),
+        }
+    }
+
+    fn assert_sentence_structure_equals(&self, uuid: &uuid::Uuid, structure: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_sentence_structure_equals(uuid, structure),
+            Connection::Sqlite(ref connection) => connection.assert_sentence_structure_equals(uuid, structure),
+        }
+    }
+
+    fn assert_sentence_content_equals(&self, uuid: &uuid::Uuid, content: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_sentence_content_equals(uuid, content),
+            Connection::Sqlite(ref connection) => connection.assert_sentence_content_equals(uuid, content),
+        }
+    }
+
+    fn assert_sentence_language_equals(&self, uuid: &uuid::Uuid, iso639_3: &str) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_sentence_language_equals(uuid, iso639_3),
+            Connection::Sqlite(ref connection) => connection.assert_sentence_language_equals(uuid, iso639_3),
+        }
+    }
+
+    fn assert_sentence_structure_is_null(&self, uuid: &uuid::Uuid) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_sentence_structure_is_null(uuid),
+            Connection::Sqlite(ref connection) => connection.assert_sentence_structure_is_null(uuid),
+        }
+    }
+
+    fn assert_sentence_is_deleted(&self, uuid: &uuid::Uuid, deleted: bool) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_sentence_is_deleted(uuid, deleted),
+            Connection::Sqlite(ref connection) => connection.assert_sentence_is_deleted(uuid, deleted),
+        }
+    }
+
+    fn assert_sentence_does_not_exist(&self, uuid: &uuid::Uuid) {
+        match *self {
+            Connection::Postgres(ref connection) => connection.assert_sentence_does_not_exist(uuid),
+            Connection::Sqlite(ref connection) => connection.assert_sentence_does_not_exist(uuid),
+        }
+    }
+}
+
+impl DatabaseHandler for rusqlite::Connection {
+
+    /// Opens the SQLite file of the server and clean the whole database content,
+    /// only the API keys of the tests being left
+    fn connect_and_clean() -> rusqlite::Connection {
+
+        let path = env::var("SQLITE_PATH")
+            .unwrap_or_else(|_| "sentence-aligner.db".to_string());
+
+        let connection = rusqlite::Connection::open(path).unwrap();
+
+        // the server writes to the same file
+        connection.busy_timeout(Duration::from_secs(5)).unwrap();
+
+        connection.execute_batch(
+            r#"
+            PRAGMA foreign_keys = ON;
+            DELETE FROM sentence;
+            DELETE FROM language;
+            DELETE FROM user_account;
+            "#,
+        ).unwrap();
+
+        for &(role, key_hash) in API_KEYS.iter() {
+            connection.execute(
+                "INSERT INTO user_account(name) VALUES (?1)",
+                &[&role],
+            ).unwrap();
+            connection.execute(
+                "INSERT INTO api_key(user_id, role, key_hash) SELECT id, ?1, ?2 FROM user_account WHERE name = ?1",
+                &[&role, &key_hash],
+            ).unwrap();
+        }
+
+        connection
+    }
+
+    /// Inserts a language with a given iso639_3 name
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the iso639_3 name of the language to insert
+    fn insert_language(
+        &self,
+        iso639_3: &str,
+    ) {
+
+        let _ = self.execute(
+            r#"
+            INSERT INTO language(iso639_3)
+            VALUES (?1)
+            "#,
+            &[&iso639_3]
+        )
+        .expect("problem while inserting language");
+    }
+
+    /// Inserts a sentence with the given content and language
+    ///
+    /// Args:
+    ///
+    /// `content` - the content of the language to insert
+    /// `iso639_3` - the iso639_3 name of the language to insert
+    ///
+    /// Returns:
+    ///
+    /// the generated uuid of the inserted sentence
+    fn insert_sentence(
+        &self,
+        content: &str,
+        iso639_3: &str,
+    ) -> uuid::Uuid {
+
+        let sentence_uuid = uuid::Uuid::new_v4();
+
+        self.execute(
+            r#"
+            INSERT INTO sentence(
+                id,
+                content,
+                language_id
+            ) VALUES (
+                ?1,
+                ?2,
+                (SELECT id FROM language WHERE iso639_3 = ?3)
+            )
+            "#,
+            &[
+                &sentence_uuid.to_string(),
+                &content,
+                &iso639_3,
+            ]
+        )
+        .expect("problem while inserting sentence");
+
+        sentence_uuid
+    }
+
+    /// Inserts a sentence with the given id, content and language
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the uuid of the sentence to insert
+    /// `content` - the content of the language to insert
+    /// `iso639_3` - the iso639_3 name of the language to insert
+    fn insert_sentence_with_uuid(
+        &self,
+        uuid: &uuid::Uuid,
+        content: &str,
+        iso639_3: &str,
+    ) {
+
+        let _ = self.execute(
+            r#"
+            INSERT INTO sentence(
+                id,
+                content,
+                language_id
+            ) VALUES (
+                ?1,
+                ?2,
+                (SELECT id FROM language WHERE iso639_3 = ?3)
+            )
+            "#,
+            &[
+                &uuid.to_string(),
+                &content,
+                &iso639_3,
+            ]
+        );
+    }
+
+    /// Sets the structure of the sentence with the given id
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the uuid of the sentence to update
+    /// `structure` - the XML structure to set
+    fn set_sentence_structure(
+        &self,
+        uuid: &uuid::Uuid,
+        structure: &str,
+    ) {
+
+        let _ = self.execute(
+            r#"
+            UPDATE sentence
+            SET structure = ?1
+            WHERE id = ?2
+            "#,
+            &[
+                &structure,
+                &uuid.to_string(),
+            ]
+        )
+        .expect("problem while setting sentence structure");
+    }
+
+    /// Assertion to check if a given language exists from its iso639_3 name
+    ///
+    /// Args:
+    ///
+    /// `iso639_3` - the iso639_3 name of the language that must exists
+    fn assert_language_exists(
+        &self,
+        iso639_3: &str,
+    ) {
+
+        let count: i64 = self.query_row(
+            "SELECT COUNT(*) FROM language WHERE iso639_3 = ?1",
+            &[&iso639_3],
+            |row| row.get(0),
+        )
+        .expect("problem while getting language");
+
+        assert_eq!(count, 1, "The language does not exist.");
+    }
+
+    /// Assertion to check if the structure for the sentence at the given id is equals to the expected one
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the sentence uuid of the sentence to check
+    /// `structure` - the expected structure for the sentence to check
+    fn assert_sentence_structure_equals(
+        &self,
+        uuid: &uuid::Uuid,
+        structure: &str,
+    ) {
+
+        let current_structure: Option<String> = self.query_row(
+            "SELECT structure FROM sentence WHERE id = ?1",
+            &[&uuid.to_string()],
+            |row| row.get(0),
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(
+            structure,
+            current_structure.unwrap(),
+            "the sentence structure is not the expected one.",
+        );
+    }
+
+    /// Assertion to check if the content for the sentence at the given id is equals to the expected one
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the sentence uuid of the sentence to check
+    /// `content` - the expected content for the sentence to check
+    fn assert_sentence_content_equals(
+        &self,
+        uuid: &uuid::Uuid,
+        content: &str,
+    ) {
+
+        let current_content: String = self.query_row(
+            "SELECT content FROM sentence WHERE id = ?1",
+            &[&uuid.to_string()],
+            |row| row.get(0),
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(
+            content,
+            current_content,
+            "the sentence content is not the expected one.",
+        );
+    }
+
+    /// Assertion to check if the language for the sentence at the given id is equals to the expected one
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the sentence uuid of the sentence to check
+    /// `iso639_3` - the expected language for the sentence to check
+    fn assert_sentence_language_equals(
+        &self,
+        uuid: &uuid::Uuid,
+        iso639_3: &str,
+    ) {
+
+        let current_iso639_3: String = self.query_row(
+            r#"
+                SELECT language.iso639_3
+                FROM sentence
+                JOIN language ON (sentence.language_id = language.id)
+                WHERE sentence.id = ?1
+            "#,
+            &[&uuid.to_string()],
+            |row| row.get(0),
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(
+            iso639_3,
+            current_iso639_3,
+            "the sentence language is not the expected one.",
+        );
+    }
+
+    /// Assertion that checks if a sentence structure is NULL
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the UUID of the sentence to check
+    fn assert_sentence_structure_is_null(
+        &self,
+        uuid: &uuid::Uuid,
+    ) {
+
+        let current_structure: Option<String> = self.query_row(
+            "SELECT structure FROM sentence WHERE id = ?1",
+            &[&uuid.to_string()],
+            |row| row.get(0),
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(
+            current_structure,
+            None,
+        );
+    }
+
+    /// Assertion that checks if a sentence is soft deleted or not
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the UUID of the sentence to check
+    /// `deleted` - true if the sentence must be deleted
+    fn assert_sentence_is_deleted(
+        &self,
+        uuid: &uuid::Uuid,
+        deleted: bool,
+    ) {
+
+        let is_deleted: bool = self.query_row(
+            "SELECT deleted_at IS NOT NULL FROM sentence WHERE id = ?1",
+            &[&uuid.to_string()],
+            |row| row.get(0),
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(
+            is_deleted,
+            deleted,
+            "the sentence deletion state is not the expected one.",
+        );
+    }
+
+    /// Assertion that checks that a sentence is not in database anymore
+    ///
+    /// Args:
+    ///
+    /// `uuid` - the UUID of the sentence to check
+    fn assert_sentence_does_not_exist(
+        &self,
+        uuid: &uuid::Uuid,
+    ) {
+
+        let count: i64 = self.query_row(
+            "SELECT COUNT(*) FROM sentence WHERE id = ?1",
+            &[&uuid.to_string()],
+            |row| row.get(0),
+        )
+        .expect("problem while getting sentence");
+
+        assert_eq!(count, 0, "The sentence still exists.");
+    }
 }
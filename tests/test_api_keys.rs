@@ -0,0 +1,117 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::Client;
+use sentence_aligner_client::models::Role;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_request_without_key_returns_401() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let error = Client::new(tests_commons::SERVICE_URL).get_languages().unwrap_err();
+
+    assert_eq!(error.status(), Some(401));
+    assert_eq!(error.code(), Some("unauthorized"));
+}
+
+#[test]
+fn test_request_with_unknown_key_returns_401() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let error = tests_commons::client_with_key("not-a-key").get_languages().unwrap_err();
+
+    assert_eq!(error.status(), Some(401));
+}
+
+#[test]
+fn test_reader_can_read_but_not_write() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let client = tests_commons::client_with_key(tests_commons::READER_KEY);
+
+    assert_eq!(client.get_languages().unwrap().len(), 1);
+
+    let error = client.create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap_err();
+
+    assert_eq!(error.status(), Some(403));
+    assert_eq!(error.code(), Some("forbidden"));
+}
+
+#[test]
+fn test_only_admin_can_create_language() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let reviewer = tests_commons::client_with_key(tests_commons::REVIEWER_KEY);
+    assert_eq!(reviewer.create_language("eng").unwrap_err().status(), Some(403));
+
+    let admin = tests_commons::client_with_key(tests_commons::ADMIN_KEY);
+    assert_eq!(admin.create_language("eng").unwrap(), "eng");
+}
+
+#[test]
+fn test_contributor_cannot_purge_sentence() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client_with_key(tests_commons::CONTRIBUTOR_KEY);
+
+    assert_eq!(client.delete_sentence(&uuid, true).unwrap_err().status(), Some(403));
+
+    client.delete_sentence(&uuid, false).unwrap();
+    connection.assert_sentence_is_deleted(&uuid, true);
+}
+
+#[test]
+fn test_created_key_is_shown_once_and_can_be_revoked() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let admin = tests_commons::client();
+
+    let api_key = admin.create_api_key("importer", Role::Contributor).unwrap();
+    assert_eq!(api_key.role, Role::Contributor);
+
+    let key = api_key.key.expect("a created key is returned with its secret");
+
+    let listed = admin.get_api_keys().unwrap();
+    let listed_key = listed.iter().find(|listed| listed.id == api_key.id).unwrap();
+    assert_eq!(listed_key.name, "importer");
+    assert!(listed_key.key.is_none());
+
+    let importer = tests_commons::client_with_key(&key);
+    importer.create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    admin.revoke_api_key(api_key.id).unwrap();
+
+    let error = importer.create_sentence(&tests_commons::sentence("Bye.", "eng")).unwrap_err();
+    assert_eq!(error.status(), Some(401));
+}
+
+#[test]
+fn test_non_admin_cannot_create_key() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let reviewer = tests_commons::client_with_key(tests_commons::REVIEWER_KEY);
+    let error = reviewer.create_api_key("someone", Role::Admin).unwrap_err();
+
+    assert_eq!(error.status(), Some(403));
+}
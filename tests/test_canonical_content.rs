@@ -0,0 +1,108 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_post_sentence_stores_canonical_content() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("fra");
+
+    let uuid = uuid::Uuid::new_v4();
+
+    let mut sentence = tests_commons::sentence(
+        " Un cafe\u{301}\u{a0}au   lait, s'il vous plaît.\n",
+        "fra",
+    );
+    sentence.id = Some(uuid);
+
+    let client = tests_commons::client();
+    client.create_sentence(&sentence).unwrap();
+
+    connection.assert_sentence_content_equals(
+        &uuid,
+        "Un caf\u{e9} au lait, s'il vous plaît.",
+    );
+}
+
+#[test]
+fn test_post_sentence_differing_only_by_normalisation_returns_409() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("fra");
+
+    let client = tests_commons::client();
+    client.create_sentence(&tests_commons::sentence("Un caf\u{e9}.", "fra")).unwrap();
+
+    let error = client
+        .create_sentence(&tests_commons::sentence("Un cafe\u{301}. ", "fra"))
+        .unwrap_err();
+
+    assert_eq!(error.status(), Some(409));
+}
+
+#[test]
+fn test_post_sentence_canonicalises_structure_text_nodes() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = uuid::Uuid::new_v4();
+
+    let mut sentence = tests_commons::sentence("This  is a sentence.\n", "eng");
+    sentence.id = Some(uuid);
+    sentence.structure = Some(
+        "<sentence><subject>This</subject>  <verb>is</verb> a sentence.\n</sentence>".to_string()
+    );
+
+    let client = tests_commons::client();
+    client.create_sentence(&sentence).unwrap();
+
+    connection.assert_sentence_content_equals(&uuid, "This is a sentence.");
+    connection.assert_sentence_structure_equals(
+        &uuid,
+        "<sentence><subject>This</subject> <verb>is</verb> a sentence.</sentence>",
+    );
+}
+
+#[test]
+fn test_put_sentence_text_stores_canonical_content() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    client.edit_sentence_text(&uuid, "This\tis   another sentence. ", None).unwrap();
+
+    connection.assert_sentence_content_equals(&uuid, "This is another sentence.");
+}
+
+#[test]
+fn test_get_content_collisions_returns_colliding_sentences() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let first_uuid = connection.insert_sentence("This is one sentence.", "eng");
+    let second_uuid = connection.insert_sentence("This is  one sentence.", "eng");
+    connection.insert_sentence("This is another sentence.", "eng");
+
+    let client = tests_commons::client();
+    let collisions = client.get_content_collisions().unwrap();
+
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].iso639_3, "eng");
+    assert_eq!(collisions[0].canonical, "This is one sentence.");
+    assert_eq!(collisions[0].sentences, vec![first_uuid, second_uuid]);
+}
@@ -0,0 +1,90 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_get_sentence_returns_etag() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let sentence = client.get_sentence(&uuid).unwrap();
+
+    assert_eq!(sentence.etag, "\"1\"");
+}
+
+#[test]
+fn test_get_sentence_with_current_etag_returns_304() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let etag = client.get_sentence(&uuid).unwrap().etag;
+
+    let sentence = client.get_sentence_if_none_match(&uuid, &etag).unwrap();
+
+    assert!(sentence.is_none());
+}
+
+#[test]
+fn test_put_sentence_text_with_current_etag_returns_204_and_new_etag() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let etag = client.get_sentence(&uuid).unwrap().etag;
+
+    let edit = client.edit_sentence_text(
+        &uuid,
+        "This is another sentence.",
+        Some(&etag),
+    ).unwrap();
+
+    assert_ne!(edit.etag, etag);
+
+    connection.assert_sentence_content_equals(&uuid, "This is another sentence.");
+}
+
+#[test]
+fn test_put_sentence_structure_with_outdated_etag_returns_412() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let etag = client.get_sentence(&uuid).unwrap().etag;
+
+    let first_structure = "<sentence><subject>This</subject> is one sentence.</sentence>";
+    client.edit_sentence_structure(&uuid, first_structure, Some(&etag)).unwrap();
+
+    let error = client.edit_sentence_structure(
+        &uuid,
+        "<sentence>This <verb>is</verb> one sentence.</sentence>",
+        Some(&etag),
+    ).unwrap_err();
+
+    assert_eq!(error.status(), Some(412));
+    assert_eq!(error.code(), Some("precondition-failed"));
+
+    connection.assert_sentence_structure_equals(&uuid, first_structure);
+}
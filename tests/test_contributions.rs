@@ -0,0 +1,122 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::Client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+/// Returns the id of the user of a test key, named after its role.
+fn user_id(name: &str) -> i32 {
+    tests_commons::client()
+        .get_users()
+        .unwrap()
+        .iter()
+        .find(|user| user.name == name)
+        .expect("the test users are created by connect_and_clean")
+        .id
+}
+
+fn contributor() -> Client {
+    tests_commons::client_with_key(tests_commons::CONTRIBUTOR_KEY)
+}
+
+#[test]
+fn test_created_sentence_is_attributed_to_its_author() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    let sentence = tests_commons::client().get_sentence(&created.id).unwrap().value;
+
+    let contributor_id = user_id("contributor");
+    assert_eq!(sentence.created_by, Some(contributor_id));
+    assert_eq!(sentence.updated_by, Some(contributor_id));
+}
+
+#[test]
+fn test_edited_sentence_is_attributed_to_its_last_editor() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    let admin = tests_commons::client();
+    admin.edit_sentence_text(&created.id, "Hello!", None).unwrap();
+
+    let sentence = admin.get_sentence(&created.id).unwrap().value;
+
+    assert_eq!(sentence.created_by, Some(user_id("contributor")));
+    assert_eq!(sentence.updated_by, Some(user_id("admin")));
+}
+
+#[test]
+fn test_sentence_inserted_without_author_has_no_attribution() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let sentence = tests_commons::client().get_sentence(&uuid).unwrap().value;
+
+    assert_eq!(sentence.created_by, None);
+    assert_eq!(sentence.updated_by, None);
+}
+
+#[test]
+fn test_get_user_contributions_counts_per_language() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    connection.insert_language("fra");
+    let other = connection.insert_sentence("Good morning.", "eng");
+
+    let client = contributor();
+    let first = client.create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+    let second = client.create_sentence(&tests_commons::sentence("Bye.", "eng")).unwrap();
+    client.create_sentence(&tests_commons::sentence("Bonjour.", "fra")).unwrap();
+    client.edit_sentence_text(&other, "Good evening.", None).unwrap();
+    client.edit_sentence_text(&first.id, "Hello!", None).unwrap();
+
+    let contributions = tests_commons::client()
+        .get_user_contributions(user_id("contributor"))
+        .unwrap();
+
+    assert_eq!(contributions.user.name, "contributor");
+
+    assert_eq!(contributions.languages.len(), 2);
+    assert_eq!(contributions.languages[0].iso639_3, "eng");
+    assert_eq!(contributions.languages[0].sentences_created, 2);
+    assert_eq!(contributions.languages[0].edits, 2);
+    assert_eq!(contributions.languages[1].iso639_3, "fra");
+    assert_eq!(contributions.languages[1].sentences_created, 1);
+    assert_eq!(contributions.languages[1].edits, 0);
+
+    assert_eq!(contributions.sentences.len(), 3);
+    assert_eq!(contributions.sentences[2].id, Some(first.id));
+    assert_eq!(contributions.sentences[1].id, Some(second.id));
+
+    assert_eq!(contributions.edits.len(), 2);
+    assert_eq!(contributions.edits[0].sentence_id, first.id);
+    assert_eq!(contributions.edits[1].sentence_id, other);
+}
+
+#[test]
+fn test_get_contributions_of_unknown_user_returns_404() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let error = tests_commons::client().get_user_contributions(-1).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
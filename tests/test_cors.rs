@@ -0,0 +1,92 @@
+extern crate reqwest;
+extern crate uuid;
+extern crate postgres;
+extern crate sentence_aligner_client;
+
+use reqwest::{Method, StatusCode};
+use reqwest::header::Headers;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+/// Sends the preflight request of a `POST` on the given path.
+fn preflight(path: &str) -> reqwest::Response {
+
+    let url = format!("{}{}", tests_commons::SERVICE_URL, path);
+
+    let mut headers = Headers::new();
+    headers.set_raw("Origin", "https://example.com");
+    headers.set_raw("Access-Control-Request-Method", "POST");
+    headers.set_raw("Access-Control-Request-Headers", "Content-Type");
+
+    reqwest::Client::new()
+        .request(Method::Options, &url)
+        .headers(headers)
+        .send()
+        .unwrap()
+}
+
+/// Returns a header of a response, if any.
+fn header(response: &reqwest::Response, name: &str) -> Option<String> {
+    response.headers()
+        .get_raw(name)
+        .and_then(|header| header.one())
+        .map(|header| String::from_utf8(header.to_vec()).unwrap())
+}
+
+#[test]
+fn test_preflight_of_an_existing_route_is_answered() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let response = preflight("/sentences");
+
+    assert_eq!(response.status(), StatusCode::NoContent);
+    assert!(header(&response, "Access-Control-Allow-Origin").is_some());
+
+    let methods = header(&response, "Access-Control-Allow-Methods").unwrap();
+    assert!(methods.contains("POST"));
+    assert!(methods.contains("GET"));
+    assert!(!methods.contains("PATCH"));
+
+    let allowed_headers = header(&response, "Access-Control-Allow-Headers").unwrap();
+    assert!(allowed_headers.contains("Content-Type"));
+}
+
+#[test]
+fn test_preflight_of_a_missing_route_is_not_found() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let response = preflight("/nothing/here");
+
+    assert_eq!(response.status(), StatusCode::NotFound);
+    assert!(header(&response, "Access-Control-Allow-Methods").is_none());
+}
+
+#[test]
+fn test_responses_expose_the_location_and_the_etag() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let url = format!("{}/languages", tests_commons::SERVICE_URL);
+
+    let mut headers = Headers::new();
+    headers.set_raw("Origin", "https://example.com");
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .headers(headers)
+        .send()
+        .unwrap();
+
+    let exposed_headers = header(&response, "Access-Control-Expose-Headers").unwrap();
+    assert!(exposed_headers.contains("Location"));
+    assert!(exposed_headers.contains("ETag"));
+}
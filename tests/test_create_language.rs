@@ -1,19 +1,12 @@
 extern crate postgres;
-extern crate reqwest;
 extern crate uuid;
-extern crate interface_tests_helpers;
+extern crate sentence_aligner_client;
 
-#[macro_use] extern crate serde_derive;
-
-use postgres::Connection;
-
-use interface_tests_helpers::ResponseHandler;
+use db::Connection;
 
 mod db;
-mod handlers;
 
 use db::DatabaseHandler;
-use handlers::LanguageHandler;
 
 #[path = "../utils/tests_commons.rs"]
 mod tests_commons;
@@ -25,10 +18,10 @@ fn test_post_language_returns_201() {
 
     let created_language = "eng";
 
-    let client = reqwest::Client::new();
-    let response = client.post_language(&created_language);
+    let client = tests_commons::client();
+    let iso639_3 = client.create_language(&created_language).unwrap();
 
-    response.assert_201();
+    assert_eq!(iso639_3, created_language);
 
     connection.assert_language_exists(&created_language);
 }
@@ -41,17 +34,58 @@ fn test_post_language_that_already_exists_returns_409() {
     let created_language = "eng";
     connection.insert_language(&created_language);
 
-    let client = reqwest::Client::new();
-    let response = client.post_language(&created_language);
+    let client = tests_commons::client();
+    let error = client.create_language(&created_language).unwrap_err();
+
+    assert_eq!(error.status(), Some(409));
+    assert_eq!(error.code(), Some("unique-violation"));
+}
+
+#[test]
+fn test_post_language_with_iso639_1_code_is_stored_as_iso639_3() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let iso639_3 = client.create_language("fr").unwrap();
+
+    assert_eq!(iso639_3, "fra");
+
+    connection.assert_language_exists("fra");
+}
+
+#[test]
+fn test_post_language_with_bcp47_tag_is_stored_as_iso639_3() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    client.create_language("zh-yue-HK").unwrap();
+
+    connection.assert_language_exists("yue");
+}
+
+#[test]
+fn test_post_language_with_unknown_code_returns_400() {
 
-    response.assert_409();
+    let client = tests_commons::client();
+    let error = client.create_language("xyz").unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
 }
 
 #[test]
-fn test_post_language_with_incorrect_iso639_3_length() {
+fn test_post_individual_language_has_its_macrolanguage() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    client.create_language("ekk").unwrap();
 
-    let client = reqwest::Client::new();
-    let response = client.post_language("fr"); // two characters given, three expected
+    let language = client.get_language("ekk").unwrap();
 
-    response.assert_500();
+    assert_eq!(
+        language.info.as_ref().and_then(|info| info.macrolanguage.as_ref()).map(|code| code.as_str()),
+        Some("est"),
+    );
 }
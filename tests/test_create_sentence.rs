@@ -1,21 +1,14 @@
 extern crate postgres;
-extern crate reqwest;
 extern crate uuid;
-extern crate interface_tests_helpers;
+extern crate sentence_aligner_client;
 
-#[macro_use] extern crate serde_derive;
+use sentence_aligner_client::Error;
 
-use std::collections::HashMap;
-
-use postgres::Connection;
-
-use interface_tests_helpers::ResponseHandler;
+use db::Connection;
 
 mod db;
-mod handlers;
 
 use db::DatabaseHandler;
-use handlers::SentenceHandler;
 
 #[path = "../utils/tests_commons.rs"]
 mod tests_commons;
@@ -28,14 +21,12 @@ fn test_post_sentence_returns_200() {
     let iso639_3 = "eng";
     connection.insert_language(&iso639_3);
 
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("text", "This is a sentence.");
-    json.insert("iso639_3", &iso639_3);
+    let client = tests_commons::client();
+    let created = client
+        .create_sentence(&tests_commons::sentence("This is a sentence.", iso639_3))
+        .unwrap();
 
-    let client = reqwest::Client::new();
-    let response = client.post_sentence(&json);
-
-    response.assert_201();
+    connection.assert_sentence_content_equals(&created.id, "This is a sentence.");
 }
 
 #[test]
@@ -47,17 +38,14 @@ fn test_post_sentence_without_structure_returns_200() {
     connection.insert_language(&language);
 
     let uuid = uuid::Uuid::new_v4();
-    let uuid_as_string = uuid.to_string();
 
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("id", &uuid_as_string);
-    json.insert("text", "This is a sentence.");
-    json.insert("iso639_3", language);
+    let mut sentence = tests_commons::sentence("This is a sentence.", language);
+    sentence.id = Some(uuid);
 
-    let client = reqwest::Client::new();
-    let response = client.post_sentence(&json);
+    let client = tests_commons::client();
+    let created = client.create_sentence(&sentence).unwrap();
 
-    response.assert_201();
+    assert_eq!(created.id, uuid);
 
     connection.assert_sentence_structure_is_null(&uuid);
 }
@@ -71,20 +59,15 @@ fn test_post_with_structure_returns_200() {
     connection.insert_language(&language);
 
     let uuid = uuid::Uuid::new_v4();
-    let uuid_as_string = uuid.to_string();
-
-    let mut json: HashMap<&str, &str> = HashMap::new();
 
     let structure = "<sentence><subject>This</subject> <verb>is</verb> <complement>a</complement> <complement>sentence</complement>.</sentence>";
-    json.insert("id", &uuid_as_string);
-    json.insert("text", "This is a sentence.");
-    json.insert("iso639_3", language);
-    json.insert("structure", structure);
 
-    let client = reqwest::Client::new();
-    let response = client.post_sentence(&json);
+    let mut sentence = tests_commons::sentence("This is a sentence.", language);
+    sentence.id = Some(uuid);
+    sentence.structure = Some(structure.to_string());
 
-    response.assert_201();
+    let client = tests_commons::client();
+    client.create_sentence(&sentence).unwrap();
 
     connection.assert_sentence_structure_equals(
         &uuid,
@@ -103,22 +86,18 @@ fn test_post_sentence_with_used_uuid_returns_409() {
     let text = "This is one sentence.";
     let uuid = connection.insert_sentence(&text, &iso639_3);
 
-    let uuid_string = uuid.to_string();
-
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("id", &uuid_string);
-    json.insert("text", "Une autre phrase.");
-    json.insert("iso639_3", iso639_3);
-
-    let client = reqwest::Client::new();
-    let mut response = client.post_sentence(&json);
-
-    let sentence = response.json::<tests_commons::Sentence>().unwrap();
+    let mut sentence = tests_commons::sentence("Une autre phrase.", iso639_3);
+    sentence.id = Some(uuid);
 
-    assert_eq!(sentence.text, text);
-    assert_eq!(sentence.iso639_3, iso639_3);
+    let client = tests_commons::client();
 
-    response.assert_409();
+    match client.create_sentence(&sentence) {
+        Err(Error::Conflict(sentence)) => {
+            assert_eq!(sentence.text, text);
+            assert_eq!(sentence.iso639_3, iso639_3);
+        },
+        other => panic!("expected a conflict, got {:?}", other),
+    }
 }
 
 #[test]
@@ -126,14 +105,12 @@ fn test_post_sentence_with_non_existing_language_returns_400() {
 
     let _: Connection = DatabaseHandler::connect_and_clean();
 
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("text", "This is a sentence.");
-    json.insert("iso639_3", "eng");
+    let client = tests_commons::client();
+    let error = client
+        .create_sentence(&tests_commons::sentence("This is a sentence.", "eng"))
+        .unwrap_err();
 
-    let client = reqwest::Client::new();
-    let response = client.post_sentence(&json);
-
-    response.assert_400();
+    assert_eq!(error.status(), Some(400));
 }
 
 #[test]
@@ -144,15 +121,14 @@ fn test_post_sentence_structure_that_does_not_match_content_returns_400() {
     let iso639_3 = "eng";
     connection.insert_language(&iso639_3);
 
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("text", "This is a sentence.");
-    json.insert("iso639_3", &iso639_3);
-    json.insert("structure", "<sentence>Not matching structure.</sentence>");
+    let mut sentence = tests_commons::sentence("This is a sentence.", iso639_3);
+    sentence.structure = Some("<sentence>Not matching structure.</sentence>".to_string());
 
-    let client = reqwest::Client::new();
-    let response = client.post_sentence(&json);
+    let client = tests_commons::client();
+    let error = client.create_sentence(&sentence).unwrap_err();
 
-    response.assert_400();
+    assert_eq!(error.status(), Some(400));
+    assert_eq!(error.code(), Some("invalid-input"));
 }
 
 #[test]
@@ -166,19 +142,15 @@ fn test_post_sentence_with_used_content_and_language_returns_409() {
     let text = "This is one sentence.";
     connection.insert_sentence(&text, &iso639_3);
 
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("text", text);
-    json.insert("iso639_3", iso639_3);
-
-    let client = reqwest::Client::new();
-    let mut response = client.post_sentence(&json);
-
-    let sentence = response.json::<tests_commons::Sentence>().unwrap();
+    let client = tests_commons::client();
 
-    assert_eq!(sentence.text, text);
-    assert_eq!(sentence.iso639_3, iso639_3);
-
-    response.assert_409();
+    match client.create_sentence(&tests_commons::sentence(text, iso639_3)) {
+        Err(Error::Conflict(sentence)) => {
+            assert_eq!(sentence.text, text);
+            assert_eq!(sentence.iso639_3, iso639_3);
+        },
+        other => panic!("expected a conflict, got {:?}", other),
+    }
 }
 
 #[test]
@@ -195,12 +167,6 @@ fn test_post_sentence_with_used_content_and_different_language_returns_201() {
     let text = "This is one sentence.";
     connection.insert_sentence(&text, &first_iso639_3);
 
-    let mut json: HashMap<&str, &str> = HashMap::new();
-    json.insert("text", text);
-    json.insert("iso639_3", second_iso639_3);
-
-    let client = reqwest::Client::new();
-    let response = client.post_sentence(&json);
-
-    response.assert_201();
+    let client = tests_commons::client();
+    client.create_sentence(&tests_commons::sentence(text, second_iso639_3)).unwrap();
 }
@@ -0,0 +1,90 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::Error;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_delete_unused_language_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+
+    let client = tests_commons::client();
+    let report = client.delete_language("eng", false).unwrap();
+
+    assert_eq!(report.sentences_removed, 0);
+
+    assert_eq!(client.get_language("eng").unwrap_err().status(), Some(404));
+}
+
+#[test]
+fn test_delete_used_language_without_cascade_returns_409() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+
+    match client.delete_language("eng", false) {
+        Err(Error::LanguageInUse(language)) => assert_eq!(language.sentences, 1),
+        other => panic!("expected the language to be in use, got {:?}", other),
+    }
+
+    connection.assert_language_exists("eng");
+    connection.assert_sentence_language_equals(&uuid, "eng");
+}
+
+#[test]
+fn test_delete_used_language_with_cascade_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let report = client.delete_language("eng", true).unwrap();
+
+    assert_eq!(report.sentences_removed, 1);
+
+    connection.assert_sentence_does_not_exist(&uuid);
+}
+
+#[test]
+fn test_delete_language_that_does_not_exist_returns_404() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let error = client.delete_language("eng", false).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_delete_language_by_iso639_1_code_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+
+    let client = tests_commons::client();
+    let report = client.delete_language("en", false).unwrap();
+
+    assert_eq!(report.iso639_3, "eng");
+
+    assert_eq!(client.get_language("eng").unwrap_err().status(), Some(404));
+}
@@ -0,0 +1,150 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::SentenceFilters;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_delete_sentence_returns_200_and_hides_it() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    let report = client.delete_sentence(&uuid, false).unwrap();
+
+    assert_eq!(report.id, uuid);
+    assert_eq!(report.purged, false);
+
+    connection.assert_sentence_is_deleted(&uuid, true);
+
+    assert_eq!(client.get_sentence(&uuid).unwrap_err().status(), Some(404));
+
+    let sentences = client.get_sentences(&SentenceFilters::default()).unwrap();
+
+    assert_eq!(sentences.len(), 0);
+}
+
+#[test]
+fn test_delete_sentence_that_does_not_exist_returns_404() {
+
+    let client = tests_commons::client();
+    let error = client.delete_sentence(&uuid::Uuid::new_v4(), false).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_purge_sentence_returns_200_and_removes_revisions() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    client.edit_sentence_text(&uuid, "This was one sentence.", None).unwrap();
+
+    let report = client.delete_sentence(&uuid, true).unwrap();
+
+    assert_eq!(report.purged, true);
+    assert_eq!(report.revisions_removed, 1);
+
+    connection.assert_sentence_does_not_exist(&uuid);
+}
+
+#[test]
+fn test_restore_sentence_returns_204() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    client.delete_sentence(&uuid, false).unwrap();
+
+    client.restore_sentence(&uuid).unwrap();
+
+    connection.assert_sentence_is_deleted(&uuid, false);
+
+    client.get_sentence(&uuid).unwrap();
+}
+
+#[test]
+fn test_post_sentence_with_content_of_deleted_sentence_restores_it() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let text = "This is one sentence.";
+    let uuid = connection.insert_sentence(&text, &iso639_3);
+
+    let client = tests_commons::client();
+    client.delete_sentence(&uuid, false).unwrap();
+
+    let created = client.create_sentence(&tests_commons::sentence(text, iso639_3)).unwrap();
+
+    assert_eq!(created.id, uuid);
+
+    connection.assert_sentence_is_deleted(&uuid, false);
+}
+
+#[test]
+fn test_restore_sentence_that_is_not_deleted_returns_404() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    let error = client.restore_sentence(&uuid).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_edit_sentence_with_content_of_deleted_sentence_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let text = "This is one sentence.";
+    let deleted_uuid = connection.insert_sentence(&text, &iso639_3);
+    let uuid = connection.insert_sentence("This is another sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    client.delete_sentence(&deleted_uuid, false).unwrap();
+
+    client.edit_sentence_text(&uuid, text, None).unwrap();
+
+    connection.assert_sentence_content_equals(&uuid, text);
+
+    let error = client.restore_sentence(&deleted_uuid).unwrap_err();
+
+    assert_eq!(error.status(), Some(409));
+}
@@ -0,0 +1,119 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::Error;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+const ENGLISH_SENTENCES: [&str; 12] = [
+    "The weather is very nice today.",
+    "I would like to drink a cup of tea.",
+    "She is reading a book in the garden.",
+    "Where is the nearest train station?",
+    "They have been working there for three years.",
+    "This house is bigger than the other one.",
+    "We are going to the beach this weekend.",
+    "He forgot his keys at the office.",
+    "The children are playing with the dog.",
+    "What time does the shop open in the morning?",
+    "My brother lives in a small village.",
+    "It is raining again, take your umbrella.",
+];
+
+const FRENCH_SENTENCES: [&str; 12] = [
+    "Il fait très beau aujourd'hui.",
+    "Je voudrais boire une tasse de thé.",
+    "Elle lit un livre dans le jardin.",
+    "Où est la gare la plus proche ?",
+    "Ils travaillent là depuis trois ans.",
+    "Cette maison est plus grande que l'autre.",
+    "Nous allons à la plage ce week-end.",
+    "Il a oublié ses clés au bureau.",
+    "Les enfants jouent avec le chien.",
+    "À quelle heure ouvre le magasin le matin ?",
+    "Mon frère habite dans un petit village.",
+    "Il pleut encore, prends ton parapluie.",
+];
+
+/// Stores enough english and french sentences to train the detector,
+/// and trains it.
+fn insert_corpus(connection: &Connection) {
+
+    connection.insert_language("eng");
+    connection.insert_language("fra");
+
+    for sentence in ENGLISH_SENTENCES.iter() {
+        connection.insert_sentence(sentence, "eng");
+    }
+    for sentence in FRENCH_SENTENCES.iter() {
+        connection.insert_sentence(sentence, "fra");
+    }
+
+    tests_commons::client().refresh_language_detection().unwrap();
+}
+
+#[test]
+fn test_detect_language_returns_ranked_candidates() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    insert_corpus(&connection);
+
+    let client = tests_commons::client();
+    let candidates = client.detect_language("Le chat dort sur le canapé de la maison.").unwrap();
+
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[0].iso639_3, "fra");
+    assert!(candidates[0].score > candidates[1].score);
+}
+
+#[test]
+fn test_detect_language_without_corpus_returns_no_candidate() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let candidates = client.detect_language("The cat is sleeping.").unwrap();
+
+    assert!(candidates.is_empty());
+}
+
+#[test]
+fn test_post_sentence_in_another_language_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    insert_corpus(&connection);
+
+    let sentence = tests_commons::sentence(
+        "Les enfants de mon frère jouent dans le jardin de la maison avec le chien du village.",
+        "eng",
+    );
+
+    let client = tests_commons::client();
+
+    match client.create_sentence(&sentence) {
+        Err(Error::LanguageMismatch(candidate)) => assert_eq!(candidate.iso639_3, "fra"),
+        other => panic!("expected a language mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_post_sentence_in_declared_language_returns_201_without_warning() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    insert_corpus(&connection);
+
+    let client = tests_commons::client();
+    let created = client
+        .create_sentence(&tests_commons::sentence("The dog is sleeping in the garden.", "eng"))
+        .unwrap();
+
+    assert!(created.detected_language.is_none());
+}
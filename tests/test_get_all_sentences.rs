@@ -1,21 +1,19 @@
 extern crate reqwest;
 extern crate uuid;
 extern crate postgres;
-extern crate interface_tests_helpers;
-
-#[macro_use] extern crate serde_derive;
+extern crate sentence_aligner_client;
 
 use reqwest::StatusCode;
+use reqwest::header::Headers;
 
-use postgres::Connection;
+use sentence_aligner_client::SentenceFilters;
+use sentence_aligner_client::models::Problem;
 
-use interface_tests_helpers::ResponseHandler;
+use db::Connection;
 
 mod db;
-mod handlers;
 
 use db::DatabaseHandler;
-use handlers::SentenceHandler;
 
 #[path = "../utils/tests_commons.rs"]
 mod tests_commons;
@@ -37,11 +35,9 @@ fn test_get_all_sentences_returns_200() {
     let second_text = "This is a second sentence";
     connection.insert_sentence(&second_text, &second_iso639_3);
 
-    let client = reqwest::Client::new();
-    let mut response = client.get_all_sentences();
-    response.assert_200();
+    let client = tests_commons::client();
+    let sentences = client.get_sentences(&SentenceFilters::default()).unwrap();
 
-    let sentences = response.json::<tests_commons::Sentences>().unwrap();
     assert_eq!(sentences.len(), 2);
 }
 
@@ -107,14 +103,78 @@ fn test_get_all_sentences_with_last_id_returns_200() {
         &first_iso639_3
     );
 
-    let client = reqwest::Client::new();
-    let mut response = client.get_all_sentences_with_last_uuid(&first_uuid);
-    response.assert_200();
-    let sentences = response.json::<tests_commons::Sentences>().unwrap();
+    let client = tests_commons::client();
+    let sentences = client.get_sentences(&SentenceFilters {
+        starting_after_id: Some(first_uuid),
+        ..SentenceFilters::default()
+    }).unwrap();
     assert_eq!(sentences.len(), 10);
 
-    let mut response = client.get_all_sentences_with_last_uuid(&sixth_uuid);
-    response.assert_200();
-    let sentences = response.json::<tests_commons::Sentences>().unwrap();
+    let sentences = client.get_sentences(&SentenceFilters {
+        starting_after_id: Some(sixth_uuid),
+        ..SentenceFilters::default()
+    }).unwrap();
     assert_eq!(sentences.len(), 5);
 }
+
+#[test]
+fn test_get_all_sentences_with_malformed_last_id_returns_problem() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    // the client only sends UUIDs, the malformed one is sent by hand
+    let mut headers = Headers::new();
+    headers.set_raw("Authorization", format!("Bearer {}", tests_commons::READER_KEY));
+
+    let mut response = reqwest::Client::new()
+        .get(&format!("{}/sentences?starting_after_id=not-a-uuid", tests_commons::SERVICE_URL))
+        .headers(headers)
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BadRequest);
+    assert_eq!(
+        response.headers().get_raw("Content-Type").unwrap(),
+        "application/problem+json",
+    );
+
+    let problem = response.json::<Problem>().unwrap();
+
+    assert_eq!(problem.code, "invalid-input");
+    assert_eq!(problem.problem_type, "urn:sentence-aligner:invalid-input");
+    assert_eq!(problem.status, 400);
+}
+
+#[test]
+fn test_get_all_sentences_with_limit_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    connection.insert_sentence("first sentence", "eng");
+    connection.insert_sentence("second sentence", "eng");
+    connection.insert_sentence("third sentence", "eng");
+
+    let client = tests_commons::client();
+    let sentences = client.get_sentences(&SentenceFilters {
+        limit: Some(2),
+        ..SentenceFilters::default()
+    }).unwrap();
+
+    assert_eq!(sentences.len(), 2);
+}
+
+#[test]
+fn test_get_all_sentences_with_out_of_bounds_limit_returns_400() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let error = client.get_sentences(&SentenceFilters {
+        limit: Some(0),
+        ..SentenceFilters::default()
+    }).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+    assert_eq!(error.code(), Some("invalid-input"));
+}
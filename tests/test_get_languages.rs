@@ -0,0 +1,78 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_get_all_languages_returns_200_with_sentence_counts() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+    connection.insert_language("fra");
+    connection.insert_sentence("This is one sentence.", "eng");
+    connection.insert_sentence("This is another sentence.", "eng");
+
+    let client = tests_commons::client();
+    let languages = client.get_languages().unwrap();
+
+    assert_eq!(languages.len(), 2);
+    assert_eq!(languages[0].iso639_3, "eng");
+    assert_eq!(
+        languages[0].info.as_ref().map(|info| info.name.as_str()),
+        Some("English"),
+    );
+    assert_eq!(languages[0].sentences, 2);
+    assert_eq!(languages[1].iso639_3, "fra");
+    assert_eq!(languages[1].sentences, 0);
+}
+
+#[test]
+fn test_get_language_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+    connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let language = client.get_language("eng").unwrap();
+
+    assert_eq!(language.iso639_3, "eng");
+    assert_eq!(language.sentences, 1);
+}
+
+#[test]
+fn test_get_language_that_does_not_exist_returns_404() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let error = client.get_language("eng").unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_get_language_by_other_code_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("fra");
+    connection.insert_sentence("Ceci est une phrase.", "fra");
+
+    let client = tests_commons::client();
+
+    assert_eq!(client.get_language("fr").unwrap().iso639_3, "fra");
+    assert_eq!(client.get_language("fre").unwrap().iso639_3, "fra");
+    assert_eq!(client.get_language("fr-CA").unwrap().iso639_3, "fra");
+    assert_eq!(client.get_sentences_of_language("fr", None, None).unwrap().len(), 1);
+}
@@ -1,21 +1,12 @@
 extern crate postgres;
-extern crate reqwest;
 extern crate uuid;
-extern crate interface_tests_helpers;
+extern crate sentence_aligner_client;
 
-#[macro_use] extern crate serde_derive;
-
-use reqwest::StatusCode;
-
-use postgres::Connection;
-
-use interface_tests_helpers::ResponseHandler;
+use db::Connection;
 
 mod db;
-mod handlers;
 
 use db::DatabaseHandler;
-use handlers::SentenceHandler;
 
 #[path = "../utils/tests_commons.rs"]
 mod tests_commons;
@@ -31,12 +22,8 @@ fn test_get_sentence_if_exists_returns_200() {
     let text = "This is one sentence";
     let uuid = connection.insert_sentence(&text, &iso639_3);
 
-    let client = reqwest::Client::new();
-    let mut response = client.get_sentence(&uuid);
-
-    response.assert_200();
-
-    let sentence = response.json::<tests_commons::Sentence>().unwrap();
+    let client = tests_commons::client();
+    let sentence = client.get_sentence(&uuid).unwrap().value;
 
     assert_eq!(
         sentence.text,
@@ -56,7 +43,9 @@ fn test_get_sentence_if_does_not_exist_returns_404() {
 
     let uuid_not_in_database = uuid::Uuid::new_v4();
 
-    let client = reqwest::Client::new();
-    let response = client.get_sentence(&uuid_not_in_database);
-    response.assert_404();
+    let client = tests_commons::client();
+    let error = client.get_sentence(&uuid_not_in_database).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+    assert_eq!(error.code(), Some("not-found"));
 }
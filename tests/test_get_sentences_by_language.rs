@@ -1,21 +1,12 @@
 extern crate postgres;
-extern crate reqwest;
 extern crate uuid;
-extern crate interface_tests_helpers;
+extern crate sentence_aligner_client;
 
-#[macro_use] extern crate serde_derive;
-
-use reqwest::StatusCode;
-
-use postgres::Connection;
-
-use interface_tests_helpers::ResponseHandler;
+use db::Connection;
 
 mod db;
-mod handlers;
 
 use db::DatabaseHandler;
-use handlers::SentenceHandler;
 
 #[path = "../utils/tests_commons.rs"]
 mod tests_commons;
@@ -40,12 +31,8 @@ fn test_get_sentence_by_language_returns_200() {
     let third_text = "Ceci est une phrase.";
     connection.insert_sentence(&third_text, &second_iso639_3);
 
-    let client = reqwest::Client::new();
-    let mut response = client.get_sentences_by_language(&first_iso639_3);
-
-    response.assert_200();
-
-    let sentences = response.json::<tests_commons::Sentences>().unwrap();
+    let client = tests_commons::client();
+    let sentences = client.get_sentences_of_language(&first_iso639_3, None, None).unwrap();
 
     assert_eq!(sentences.len(), 2);
 }
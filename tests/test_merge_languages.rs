@@ -0,0 +1,87 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_merge_language_moves_sentences_and_resolves_duplicates() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+    connection.insert_language("enm");
+
+    let moved_uuid = connection.insert_sentence("This is one sentence.", "enm");
+    let duplicated_uuid = connection.insert_sentence("This is a common sentence.", "enm");
+    let kept_uuid = connection.insert_sentence("This is a common sentence.", "eng");
+
+    let client = tests_commons::client();
+    let report = client.merge_language("enm", "eng").unwrap();
+
+    assert_eq!(report.moved, 1);
+    assert_eq!(report.duplicates.len(), 1);
+    assert_eq!(report.duplicates[0].removed, duplicated_uuid);
+    assert_eq!(report.duplicates[0].kept, kept_uuid);
+
+    connection.assert_sentence_language_equals(&moved_uuid, "eng");
+    connection.assert_sentence_does_not_exist(&duplicated_uuid);
+
+    assert_eq!(client.get_language("enm").unwrap_err().status(), Some(404));
+}
+
+#[test]
+fn test_merge_language_into_itself_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+
+    let client = tests_commons::client();
+    let error = client.merge_language("eng", "eng").unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_merge_language_that_does_not_exist_returns_404() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+
+    let client = tests_commons::client();
+    let error = client.merge_language("enm", "eng").unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_merge_language_by_other_codes_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("nob");
+    connection.insert_language("nor");
+
+    let moved_uuid = connection.insert_sentence("Dette er en setning.", "nob");
+
+    let client = tests_commons::client();
+    let report = client.merge_language("nb", "no").unwrap();
+
+    assert_eq!(report.from, "nob");
+    assert_eq!(report.into, "nor");
+    assert_eq!(report.moved, 1);
+
+    connection.assert_sentence_language_equals(&moved_uuid, "nor");
+
+    // the same language, given by two codes
+    assert_eq!(client.merge_language("nor", "no").unwrap_err().status(), Some(400));
+}
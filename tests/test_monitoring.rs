@@ -0,0 +1,65 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_health_returns_200() {
+
+    let client = tests_commons::client();
+
+    client.health().unwrap();
+}
+
+#[test]
+fn test_ready_returns_200() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+
+    client.ready().unwrap();
+}
+
+#[test]
+fn test_metrics_count_the_requests_by_route() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    client.get_sentence(&uuid).unwrap();
+
+    let metrics = client.metrics().unwrap();
+
+    assert!(metrics.lines().any(|line| {
+        line.starts_with(
+            "sentence_aligner_http_requests_total{method=\"GET\",route=\"/sentences/<sentence_uuid>\",status=\"200\"}"
+        )
+    }));
+    assert!(metrics.contains("sentence_aligner_http_request_duration_seconds_count"));
+}
+
+#[test]
+fn test_metrics_count_the_sentences_by_language() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    connection.insert_sentence("This is one sentence.", "eng");
+    connection.insert_sentence("This is another sentence.", "eng");
+
+    let client = tests_commons::client();
+    let metrics = client.metrics().unwrap();
+
+    assert!(metrics.lines().any(|line| line == "sentence_aligner_sentences{iso639_3=\"eng\"} 2"));
+    assert!(metrics.lines().any(|line| line == "sentence_aligner_database_up 1"));
+}
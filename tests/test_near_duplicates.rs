@@ -0,0 +1,97 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_get_near_duplicates_returns_groups_of_similar_sentences() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+
+    let first_uuid = connection.insert_sentence("This is a sentence.", "eng");
+    let second_uuid = connection.insert_sentence("this is a sentence", "eng");
+    let third_uuid = connection.insert_sentence("This is a sentense!", "eng");
+    connection.insert_sentence("Something totally different here.", "eng");
+
+    let client = tests_commons::client();
+    client.refresh_near_duplicates().unwrap();
+
+    let groups = client.get_near_duplicates("eng").unwrap();
+
+    assert_eq!(groups.len(), 1);
+
+    let uuids: Vec<uuid::Uuid> = groups[0].sentences
+        .iter()
+        .map(|sentence| sentence.id.unwrap())
+        .collect();
+
+    assert_eq!(uuids, vec![first_uuid, second_uuid, third_uuid]);
+}
+
+#[test]
+fn test_get_near_duplicates_of_unknown_language_returns_404() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let error = client.get_near_duplicates("eng").unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_merge_sentence_moves_structure_and_deletes_merged_sentence() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+
+    let merged_uuid = connection.insert_sentence("This is a sentence!", "eng");
+    connection.set_sentence_structure(
+        &merged_uuid,
+        "<sentence><subject>This</subject> is a sentence!</sentence>",
+    );
+    let kept_uuid = connection.insert_sentence("This is a sentence.", "eng");
+
+    let client = tests_commons::client();
+    let report = client.merge_sentence(&merged_uuid, &kept_uuid).unwrap();
+
+    assert_eq!(report.removed, merged_uuid);
+    assert_eq!(report.kept, kept_uuid);
+    assert!(report.structure_moved);
+
+    connection.assert_sentence_is_deleted(&merged_uuid, true);
+    connection.assert_sentence_structure_equals(
+        &kept_uuid,
+        "<sentence><subject>This</subject> is a sentence.</sentence>",
+    );
+}
+
+#[test]
+fn test_merge_sentence_of_another_language_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("eng");
+    connection.insert_language("fra");
+
+    let merged_uuid = connection.insert_sentence("Restaurant", "eng");
+    let kept_uuid = connection.insert_sentence("Restaurant", "fra");
+
+    let client = tests_commons::client();
+    let error = client.merge_sentence(&merged_uuid, &kept_uuid).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+
+    connection.assert_sentence_is_deleted(&merged_uuid, false);
+}
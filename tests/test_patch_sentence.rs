@@ -0,0 +1,155 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+#[macro_use] extern crate serde_json;
+
+use sentence_aligner_client::Error;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_patch_sentence_text_and_structure_returns_204() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+    connection.set_sentence_structure(
+        &uuid,
+        "<sentence><subject>This</subject> is one sentence.</sentence>",
+    );
+
+    let client = tests_commons::client();
+    client.patch_sentence(
+        &uuid,
+        &json!({
+            "text": "It was another sentence.",
+            "structure": "<sentence><subject>It</subject> <verb>was</verb> another sentence.</sentence>"
+        }),
+        None,
+    ).unwrap();
+
+    connection.assert_sentence_content_equals(&uuid, "It was another sentence.");
+    connection.assert_sentence_structure_equals(
+        &uuid,
+        "<sentence><subject>It</subject> <verb>was</verb> another sentence.</sentence>",
+    );
+}
+
+#[test]
+fn test_patch_sentence_text_only_reprojects_structure() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+    connection.set_sentence_structure(
+        &uuid,
+        "<sentence><subject>This</subject> <verb>is</verb> one sentence.</sentence>",
+    );
+
+    let client = tests_commons::client();
+    let edit = client.patch_sentence(&uuid, &json!({"text": "This was one sentence."}), None).unwrap();
+
+    assert_eq!(edit.structure_status, Some("reprojected".to_string()));
+
+    connection.assert_sentence_structure_equals(
+        &uuid,
+        "<sentence><subject>This</subject> <verb>was</verb> one sentence.</sentence>",
+    );
+}
+
+#[test]
+fn test_patch_sentence_structure_to_null_removes_it() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+    connection.set_sentence_structure(
+        &uuid,
+        "<sentence><subject>This</subject> is one sentence.</sentence>",
+    );
+
+    let client = tests_commons::client();
+    client.patch_sentence(&uuid, &json!({"structure": null}), None).unwrap();
+
+    connection.assert_sentence_structure_is_null(&uuid);
+}
+
+#[test]
+fn test_patch_sentence_structure_that_does_not_match_text_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let error = client.patch_sentence(
+        &uuid,
+        &json!({
+            "text": "It was another sentence.",
+            "structure": "<sentence><subject>This</subject> is one sentence.</sentence>"
+        }),
+        None,
+    ).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+
+    connection.assert_sentence_content_equals(&uuid, "This is one sentence.");
+    connection.assert_sentence_structure_is_null(&uuid);
+}
+
+#[test]
+fn test_patch_sentence_with_unknown_field_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let uuid = connection.insert_sentence("This is one sentence.", "eng");
+
+    let client = tests_commons::client();
+    let error = client.patch_sentence(&uuid, &json!({"id": "not a patchable field"}), None).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_patch_sentence_language_already_used_returns_409() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    connection.insert_language("fra");
+
+    let uuid = connection.insert_sentence("Restaurant", "eng");
+    let other_uuid = connection.insert_sentence("Restaurant", "fra");
+
+    let client = tests_commons::client();
+
+    match client.patch_sentence(&uuid, &json!({"iso639_3": "fra"}), None) {
+        Err(Error::Conflict(sentence)) => assert_eq!(sentence.id, Some(other_uuid)),
+        other => panic!("expected a conflict, got {:?}", other),
+    }
+
+    connection.assert_sentence_language_equals(&uuid, "eng");
+}
+
+#[test]
+fn test_patch_sentence_that_does_not_exist_returns_404() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let error = client.patch_sentence(&uuid::Uuid::new_v4(), &json!({"text": "Nothing."}), None).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
@@ -1,22 +1,14 @@
 extern crate postgres;
-extern crate reqwest;
 extern crate uuid;
-extern crate interface_tests_helpers;
+extern crate sentence_aligner_client;
 
-#[macro_use] extern crate serde_derive;
+use sentence_aligner_client::Error;
 
-use reqwest::StatusCode;
-use reqwest::header::ContentType;
-
-use postgres::Connection;
-
-use interface_tests_helpers::ResponseHandler;
+use db::Connection;
 
 mod db;
-mod handlers;
 
 use db::DatabaseHandler;
-use handlers::SentenceHandler;
 
 #[path = "../utils/tests_commons.rs"]
 mod tests_commons;
@@ -32,18 +24,76 @@ fn test_put_sentence_text_returns_204() {
 
     let modified_sentence = "This is a modified sentence.";
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_text(
+    let client = tests_commons::client();
+    client.edit_sentence_text(
         &uuid,
         &modified_sentence,
+        None,
+    ).unwrap();
+
+    connection.assert_sentence_content_equals(
+        &uuid,
+        &modified_sentence,
+    );
+}
+
+#[test]
+fn test_put_sentence_text_within_element_reprojects_structure() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+    connection.set_sentence_structure(
+        &uuid,
+        "<sentence><subject>This</subject> <verb>is</verb> one sentence.</sentence>",
+    );
+
+    let client = tests_commons::client();
+    let edit = client.edit_sentence_text(
+        &uuid,
+        "This was one sentence.",
+        None,
+    ).unwrap();
+
+    assert_eq!(edit.structure_status, Some("reprojected".to_string()));
+
+    connection.assert_sentence_structure_equals(
+        &uuid,
+        "<sentence><subject>This</subject> <verb>was</verb> one sentence.</sentence>",
+    );
+}
+
+#[test]
+fn test_put_sentence_text_across_elements_invalidates_structure() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+    connection.set_sentence_structure(
+        &uuid,
+        "<sentence><subject>This</subject> <verb>is</verb> one sentence.</sentence>",
     );
 
-    response.assert_204();
+    let client = tests_commons::client();
+    let edit = client.edit_sentence_text(
+        &uuid,
+        "It one sentence.",
+        None,
+    ).unwrap();
+
+    assert_eq!(edit.structure_status, Some("invalidated".to_string()));
 
     connection.assert_sentence_content_equals(
         &uuid,
-        &modified_sentence,
+        "It one sentence.",
     );
+    connection.assert_sentence_structure_is_null(&uuid);
 }
 
 #[test]
@@ -60,30 +110,28 @@ fn test_put_sentence_text_if_text_already_used_returns_409() {
     let second_text = "This is the second sentence content.";
     connection.insert_sentence(&second_text, &iso_639_3);
 
-    let client = reqwest::Client::new();
-    let mut response = client.update_sentence_text(
-        &first_uuid,
-        &second_text,
-    );
-
-    let sentence = response.json::<tests_commons::Sentence>().unwrap();
+    let client = tests_commons::client();
 
-    assert_eq!(sentence.text, second_text);
-    assert_eq!(sentence.iso639_3, iso_639_3);
-
-    response.assert_409();
+    match client.edit_sentence_text(&first_uuid, &second_text, None) {
+        Err(Error::Conflict(sentence)) => {
+            assert_eq!(sentence.text, second_text);
+            assert_eq!(sentence.iso639_3, iso_639_3);
+        },
+        other => panic!("expected a conflict, got {:?}", other),
+    }
 }
 
 #[test]
 fn test_put_sentence_text_that_does_not_exist_returns_404() {
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_text(
+    let client = tests_commons::client();
+    let error = client.edit_sentence_text(
         &uuid::Uuid::new_v4(),
         "This is a sentence",
-    );
+        None,
+    ).unwrap_err();
 
-    response.assert_404();
+    assert_eq!(error.status(), Some(404));
 }
 
 #[test]
@@ -100,13 +148,12 @@ fn test_put_sentence_language_returns_204() {
     let second_iso639_3 = "fra";
     connection.insert_language(&second_iso639_3);
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_language(
+    let client = tests_commons::client();
+    client.edit_sentence_language(
         &uuid,
         &second_iso639_3,
-    );
-
-    response.assert_204();
+        None,
+    ).unwrap();
 
     connection.assert_sentence_language_equals(
         &uuid,
@@ -117,13 +164,14 @@ fn test_put_sentence_language_returns_204() {
 #[test]
 fn test_put_sentence_language_that_does_not_exist_returns_404() {
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_language(
+    let client = tests_commons::client();
+    let error = client.edit_sentence_language(
         &uuid::Uuid::new_v4(),
         "fra",
-    );
+        None,
+    ).unwrap_err();
 
-    response.assert_404();
+    assert_eq!(error.status(), Some(404));
 }
 
 #[test]
@@ -138,13 +186,12 @@ fn test_put_sentence_structure_returns_204() {
 
     let modified_structure = "<sentence><subject>This</subject> <verb>is</verb> <complement>one</complement> <complement>sentence.</complement></sentence>";
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_structure(
+    let client = tests_commons::client();
+    client.edit_sentence_structure(
         &uuid,
         &modified_structure,
-    );
-
-    response.assert_204();
+        None,
+    ).unwrap();
 
     connection.assert_sentence_structure_equals(
         &uuid,
@@ -155,13 +202,14 @@ fn test_put_sentence_structure_returns_204() {
 #[test]
 fn test_put_sentence_structure_that_does_not_exist_returns_404() {
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_structure(
+    let client = tests_commons::client();
+    let error = client.edit_sentence_structure(
         &uuid::Uuid::new_v4(),
         "<sentence><subject>This</subject> <verb>is</verb> <complement>one</complement> <complement>sentence</complement></sentence>",
-    );
+        None,
+    ).unwrap_err();
 
-    response.assert_404();
+    assert_eq!(error.status(), Some(404));
 }
 
 #[test]
@@ -173,13 +221,14 @@ fn test_put_sentence_structure_that_does_not_match_content_returns_400() {
     let iso639_3 = "eng";
     let uuid = connection.insert_sentence(&text, &iso639_3);
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_structure(
+    let client = tests_commons::client();
+    let error = client.edit_sentence_structure(
         &uuid,
         "<sentence><subject>I</subject> <verb>eat</verb> <complement>apple</complement></sentence>",
-    );
+        None,
+    ).unwrap_err();
 
-    response.assert_400();
+    assert_eq!(error.status(), Some(400));
 }
 
 #[test]
@@ -192,13 +241,12 @@ fn test_put_sentence_structure_with_untagged_words_returns_204() {
     let uuid = connection.insert_sentence(&text, &iso639_3);
     let modified_structure = "<sentence><subject>This</subject> <verb>is</verb> one sentence.</sentence>";
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_structure(
+    let client = tests_commons::client();
+    client.edit_sentence_structure(
         &uuid,
         &modified_structure,
-    );
-
-    response.assert_204();
+        None,
+    ).unwrap();
 
     connection.assert_sentence_structure_equals(
         &uuid,
@@ -215,13 +263,14 @@ fn test_put_sentence_structure_with_spaces_that_do_not_match_content() {
     let iso639_3 = "eng";
     let uuid = connection.insert_sentence(&text, &iso639_3);
 
-    let client = reqwest::Client::new();
-    let response = client.update_sentence_structure(
+    let client = tests_commons::client();
+    let error = client.edit_sentence_structure(
         &uuid,
         "<sentence><subject>This</subject><verb>is</verb><complement>one</complement><complement>sentence</complement></sentence>",
-    );
+        None,
+    ).unwrap_err();
 
-    response.assert_400();
+    assert_eq!(error.status(), Some(400));
 }
 
 #[test]
@@ -247,16 +296,13 @@ fn test_put_sentence_language_if_language_already_used_returns_409() {
         &second_language,
     );
 
-    let client = reqwest::Client::new();
-    let mut response = client.update_sentence_language(
-        &first_sentence_uuid,
-        &second_language,
-    );
-
-    let sentence = response.json::<tests_commons::Sentence>().unwrap();
-
-    assert_eq!(sentence.text, common_text);
-    assert_eq!(sentence.iso639_3, second_language);
+    let client = tests_commons::client();
 
-    response.assert_409();
+    match client.edit_sentence_language(&first_sentence_uuid, &second_language, None) {
+        Err(Error::Conflict(sentence)) => {
+            assert_eq!(sentence.text, common_text);
+            assert_eq!(sentence.iso639_3, second_language);
+        },
+        other => panic!("expected a conflict, got {:?}", other),
+    }
 }
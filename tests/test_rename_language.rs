@@ -0,0 +1,72 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_rename_language_moves_sentences() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("nor");
+
+    let moved_uuid = connection.insert_sentence("Dette er en setning.", "nor");
+
+    let client = tests_commons::client();
+    let language = client.rename_language("nor", "nb").unwrap();
+
+    assert_eq!(language.iso639_3, "nob");
+    assert_eq!(language.sentences, 1);
+
+    connection.assert_sentence_language_equals(&moved_uuid, "nob");
+    assert_eq!(client.get_language("nor").unwrap_err().status(), Some(404));
+
+    let history = client.get_sentence_history(&moved_uuid).unwrap();
+    assert!(history.iter().any(|revision| revision.new.iso639_3 == Some("nob".to_string())));
+}
+
+#[test]
+fn test_rename_language_to_used_code_returns_409() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("nor");
+    connection.insert_language("nob");
+
+    let client = tests_commons::client();
+    let error = client.rename_language("nor", "nob").unwrap_err();
+
+    assert_eq!(error.status(), Some(409));
+}
+
+#[test]
+fn test_rename_language_to_unknown_code_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("nor");
+
+    let client = tests_commons::client();
+    let error = client.rename_language("nor", "xyz").unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_rename_language_that_does_not_exist_returns_404() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let client = tests_commons::client();
+    let error = client.rename_language("nor", "nob").unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
@@ -0,0 +1,81 @@
+extern crate reqwest;
+extern crate uuid;
+extern crate postgres;
+extern crate sentence_aligner_client;
+
+use reqwest::StatusCode;
+use reqwest::header::Headers;
+
+use sentence_aligner_client::models::Problem;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+/// Gets a sentence that does not exist, with the given request id if any.
+fn get_missing_sentence(request_id: Option<&str>) -> reqwest::Response {
+
+    let url = format!("{}/sentences/{}", tests_commons::SERVICE_URL, uuid::Uuid::new_v4());
+
+    let mut headers = Headers::new();
+    headers.set_raw("Authorization", format!("Bearer {}", tests_commons::READER_KEY));
+    if let Some(request_id) = request_id {
+        headers.set_raw("X-Request-Id", request_id.to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let mut request = client.get(&url);
+    request.headers(headers);
+
+    request.send().unwrap()
+}
+
+/// Returns the `X-Request-Id` of a response.
+fn request_id(response: &reqwest::Response) -> String {
+    let header = response.headers().get_raw("X-Request-Id").unwrap().one().unwrap();
+    String::from_utf8(header.to_vec()).unwrap()
+}
+
+#[test]
+fn test_request_id_is_sent_back_in_the_header_and_the_problem() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let mut response = get_missing_sentence(Some("client-42.retry:1"));
+
+    assert_eq!(response.status(), StatusCode::NotFound);
+    assert_eq!(request_id(&response), "client-42.retry:1");
+
+    let problem = response.json::<Problem>().unwrap();
+    assert_eq!(problem.request_id, Some("client-42.retry:1".to_string()));
+}
+
+#[test]
+fn test_request_id_is_generated_when_not_given() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let mut response = get_missing_sentence(None);
+
+    let id = request_id(&response);
+    assert!(uuid::Uuid::parse_str(&id).is_ok());
+
+    let problem = response.json::<Problem>().unwrap();
+    assert_eq!(problem.request_id, Some(id));
+}
+
+#[test]
+fn test_request_id_with_spaces_is_replaced() {
+
+    let _connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let response = get_missing_sentence(Some("not an id"));
+
+    let id = request_id(&response);
+    assert!(uuid::Uuid::parse_str(&id).is_ok());
+}
@@ -0,0 +1,244 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::Client;
+use sentence_aligner_client::models::{NewReview, ReviewStatus, ReviewSubject};
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+fn contributor() -> Client {
+    tests_commons::client_with_key(tests_commons::CONTRIBUTOR_KEY)
+}
+
+fn reviewer() -> Client {
+    tests_commons::client_with_key(tests_commons::REVIEWER_KEY)
+}
+
+/// Returns a review of a sentence text.
+///
+/// Args:
+///
+/// `status` - the new status of the sentence
+/// `comment` - the comment of the reviewer
+fn review(status: ReviewStatus, comment: Option<&str>) -> NewReview {
+    NewReview {
+        subject: ReviewSubject::Sentence,
+        status: status,
+        comment: comment.map(|comment| comment.to_string()),
+    }
+}
+
+#[test]
+fn test_created_sentence_is_pending_review() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    let sentence = tests_commons::client().get_sentence(&created.id).unwrap().value;
+
+    assert_eq!(sentence.review_status, ReviewStatus::PendingReview);
+    assert_eq!(sentence.structure_review_status, None);
+
+    let queue = reviewer().get_review_queue(None).unwrap();
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].id, Some(created.id));
+}
+
+#[test]
+fn test_create_approved_sentence_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let mut sentence = tests_commons::sentence("Hello.", "eng");
+    sentence.review_status = ReviewStatus::Approved;
+
+    let error = contributor().create_sentence(&sentence).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_draft_is_only_queued_once_submitted() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let mut sentence = tests_commons::sentence("Hello.", "eng");
+    sentence.review_status = ReviewStatus::Draft;
+    sentence.structure = Some("<sentence>Hello.</sentence>".to_string());
+
+    let client = contributor();
+    let created = client.create_sentence(&sentence).unwrap();
+
+    let draft = client.get_sentence(&created.id).unwrap().value;
+    assert_eq!(draft.review_status, ReviewStatus::Draft);
+    assert_eq!(draft.structure_review_status, Some(ReviewStatus::Draft));
+    assert!(reviewer().get_review_queue(None).unwrap().is_empty());
+
+    client.submit_sentence(&created.id).unwrap();
+
+    let submitted = client.get_sentence(&created.id).unwrap().value;
+    assert_eq!(submitted.review_status, ReviewStatus::PendingReview);
+    assert_eq!(submitted.structure_review_status, Some(ReviewStatus::PendingReview));
+    assert_eq!(reviewer().get_review_queue(None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_review_sentence_records_status_and_comment() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+    let etag = tests_commons::client().get_sentence(&created.id).unwrap().etag;
+
+    let client = reviewer();
+    let recorded = client
+        .review_sentence(&created.id, &review(ReviewStatus::Approved, Some("  Looks right.  ")))
+        .unwrap();
+
+    assert_eq!(recorded.status, ReviewStatus::Approved);
+    assert_eq!(recorded.comment, Some("Looks right.".to_string()));
+    assert_eq!(recorded.reviewer, Some("reviewer".to_string()));
+
+    let sentence = client.get_sentence(&created.id).unwrap();
+    assert_eq!(sentence.value.review_status, ReviewStatus::Approved);
+    assert!(sentence.etag != etag);
+
+    let reviews = client.get_sentence_reviews(&created.id).unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].id, recorded.id);
+    assert_eq!(reviews[0].subject, ReviewSubject::Sentence);
+    assert_eq!(reviews[0].comment, Some("Looks right.".to_string()));
+
+    assert!(client.get_review_queue(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_edited_approved_sentence_is_pending_review_again() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+    reviewer().review_sentence(&created.id, &review(ReviewStatus::Approved, None)).unwrap();
+
+    contributor().edit_sentence_text(&created.id, "Hello!", None).unwrap();
+
+    let sentence = tests_commons::client().get_sentence(&created.id).unwrap().value;
+
+    assert_eq!(sentence.review_status, ReviewStatus::PendingReview);
+}
+
+#[test]
+fn test_reject_sentence_without_comment_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    let error = reviewer()
+        .review_sentence(&created.id, &review(ReviewStatus::Rejected, Some(" ")))
+        .unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_review_missing_structure_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let created = contributor().create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    let mut structure_review = review(ReviewStatus::Approved, None);
+    structure_review.subject = ReviewSubject::Structure;
+
+    let error = reviewer().review_sentence(&created.id, &structure_review).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_review_as_contributor_returns_403() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+
+    let client = contributor();
+    let created = client.create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+
+    let error = client.review_sentence(&created.id, &review(ReviewStatus::Approved, None)).unwrap_err();
+
+    assert_eq!(error.status(), Some(403));
+}
+
+#[test]
+fn test_review_queue_filters_by_language() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    connection.insert_language("fra");
+
+    let client = contributor();
+    client.create_sentence(&tests_commons::sentence("Hello.", "eng")).unwrap();
+    let french = client.create_sentence(&tests_commons::sentence("Bonjour.", "fra")).unwrap();
+
+    let queue = reviewer().get_review_queue(Some("fra")).unwrap();
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].id, Some(french.id));
+}
+
+#[test]
+fn test_review_queue_filters_by_iso639_1_code() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("fra");
+
+    let french = contributor().create_sentence(&tests_commons::sentence("Bonjour.", "fra")).unwrap();
+
+    let queue = reviewer().get_review_queue(Some("fr")).unwrap();
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].id, Some(french.id));
+}
+
+#[test]
+fn test_merged_language_sentences_are_pending_review_again() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+    connection.insert_language("eng");
+    connection.insert_language("enm");
+
+    let approved = contributor().create_sentence(&tests_commons::sentence("Hello.", "enm")).unwrap();
+    reviewer().review_sentence(&approved.id, &review(ReviewStatus::Approved, None)).unwrap();
+
+    let mut sentence = tests_commons::sentence("Hi.", "enm");
+    sentence.review_status = ReviewStatus::Draft;
+    let draft = contributor().create_sentence(&sentence).unwrap();
+
+    tests_commons::client().merge_language("enm", "eng").unwrap();
+
+    let client = reviewer();
+    assert_eq!(client.get_sentence(&approved.id).unwrap().value.review_status, ReviewStatus::PendingReview);
+    assert_eq!(client.get_sentence(&draft.id).unwrap().value.review_status, ReviewStatus::Draft);
+
+    let queue = client.get_review_queue(Some("eng")).unwrap();
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].id, Some(approved.id));
+}
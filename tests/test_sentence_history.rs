@@ -0,0 +1,112 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use sentence_aligner_client::Error;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_get_sentence_history_returns_200() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    client.edit_sentence_text(&uuid, "This was one sentence.", None).unwrap();
+
+    let revisions = client.get_sentence_history(&uuid).unwrap();
+
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].old.text, "This is one sentence.");
+    assert_eq!(revisions[0].new.text, "This was one sentence.");
+    assert_eq!(revisions[0].text_diff, "This [-i-]{+wa+}s one sentence.");
+    assert_eq!(revisions[0].structure_diff, None);
+}
+
+#[test]
+fn test_get_sentence_history_that_does_not_exist_returns_404() {
+
+    let client = tests_commons::client();
+    let error = client.get_sentence_history(&uuid::Uuid::new_v4()).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
+
+#[test]
+fn test_revert_sentence_returns_204() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    client.edit_sentence_text(&uuid, "This was one sentence.", None).unwrap();
+    client.edit_sentence_text(&uuid, "This was one long sentence.", None).unwrap();
+
+    let revisions = client.get_sentence_history(&uuid).unwrap();
+
+    client.revert_sentence(&uuid, revisions[0].id).unwrap();
+
+    connection.assert_sentence_content_equals(
+        &uuid,
+        "This is one sentence.",
+    );
+
+    let revisions = client.get_sentence_history(&uuid).unwrap();
+
+    assert_eq!(revisions.len(), 3, "the revert must be recorded as a revision.");
+}
+
+#[test]
+fn test_revert_sentence_to_already_used_content_returns_409() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    client.edit_sentence_text(&uuid, "This was one sentence.", None).unwrap();
+
+    connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let revisions = client.get_sentence_history(&uuid).unwrap();
+
+    match client.revert_sentence(&uuid, revisions[0].id) {
+        Err(Error::Conflict(sentence)) => assert_eq!(sentence.text, "This is one sentence."),
+        other => panic!("expected a conflict, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_revert_sentence_with_unknown_revision_returns_404() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    let iso639_3 = "eng";
+    connection.insert_language(&iso639_3);
+
+    let uuid = connection.insert_sentence("This is one sentence.", &iso639_3);
+
+    let client = tests_commons::client();
+    let error = client.revert_sentence(&uuid, 0).unwrap_err();
+
+    assert_eq!(error.status(), Some(404));
+}
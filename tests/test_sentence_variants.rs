@@ -0,0 +1,106 @@
+extern crate postgres;
+extern crate uuid;
+extern crate sentence_aligner_client;
+
+use db::Connection;
+
+mod db;
+
+use db::DatabaseHandler;
+
+#[path = "../utils/tests_commons.rs"]
+mod tests_commons;
+
+#[test]
+fn test_post_sentence_with_language_tag_stores_script_and_region() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("srp");
+
+    let uuid = uuid::Uuid::new_v4();
+
+    let mut sentence = tests_commons::sentence("Ovo je rečenica.", "sr-latn-rs");
+    sentence.id = Some(uuid);
+
+    let client = tests_commons::client();
+    client.create_sentence(&sentence).unwrap();
+
+    let sentence = client.get_sentence(&uuid).unwrap().value;
+
+    assert_eq!(sentence.iso639_3, "srp");
+    assert_eq!(sentence.script, Some("Latn".to_string()));
+    assert_eq!(sentence.region, Some("RS".to_string()));
+}
+
+#[test]
+fn test_post_same_sentence_in_another_script_returns_201() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("zho");
+
+    let mut sentence = tests_commons::sentence("中文", "zho");
+    sentence.script = Some("Hans".to_string());
+
+    let client = tests_commons::client();
+    client.create_sentence(&sentence).unwrap();
+
+    sentence.script = Some("Hant".to_string());
+    client.create_sentence(&sentence).unwrap();
+
+    assert_eq!(client.create_sentence(&sentence).unwrap_err().status(), Some(409));
+}
+
+#[test]
+fn test_post_sentence_with_unknown_script_returns_400() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("srp");
+
+    let mut sentence = tests_commons::sentence("Ovo je rečenica.", "srp");
+    sentence.script = Some("Xxxx".to_string());
+
+    let client = tests_commons::client();
+    let error = client.create_sentence(&sentence).unwrap_err();
+
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_get_sentences_by_language_and_script_returns_only_this_script() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("srp");
+
+    let client = tests_commons::client();
+
+    client.create_sentence(&tests_commons::sentence("Ovo je rečenica.", "sr-Latn")).unwrap();
+    client.create_sentence(&tests_commons::sentence("Ово је реченица.", "sr-Cyrl")).unwrap();
+
+    let sentences = client.get_sentences_of_language("srp", Some("cyrl"), None).unwrap();
+
+    assert_eq!(sentences.len(), 1);
+    assert_eq!(sentences[0].text, "Ово је реченица.");
+}
+
+#[test]
+fn test_put_sentence_language_tag_updates_script() {
+
+    let connection: Connection = DatabaseHandler::connect_and_clean();
+
+    connection.insert_language("srp");
+
+    let uuid = connection.insert_sentence("Ovo je rečenica.", "srp");
+
+    let client = tests_commons::client();
+    client.edit_sentence_language(&uuid, "sr-Latn", None).unwrap();
+
+    let sentence = client.get_sentence(&uuid).unwrap().value;
+
+    assert_eq!(sentence.iso639_3, "srp");
+    assert_eq!(sentence.script, Some("Latn".to_string()));
+    assert_eq!(sentence.region, None);
+}
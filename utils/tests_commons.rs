@@ -1,15 +1,49 @@
 //! Common items for interface tests only.
 
-extern crate uuid;
+#![allow(dead_code)]
+
+use sentence_aligner_client::Client;
+use sentence_aligner_client::models::{ReviewStatus, Sentence};
 
 pub const SERVICE_URL: &str = "http://localhost:8000";
 
-pub type Sentences = Vec<Sentence>;
+/// API keys of each role, stored by `DatabaseHandler::connect_and_clean`.
+pub const READER_KEY: &str = "test-reader-key";
+pub const CONTRIBUTOR_KEY: &str = "test-contributor-key";
+pub const REVIEWER_KEY: &str = "test-reviewer-key";
+pub const ADMIN_KEY: &str = "test-admin-key";
+
+/// Returns a client of the tested server, allowed to do anything.
+pub fn client() -> Client {
+    client_with_key(ADMIN_KEY)
+}
+
+/// Returns a client of the tested server, authenticated by the given key.
+///
+/// Args:
+///
+/// `api_key` - the API key
+pub fn client_with_key(api_key: &str) -> Client {
+    Client::new(SERVICE_URL).with_api_key(api_key)
+}
 
-#[derive(Deserialize)]
-pub struct Sentence {
-    pub id: Option<uuid::Uuid>,
-    pub text: String,
-    pub iso639_3: String,
-    pub structure: Option<String>,
+/// Returns a new sentence without id, structure nor variant.
+///
+/// Args:
+///
+/// `text` - the text of the sentence
+/// `iso639_3` - the language of the sentence
+pub fn sentence(text: &str, iso639_3: &str) -> Sentence {
+    Sentence {
+        id: None,
+        text: text.to_string(),
+        iso639_3: iso639_3.to_string(),
+        structure: None,
+        script: None,
+        region: None,
+        created_by: None,
+        updated_by: None,
+        review_status: ReviewStatus::PendingReview,
+        structure_review_status: None,
+    }
 }